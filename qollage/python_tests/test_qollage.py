@@ -11,9 +11,12 @@
 # the License.
 
 import pytest  # type: ignore
+import os
 import sys
-from qollage import draw_circuit, save_circuit, circuit_to_typst_str
+import numpy as np
+from qollage import draw_circuit, save_circuit, save_circuits, circuit_to_typst_str, circuit_to_text_str, circuit_to_quantikz_str, draw_device, device_to_typst_str, draw_circuit_timing, circuit_to_timing_typst_str, draw_circuits_diff, circuits_diff_to_typst_str, circuit_statistics, RenderOptions, QollageError, UnsupportedOperationError, InvalidOptionError
 from qoqo import Circuit, operations as ops  # type: ignore
+from qoqo.devices import SquareLatticeDevice  # type: ignore
 
 
 def test_simple_draw() -> None:
@@ -24,8 +27,220 @@ def test_simple_draw() -> None:
     
     draw_circuit(circuit)
     typst_str = circuit_to_typst_str(circuit)
+    text_str = circuit_to_text_str(circuit, charset="ascii")
+    assert text_str.isascii()
+    latex_str = circuit_to_quantikz_str(circuit)
+    assert latex_str.startswith("\\begin{quantikz}")
     save_circuit(circuit)
 
 
+def test_device_draw() -> None:
+    """Test drawing a device"""
+    device = SquareLatticeDevice(2, 2, ["RotateZ"], ["CNOT"], 1.0)
+
+    draw_device(device)
+    typst_str = device_to_typst_str(device)
+    assert "Qubits: 4" in typst_str
+
+    circuit = Circuit()
+    circuit += ops.CNOT(0, 3)
+    typst_str = circuit_to_typst_str(circuit, options=RenderOptions(device=device))
+    assert "Device violations: 1" in typst_str
+    save_circuit(
+        circuit, "device_circuit.svg", options=RenderOptions(device=device, statistics=True)
+    )
+    os.remove("device_circuit.svg")
+
+
+def test_circuit_timing() -> None:
+    """Test drawing a circuit on a time axis"""
+    device = SquareLatticeDevice(1, 2, ["RotateZ"], ["CNOT"], 1.0)
+    circuit = Circuit()
+    circuit += ops.RotateZ(0, 0.5)
+    circuit += ops.CNOT(0, 1)
+    circuit += ops.PragmaSleep([1], 2.0)
+    circuit += ops.Hadamard(1)
+
+    draw_circuit_timing(circuit, device=device, durations={"Hadamard": 0.5})
+    typst_str = circuit_to_timing_typst_str(circuit, default_duration=1.0)
+    assert "Time" in typst_str
+
+
+def test_circuits_diff() -> None:
+    """Test drawing the differences between two circuits"""
+    before = Circuit()
+    before += ops.Hadamard(0)
+    before += ops.RotateX(1, 0.3)
+    after = Circuit()
+    after += ops.Hadamard(0)
+    after += ops.RotateX(1, 0.5)
+    after += ops.CNOT(0, 1)
+
+    draw_circuits_diff(before, after)
+    typst_str = circuits_diff_to_typst_str(before, after)
+    assert "added: 1" in typst_str
+    assert "changed: 1" in typst_str
+
+
+def test_circuit_statistics() -> None:
+    """Test the statistics of a circuit"""
+    circuit = Circuit()
+    circuit += ops.Hadamard(0)
+    circuit += ops.CNOT(0, 1)
+    circuit += ops.RotateZ(1, "theta")
+    circuit += ops.PragmaSleep([1], 1.0)
+
+    statistics = circuit_statistics(circuit, render_pragmas="none")
+    assert statistics["number_qubits"] == 2
+    assert statistics["gate_counts"] == {"CNOT": 1, "Hadamard": 1, "RotateZ": 1}
+    assert statistics["symbolic_parameters"] == ["theta"]
+    assert statistics["hidden_pragmas"] == {"PragmaSleep": 1}
+
+    typst_str = circuit_to_typst_str(circuit, options=RenderOptions(statistics=True))
+    assert "Circuit statistics" in typst_str
+
+
+# The analog operations are only built into qoqo with its unstable_analog_operations feature.
+requires_analog_operations = pytest.mark.skipif(
+    not hasattr(ops, "ApplyConstantPauliHamiltonian"),
+    reason="qoqo is built without the analog operations",
+)
+
+
+def constant_hamiltonian() -> "ops.ApplyConstantPauliHamiltonian":
+    """Returns an operation the backend doesn't draw"""
+    from struqture_py.spins import PauliHamiltonian  # type: ignore
+
+    hamiltonian = PauliHamiltonian()
+    hamiltonian.add_operator_product("0Z1X", 1.0)
+    return ops.ApplyConstantPauliHamiltonian(hamiltonian, 1.0)
+
+
+@requires_analog_operations
+def test_lenient() -> None:
+    """Test drawing unsupported operations as placeholders"""
+    circuit = Circuit()
+    circuit += ops.Hadamard(0)
+    circuit += constant_hamiltonian()
+
+    with pytest.raises(ValueError):
+        circuit_to_typst_str(circuit)
+    with pytest.warns(UserWarning, match="ApplyConstantPauliHamiltonian"):
+        typst_str = circuit_to_typst_str(circuit, lenient=True)
+    assert "ApplyConstantPauliHamiltonian" in typst_str
+    with pytest.warns(UserWarning, match="ApplyConstantPauliHamiltonian"):
+        save_circuit(circuit, "lenient_circuit.svg", lenient=True)
+    with pytest.warns(UserWarning, match="ApplyConstantPauliHamiltonian"):
+        assert save_circuits([circuit], ["lenient_circuit.png"], lenient=True) == [None]
+    for path in ["lenient_circuit.svg", "lenient_circuit.png"]:
+        os.remove(path)
+
+
+@requires_analog_operations
+def test_exceptions() -> None:
+    """Test the exceptions raised for each kind of error"""
+    circuit = Circuit()
+    circuit += ops.Hadamard(0)
+    circuit += constant_hamiltonian()
+
+    with pytest.raises(UnsupportedOperationError) as error:
+        circuit_to_typst_str(circuit)
+    assert isinstance(error.value, QollageError)
+    assert error.value.index == 1
+    with pytest.raises(InvalidOptionError):
+        RenderOptions(layout_mode="diagonal")
+
+
+def test_float_style() -> None:
+    """Test the styles of the displayed floats"""
+    circuit = Circuit()
+    circuit += ops.RotateX(0, 5 * 3.141592653589793 / 8)
+
+    typst_str = circuit_to_typst_str(circuit)
+    assert "(5pi)/8" in typst_str
+    typst_str = circuit_to_typst_str(circuit, options=RenderOptions(float_style="degrees"))
+    assert "112.5°" in typst_str
+    typst_str = circuit_to_typst_str(circuit, options=RenderOptions(float_style="decimal"))
+    assert "1.963" in typst_str
+    typst_str = circuit_to_typst_str(circuit, options=RenderOptions(max_denominator=4))
+    assert "(5pi)/8" not in typst_str
+    with pytest.raises(InvalidOptionError):
+        RenderOptions(float_style="roman")
+
+
+def test_angle_unit() -> None:
+    """Test the units of the displayed rotation angles"""
+    circuit = Circuit()
+    circuit += ops.RotateX(0, 3.141592653589793 / 2)
+    circuit += ops.PMInteraction(0, 1, 3.141592653589793 / 2)
+
+    typst_str = circuit_to_typst_str(circuit, options=RenderOptions(angle_unit="degrees"))
+    assert "90°" in typst_str
+    assert '"PMInteraction"(pi/2)' in typst_str
+    text_str = circuit_to_text_str(circuit, options=RenderOptions(angle_unit="turns"))
+    assert "Rx(1/4 tr)" in text_str
+    with pytest.raises(InvalidOptionError):
+        RenderOptions(angle_unit="gradians")
+
+
+def test_set_state() -> None:
+    """Test drawing the states set by pragmas"""
+    circuit = Circuit()
+    circuit += ops.Hadamard(0)
+    circuit += ops.PragmaSetStateVector(np.array([0.0, 0.6, 0.0, -0.8], dtype=complex))
+    circuit += ops.PragmaSetDensityMatrix(np.eye(4, dtype=complex) / 4)
+
+    typst_str = circuit_to_typst_str(circuit)
+    assert "0.6 |01> - 0.8 |11>" in typst_str
+    assert "mat(0.25, 0, 0, 0;" in typst_str
+    typst_str = circuit_to_typst_str(circuit, options=RenderOptions(max_state_size=1))
+    assert "0.6 |01> + dots" in typst_str
+    assert "mat(0.25, dots.h; dots.v, dots.down)" in typst_str
+
+
+def test_complex_style() -> None:
+    """Test the styles of the displayed complex numbers"""
+    circuit = Circuit()
+    circuit += ops.Bogoliubov(0, 1, 0.5, -0.5)
+
+    typst_str = circuit_to_typst_str(circuit)
+    assert '"Bogoliubov"(0.5 - 0.5 i)' in typst_str
+    typst_str = circuit_to_typst_str(circuit, options=RenderOptions(complex_style="polar"))
+    assert '"Bogoliubov"(1/sqrt(2) dot e^(-i pi/4))' in typst_str
+    with pytest.raises(InvalidOptionError):
+        RenderOptions(complex_style="exponential")
+
+
+def test_wire_labels() -> None:
+    """Test the custom labels of the wires"""
+    circuit = Circuit()
+    circuit += ops.DefinitionBit("ro", 2, True)
+    circuit += ops.CNOT(0, 1)
+    circuit += ops.Squeezing(0, 0.1, 0.0)
+    circuit += ops.MeasureQubit(1, "ro", 0)
+
+    typst_str = circuit_to_typst_str(circuit, initialization_mode="qubit")
+    assert "lstick($b[0]$" in typst_str
+    options = RenderOptions(
+        qubit_labels={0: "anc", 1: "data_0"},
+        mode_labels={0: "a"},
+        register_labels={"ro": "readout"},
+        initial_states={0: "|+>"},
+    )
+    typst_str = circuit_to_typst_str(circuit, options=options)
+    assert 'lstick($"anc": |+>$' in typst_str
+    assert 'lstick($"data"_0: |0>$' in typst_str
+    assert "lstick($a: |0>$" in typst_str
+    assert '"readout : "' in typst_str
+    text_str = circuit_to_text_str(
+        circuit, charset="ascii", options=RenderOptions(qubit_labels={0: "anc"})
+    )
+    assert "anc: |0>" in text_str
+    latex_str = circuit_to_quantikz_str(
+        circuit, options=RenderOptions(initial_states={1: "|1>"})
+    )
+    assert "\\lstick{$\\ket{1}$}" in latex_str
+
+
 if __name__ == "__main__":
     pytest.main(sys.argv)
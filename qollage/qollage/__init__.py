@@ -18,8 +18,28 @@ qollage is the HQS python package to draw qoqo quantum circuits.
     :toctree: generated/
 
     draw_circuit
+    draw_quantum_program
+    draw_device
+    draw_circuit_timing
+    draw_circuits_diff
     save_circuit
+    save_circuits
     circuit_to_typst_str
+    circuit_to_text_str
+    circuit_to_quantikz_str
+    device_to_typst_str
+    circuit_to_timing_typst_str
+    circuits_diff_to_typst_str
+    circuit_statistics
+    RenderOptions
+    QollageError
+    NetworkError
+    CacheError
+    TypstCompileError
+    UnsupportedOperationError
+    InvalidOperationError
+    InvalidOptionError
+    RenderError
 
 """
 
@@ -18,21 +18,156 @@ qollage is the HQS python package to draw qoqo quantum circuits.
     :toctree: generated/
 
     draw_circuit
+    draw_quantum_program
+    draw_device
+    draw_circuit_timing
+    draw_circuits_diff
     save_circuit
     circuit_to_typst_str
+    circuit_to_text_str
+    circuit_to_quantikz_str
+    device_to_typst_str
+    circuit_to_timing_typst_str
+    circuits_diff_to_typst_str
+    circuit_statistics
+    RenderOptions
+    QollageError
+    NetworkError
+    CacheError
+    TypstCompileError
+    UnsupportedOperationError
+    InvalidOperationError
+    InvalidOptionError
+    RenderError
 
 """
 
-from qoqo import Circuit  # type: ignore
-from typing import Optional
+from qoqo import Circuit, QuantumProgram  # type: ignore
+from qoqo.devices import GenericDevice  # type: ignore
+from typing import Any, Dict, List, Optional
+
+class QollageError(ValueError):
+    """
+    Base class of the errors raised by qollage when drawing.
+
+    The `index` attribute is the index of the operation of the circuit causing the error, or None.
+    """
+
+    index: Optional[int]
+
+class NetworkError(QollageError):
+    """A font or a Typst package couldn't be downloaded."""
+
+class CacheError(QollageError):
+    """A file of the cache directory couldn't be created, read or written."""
+
+class TypstCompileError(QollageError):
+    """
+    The Typst compiler rejected the generated source.
+
+    The errors caused by the code drawing an operation are reported as coming from this
+    operation, e.g. `operation #3 (RotateX on qubit 1): ...`.
+    """
+
+class UnsupportedOperationError(QollageError):
+    """An operation of the circuit is not supported by the Typst backend."""
+
+class InvalidOperationError(QollageError):
+    """An operation of the circuit can't be drawn."""
+
+class InvalidOptionError(QollageError):
+    """An option given to the drawing function is not valid."""
+
+class RenderError(QollageError):
+    """The compiled document couldn't be exported to an image or a document."""
+
+class RenderOptions:
+    """
+    Options of the representation of a circuit, shared by the drawing functions.
+
+    ## Args:
+         * render_pragmas (str, optional): How to render Pragmas operations:\n
+             - "all" to render every pragmas.\n
+             - "none" to not render any pragmas.\n
+             - "PragmaOperation1, PragmaOperation2" to render only some pragmas.
+         * initialization_mode (str, optional): What to display at the beginning of the circuit:\n
+             - "state" for "|0>". Used if None. \n
+             - "qubit" for "q[n]" and "b[n]".\n
+         * max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+             The default setting `None` does not create a new line.
+         * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
+             If None, the default rounding accuracy of roqollage (3) is used.
+         * layout_mode (str, optional): How to place the operations in the columns:\n
+             - "sequential" to give each operation its own column. Used if None. \n
+             - "asap" to place each operation in the earliest free column.\n
+             - "alap" to place each operation in the latest free column.\n
+         * page_per_chunk (bool, optional): Whether to put each line of the circuit on its own page.
+             Only used when saving a pdf document.
+         * device (Optional(Device)): The device the circuit is checked against. The gates it doesn't support
+             (no gate time) and the two-qubit gates on qubits that are not an edge of the device are highlighted
+             and listed in a legend below the circuit.
+         * statistics (bool): Whether to add a table summarizing the circuit below the circuit.
+         * float_style (Optional(str)): How the floats are written:\n
+             - "symbolic" for multiples of pi, fractions and square roots like 5π/8. Used if None. \n
+             - "decimal" for decimal numbers.\n
+             - "degrees" for the multiples of pi in degrees like 112.5°.\n
+         * max_denominator (Optional(int)): The largest denominator of the fractions recognized in the floats.
+             If None, 16 is used.
+         * angle_unit (Optional(str)): The unit of the float rotation angles of the operations, the other parameters are left unchanged:\n
+             - "radians". Used if None. \n
+             - "degrees" like 90°.\n
+             - "turns" like 1/4 tr.\n
+         * max_state_size (Optional(int)): The largest number of kets of the state vectors, and of rows and columns of the density matrices,
+             displayed before an ellipsis. If None, 8 is used.
+         * complex_style (Optional(str)): How the complex numbers are written:\n
+             - "cartesian" like 0.5 - 0.5i. Used if None. \n
+             - "polar" like 1/√2·e^(-iπ/4).\n
+         * qubit_labels (Optional(Dict[int, str])): The labels of the qubit wires by qubit index, like {0: "anc", 1: "data_0"},
+             replacing "q[n]". The labels are Typst math, in which the words that aren't Typst symbols are written as text.
+         * mode_labels (Optional(Dict[int, str])): The labels of the bosonic mode wires by mode index, replacing "b[n]".
+         * register_labels (Optional(Dict[str, str])): The labels of the classical register wires by register name,
+             replacing the register names.
+         * initial_states (Optional(Dict[int, str])): The initial states of the qubit wires by qubit index, like {0: "|+>", 1: "|1>"},
+             replacing "|0>".
+
+    ## Raises:
+         * TypeError: Device conversion error.
+         * InvalidOptionError: Option not accepted.
+    """
+
+    def __init__(
+        self,
+        render_pragmas: str = "all",
+        initialization_mode: Optional[str] = None,
+        max_circuit_length: Optional[int] = None,
+        rounding_accuracy: Optional[int] = None,
+        layout_mode: Optional[str] = None,
+        page_per_chunk: bool = False,
+        device: Optional[GenericDevice] = None,
+        statistics: bool = False,
+        float_style: Optional[str] = None,
+        max_denominator: Optional[int] = None,
+        angle_unit: Optional[str] = None,
+        max_state_size: Optional[int] = None,
+        complex_style: Optional[str] = None,
+        qubit_labels: Optional[Dict[int, str]] = None,
+        mode_labels: Optional[Dict[int, str]] = None,
+        register_labels: Optional[Dict[str, str]] = None,
+        initial_states: Optional[Dict[int, str]] = None,
+    ) -> None: ...
 
 def draw_circuit(
     circuit: Circuit,
     pixel_per_point: float = 3.0,
-    render_pragmas: str = "all",
+    render_pragmas: Optional[str] = None,
     initialization_mode: Optional[str] = None,
     max_circuit_length: Optional[int] = None,
     rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+    lenient: bool = False,
+    cache_dir: Optional[str] = None,
+    font_paths: Optional[List[str]] = None,
+    package_source: Optional[str] = None,
 ) -> None:
     """
     Displays the qoqo circuit as an image output
@@ -41,63 +176,195 @@ def draw_circuit(
          * circuit (Circuit): The qoqo circuit to draw.
          * pixel_per_point (float, optional): The pixels per point ration of the image.
             The higher the value, the bigger the image will be but the longer it will take to render.
-         * render_pragmas (str, optional): How to render Pragmas operations:\n
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
              - `"all"` to render every pragmas.\n
              - `"none"` to not render any pragmas.\n
              - `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.
          * initialization_mode (str, optional): What to display at the beginning of the circuit:\n
              - "state" for "|0>". Used if None. \n
-             - "qubit" for "q[n]".\n
+             - "qubit" for "q[n]" and "b[n]".\n
          * max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
              The default setting `None` does not create a new line.
          * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
              If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuit representation, e.g. the layout mode, the device,
+             the statistics table, the number format or the wire labels.
+             The arguments above override the corresponding options when they are set.
+         * lenient (bool): Whether to draw the operations not supported as placeholder boxes labeled with their
+             name, with a UserWarning for each of them, instead of raising an error.
+         * cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+             If None, the `qollage` directory of the user cache directory is used.
+         * font_paths (Optional(List[str])): Additional font files or directories containing font files.
+         * package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+             or a local directory containing the Typst packages. If None, https://packages.typst.org is used.
 
     ## Raises:
          * TypeError: Circuit conversion error.
-         * ValueError: Operation not supported | Memory limit exceeded if pixel_per_point is too large.
+         * UnsupportedOperationError: Operation not supported.
+         * ValueError: Memory limit exceeded if pixel_per_point is too large.
+    """
+
+def draw_quantum_program(
+    program: QuantumProgram,
+    pixel_per_point: float = 3.0,
+    render_pragmas: Optional[str] = None,
+    initialization_mode: Optional[str] = None,
+    max_circuit_length: Optional[int] = None,
+    rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+    cache_dir: Optional[str] = None,
+    font_paths: Optional[List[str]] = None,
+    package_source: Optional[str] = None,
+) -> None:
+    """
+    Displays the qoqo quantum program as an image output.
+    The constant circuit and each measurement circuit are drawn in labeled panels, along with the
+    input parameters of the program and the measurement input (measured Pauli products and
+    expectation values formulas).
+
+    ## Args:
+         * program (QuantumProgram): The qoqo quantum program to draw.
+         * pixel_per_point (float, optional): The pixels per point ration of the image.
+            The higher the value, the bigger the image will be but the longer it will take to render.
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
+             - `"all"` to render every pragmas.\n
+             - `"none"` to not render any pragmas.\n
+             - `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.
+         * initialization_mode (str, optional): What to display at the beginning of the circuits:\n
+             - "state" for "|0>". Used if None. \n
+             - "qubit" for "q[n]" and "b[n]".\n
+         * max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+             The default setting `None` does not create a new line.
+         * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
+             If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuits representation, e.g. the layout mode or the number format.
+             The arguments above override the corresponding options when they are set.
+         * cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+             If None, the `qollage` directory of the user cache directory is used.
+         * font_paths (Optional(List[str])): Additional font files or directories containing font files.
+         * package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+             or a local directory containing the Typst packages. If None, https://packages.typst.org is used.
+
+    ## Raises:
+         * TypeError: QuantumProgram conversion error.
+         * UnsupportedOperationError: Operation not supported.
+         * ValueError: Memory limit exceeded if pixel_per_point is too large.
     """
 
 def save_circuit(
     circuit: Circuit,
     path: Optional[str] = None,
     pixel_per_point: float = 3.0,
-    render_pragmas: str = "all",
+    render_pragmas: Optional[str] = None,
     initialization_mode: Optional[str] = None,
     max_circuit_length: Optional[int] = None,
     rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+    lenient: bool = False,
+    cache_dir: Optional[str] = None,
+    font_paths: Optional[List[str]] = None,
+    package_source: Optional[str] = None,
 ) -> None:
     """
-    Saves the qoqo circuit as a png image
+    Saves the qoqo circuit as a png or svg image, or as a pdf document
 
     ## Args:
          * circuit (Circuit): The qoqo circuit to be saved.
          * path (str, optional): The path to where the image should be saved. "./circuit.png" will be used if None.
+            The image is saved as a svg if the path ends with ".svg", as a pdf if the path ends with ".pdf"
+            and as a png otherwise.
          * pixel_per_point (float, optional): The pixels per point ration of the image.
             The higher the value, the bigger the image will be but the longer it will take to render.
-         * render_pragmas (str, optional): How to render Pragmas operations:\n
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
              - "all" to render every pragmas.\n
              - "none" to not render any pragmas.\n
              - "PragmaOperation1, PragmaOperation2" to render only some pragmas.
          * initialization_mode (str, optional): What to display at the beginning of the circuit:\n
              - "state" for "|0>". Used if None. \n
-             - "qubit" for "q[n]".\n
+             - "qubit" for "q[n]" and "b[n]".\n
          * max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
              The default setting `None` does not create a new line.
          * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
              If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuit representation, e.g. the layout mode, the device,
+             the statistics table, the number format or the wire labels.
+             The arguments above override the corresponding options when they are set.
+         * lenient (bool): Whether to draw the operations not supported as placeholder boxes labeled with their
+             name, with a UserWarning for each of them, instead of raising an error.
+         * cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+             If None, the `qollage` directory of the user cache directory is used.
+         * font_paths (Optional(List[str])): Additional font files or directories containing font files.
+         * package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+             or a local directory containing the Typst packages. If None, https://packages.typst.org is used.
 
     ## Raises:
          * TypeError: Circuit conversion error
-         * ValueError: Operation not supported. | Memory limit exceeded if pixel_per_point is too large. | Couldn't create the corresponding file.
+         * UnsupportedOperationError: Operation not supported.
+         * ValueError: Memory limit exceeded if pixel_per_point is too large. | Couldn't create the corresponding file.
+    """
+
+def save_circuits(
+    circuits: List[Circuit],
+    paths: List[str],
+    pixel_per_point: float = 3.0,
+    render_pragmas: Optional[str] = None,
+    initialization_mode: Optional[str] = None,
+    max_circuit_length: Optional[int] = None,
+    rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+    lenient: bool = False,
+    cache_dir: Optional[str] = None,
+    font_paths: Optional[List[str]] = None,
+    package_source: Optional[str] = None,
+) -> List[Optional[str]]:
+    """
+    Saves several qoqo circuits as png images, rendering them in parallel.
+    A circuit that can't be rendered doesn't prevent the other circuits from being saved.
+
+    ## Args:
+         * circuits (List[Circuit]): The qoqo circuits to be saved.
+         * paths (List[str]): The paths to where the images should be saved, one per circuit.
+            ".png" is appended to the paths not ending with ".png".
+         * pixel_per_point (float, optional): The pixels per point ration of the images.
+            The higher the value, the bigger the images will be but the longer it will take to render.
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
+             - "all" to render every pragmas.\n
+             - "none" to not render any pragmas.\n
+             - "PragmaOperation1, PragmaOperation2" to render only some pragmas.
+         * initialization_mode (str, optional): What to display at the beginning of the circuits:\n
+             - "state" for "|0>". Used if None. \n
+             - "qubit" for "q[n]" and "b[n]".\n
+         * max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+             The default setting `None` does not create a new line.
+         * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
+             If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuit representation, e.g. the layout mode, the device,
+             the statistics table, the number format or the wire labels.
+             The arguments above override the corresponding options when they are set.
+         * lenient (bool): Whether to draw the operations not supported as placeholder boxes labeled with their
+             name, with a UserWarning for each of them, instead of raising an error.
+         * cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+             If None, the `qollage` directory of the user cache directory is used.
+         * font_paths (Optional(List[str])): Additional font files or directories containing font files.
+         * package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+             or a local directory containing the Typst packages. If None, https://packages.typst.org is used.
+
+    ## Returns:
+         * List[Optional[str]]: For each circuit, None if it was saved and the error message otherwise.
+
+    ## Raises:
+         * TypeError: Circuit conversion error.
+         * ValueError: The number of paths doesn't match the number of circuits. | Typst backend error.
     """
 
 def circuit_to_typst_str(
     circuit: Circuit,
-    render_pragmas: str = "all",
+    render_pragmas: Optional[str] = None,
     initialization_mode: Optional[str] = None,
     max_circuit_length: Optional[int] = None,
     rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+    lenient: bool = False,
 ) -> str:
     """
     Returns the circuit's representation in Typst.
@@ -106,22 +373,338 @@ def circuit_to_typst_str(
 
     ## Args:
          * circuit (Circuit): The qoqo circuit to be saved.
-         * render_pragmas (str, optional): How to render Pragmas operations:\n
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
              - "all" to render every pragmas.\n
              - "none" to not render any pragmas.\n
              - "PragmaOperation1, PragmaOperation2" to render only some pragmas.
          * initialization_mode (str, optional): What to display at the beginning of the circuit:\n
              - "state" for "|0>". Used if None. \n
-             - "qubit" for "q[n]".\n
+             - "qubit" for "q[n]" and "b[n]".\n
          * max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
              The default setting `None` does not create a new line.
          * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
              If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuit representation, e.g. the layout mode, the device,
+             the statistics table, the number format or the wire labels.
+             The arguments above override the corresponding options when they are set.
+         * lenient (bool): Whether to draw the operations not supported as placeholder boxes labeled with their
+             name, with a UserWarning for each of them, instead of raising an error.
 
     ## Returns:
          * str: The circuit's representation in Typst.
 
+    ## Raises:
+         * TypeError: Circuit or Device conversion error
+         * UnsupportedOperationError: Operation not supported.
+         * ValueError: Memory limit exceeded if pixel_per_point is too large. | Couldn't create the corresponding file.
+    """
+
+def circuit_to_text_str(
+    circuit: Circuit,
+    render_pragmas: Optional[str] = None,
+    initialization_mode: Optional[str] = None,
+    rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+    charset: str = "unicode",
+) -> str:
+    """
+    Returns the circuit's text representation.
+    It doesn't need Typst, fonts or a network access and can be printed in a terminal or a log.
+
+
+    ## Args:
+         * circuit (Circuit): The qoqo circuit to draw.
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
+             - "all" to render every pragmas.\n
+             - "none" to not render any pragmas.\n
+             - "PragmaOperation1, PragmaOperation2" to render only some pragmas.
+         * initialization_mode (str, optional): What to display at the beginning of the circuit:\n
+             - "state" for "|0>". Used if None. \n
+             - "qubit" for "q[n]" and "b[n]".\n
+         * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
+             If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuit representation, e.g. the layout mode, the number format
+             or the wire labels. The arguments above override the corresponding options when they are set.
+         * charset (str, optional): The characters used to draw the circuit:\n
+             - "unicode" for box-drawing characters. Used by default. \n
+             - "ascii" for plain ASCII characters.\n
+
+    ## Returns:
+         * str: The circuit's text representation.
+
     ## Raises:
          * TypeError: Circuit conversion error
-         * ValueError: Operation not supported. | Memory limit exceeded if pixel_per_point is too large. | Couldn't create the corresponding file.
+         * UnsupportedOperationError: Operation not supported.
+         * InvalidOptionError: Charset not accepted.
+    """
+
+def circuit_to_quantikz_str(
+    circuit: Circuit,
+    render_pragmas: Optional[str] = None,
+    initialization_mode: Optional[str] = None,
+    max_circuit_length: Optional[int] = None,
+    rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+) -> str:
+    """
+    Returns the circuit's representation in LaTeX, drawn with the quantikz package.
+    The string contains `quantikz` environments and needs `\\usetikzlibrary{quantikz2}` in the preamble.
+
+
+    ## Args:
+         * circuit (Circuit): The qoqo circuit to draw.
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
+             - "all" to render every pragmas.\n
+             - "none" to not render any pragmas.\n
+             - "PragmaOperation1, PragmaOperation2" to render only some pragmas.
+         * initialization_mode (str, optional): What to display at the beginning of the circuit:\n
+             - "state" for "|0>". Used if None. \n
+             - "qubit" for "q[n]" and "b[n]".\n
+         * max_circuit_length (Optional(int)): The maximum number of gates per qubit before starting a new quantikz environment.
+             The default setting `None` does not split the circuit.
+         * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
+             If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuit representation, e.g. the layout mode, the number format
+             or the wire labels. The arguments above override the corresponding options when they are set.
+
+    ## Returns:
+         * str: The circuit's representation in LaTeX.
+
+    ## Raises:
+         * TypeError: Circuit conversion error
+         * UnsupportedOperationError: Operation not supported.
+    """
+
+def draw_device(
+    device: GenericDevice,
+    pixel_per_point: float = 3.0,
+    options: Optional[RenderOptions] = None,
+    cache_dir: Optional[str] = None,
+    font_paths: Optional[List[str]] = None,
+    package_source: Optional[str] = None,
+) -> None:
+    """
+    Displays the connectivity graph of a qoqo device as an image output.
+    The qubits are annotated with their single-qubit gate times and decoherence rates and the
+    edges with their two-qubit gate times. Annotations shared by every qubit, or every edge,
+    are displayed once above the graph.
+
+    ## Args:
+         * device (Device): The qoqo device to draw (AllToAllDevice, SquareLatticeDevice, GenericDevice or
+             any device with a `generic_device` method).
+         * pixel_per_point (float, optional): The pixels per point ration of the image.
+            The higher the value, the bigger the image will be but the longer it will take to render.
+         * options (Optional(RenderOptions)): The options giving the format of the displayed numbers, the other options are not used.
+         * cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+             If None, the `qollage` directory of the user cache directory is used.
+         * font_paths (Optional(List[str])): Additional font files or directories containing font files.
+         * package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+             or a local directory containing the Typst packages. If None, https://packages.typst.org is used.
+
+    ## Raises:
+         * TypeError: Device conversion error.
+         * ValueError: Memory limit exceeded if pixel_per_point is too large.
+    """
+
+def device_to_typst_str(
+    device: GenericDevice,
+    options: Optional[RenderOptions] = None,
+) -> str:
+    """
+    Returns the Typst representation of the connectivity graph of a qoqo device.
+
+    ## Args:
+         * device (Device): The qoqo device to draw (AllToAllDevice, SquareLatticeDevice, GenericDevice or
+             any device with a `generic_device` method).
+         * options (Optional(RenderOptions)): The options giving the format of the displayed numbers, the other options are not used.
+
+    ## Returns:
+         * str: The device's representation in Typst.
+
+    ## Raises:
+         * TypeError: Device conversion error.
+    """
+
+def draw_circuit_timing(
+    circuit: Circuit,
+    device: Optional[GenericDevice] = None,
+    durations: Optional[Dict[str, float]] = None,
+    default_duration: Optional[float] = None,
+    pixel_per_point: float = 3.0,
+    options: Optional[RenderOptions] = None,
+    cache_dir: Optional[str] = None,
+    font_paths: Optional[List[str]] = None,
+    package_source: Optional[str] = None,
+) -> None:
+    """
+    Displays the qoqo circuit on a time axis as an image output.
+    The position and the width of each operation are proportional to its start time and duration,
+    each operation starting as early as its qubits allow. The durations are taken from `durations`,
+    then from the gate times of the device, then from `default_duration`. PragmaSleep operations
+    are drawn with their sleep time, the other pragmas only if they appear in `durations`.
+
+    ## Args:
+         * circuit (Circuit): The qoqo circuit to draw.
+         * device (Optional(Device)): The device providing the gate times.
+         * durations (Optional(Dict[str, float])): The durations of the operations by name,
+             overriding the gate times of the device.
+         * default_duration (Optional(float)): The duration of the operations without known duration.
+             If None, an error is raised for these operations.
+         * pixel_per_point (float, optional): The pixels per point ration of the image.
+            The higher the value, the bigger the image will be but the longer it will take to render.
+         * options (Optional(RenderOptions)): The options giving the format of the displayed numbers, the other options are not used.
+         * cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+             If None, the `qollage` directory of the user cache directory is used.
+         * font_paths (Optional(List[str])): Additional font files or directories containing font files.
+         * package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+             or a local directory containing the Typst packages. If None, https://packages.typst.org is used.
+
+    ## Raises:
+         * TypeError: Circuit or Device conversion error.
+         * InvalidOperationError: Unknown operation duration. | Symbolic sleep time.
+    """
+
+def circuit_to_timing_typst_str(
+    circuit: Circuit,
+    device: Optional[GenericDevice] = None,
+    durations: Optional[Dict[str, float]] = None,
+    default_duration: Optional[float] = None,
+    options: Optional[RenderOptions] = None,
+) -> str:
+    """
+    Returns the Typst representation of the qoqo circuit on a time axis.
+
+    ## Args:
+         * circuit (Circuit): The qoqo circuit to draw.
+         * device (Optional(Device)): The device providing the gate times.
+         * durations (Optional(Dict[str, float])): The durations of the operations by name,
+             overriding the gate times of the device.
+         * default_duration (Optional(float)): The duration of the operations without known duration.
+             If None, an error is raised for these operations.
+         * options (Optional(RenderOptions)): The options giving the format of the displayed numbers, the other options are not used.
+
+    ## Returns:
+         * str: The circuit's timing representation in Typst.
+
+    ## Raises:
+         * TypeError: Circuit or Device conversion error.
+         * InvalidOperationError: Unknown operation duration. | Symbolic sleep time.
+    """
+
+def draw_circuits_diff(
+    before: Circuit,
+    after: Circuit,
+    pixel_per_point: float = 3.0,
+    render_pragmas: Optional[str] = None,
+    initialization_mode: Optional[str] = None,
+    max_circuit_length: Optional[int] = None,
+    rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+    cache_dir: Optional[str] = None,
+    font_paths: Optional[List[str]] = None,
+    package_source: Optional[str] = None,
+) -> None:
+    """
+    Displays the differences between two qoqo circuits as an image output.
+    The operations of both circuits are drawn in a single circuit, aligned on their longest common
+    sequence of operations: the added operations are filled in green, the removed ones in red and
+    the ones whose parameters changed in yellow.
+
+    ## Args:
+         * before (Circuit): The first qoqo circuit.
+         * after (Circuit): The second qoqo circuit.
+         * pixel_per_point (float, optional): The pixels per point ration of the image.
+            The higher the value, the bigger the image will be but the longer it will take to render.
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
+             - `"all"` to render every pragmas.\n
+             - `"none"` to not render any pragmas.\n
+             - `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.
+         * initialization_mode (str, optional): What to display at the beginning of the circuit:\n
+             - "state" for "|0>". Used if None. \n
+             - "qubit" for "q[n]" and "b[n]".\n
+         * max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+             The default setting `None` does not create a new line.
+         * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
+             If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuit representation, e.g. the layout mode or the number format.
+             The arguments above override the corresponding options when they are set.
+         * cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+             If None, the `qollage` directory of the user cache directory is used.
+         * font_paths (Optional(List[str])): Additional font files or directories containing font files.
+         * package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+             or a local directory containing the Typst packages. If None, https://packages.typst.org is used.
+
+    ## Raises:
+         * TypeError: Circuit conversion error.
+         * UnsupportedOperationError: Operation not supported.
+         * ValueError: Memory limit exceeded if pixel_per_point is too large.
+    """
+
+def circuits_diff_to_typst_str(
+    before: Circuit,
+    after: Circuit,
+    render_pragmas: Optional[str] = None,
+    initialization_mode: Optional[str] = None,
+    max_circuit_length: Optional[int] = None,
+    rounding_accuracy: Optional[int] = None,
+    options: Optional[RenderOptions] = None,
+) -> str:
+    """
+    Returns the Typst representation of the differences between two qoqo circuits.
+
+    ## Args:
+         * before (Circuit): The first qoqo circuit.
+         * after (Circuit): The second qoqo circuit.
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
+             - `"all"` to render every pragmas.\n
+             - `"none"` to not render any pragmas.\n
+             - `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.
+         * initialization_mode (str, optional): What to display at the beginning of the circuit:\n
+             - "state" for "|0>". Used if None. \n
+             - "qubit" for "q[n]" and "b[n]".\n
+         * max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+             The default setting `None` does not create a new line.
+         * rounding_accuracy (Optional(int)): The number of decimals displayed for floats.
+             If None, the default rounding accuracy of roqollage (3) is used.
+         * options (Optional(RenderOptions)): The options of the circuit representation, e.g. the layout mode or the number format.
+             The arguments above override the corresponding options when they are set.
+
+    ## Returns:
+         * str: The representation of the differences in Typst.
+
+    ## Raises:
+         * TypeError: Circuit conversion error.
+         * UnsupportedOperationError: Operation not supported.
+    """
+
+def circuit_statistics(
+    circuit: Circuit,
+    render_pragmas: Optional[str] = None,
+    options: Optional[RenderOptions] = None,
+) -> Dict[str, Any]:
+    """
+    Returns the statistics of the qoqo circuit, without drawing it.
+
+    ## Args:
+         * circuit (Circuit): The qoqo circuit to inspect.
+         * render_pragmas (Optional(str)): How to render Pragmas operations:\n
+             - "all" to render every pragmas.\n
+             - "none" to not render any pragmas.\n
+             - "PragmaOperation1, PragmaOperation2" to render only some pragmas.
+         * options (Optional(RenderOptions)): The options giving the layout mode the depth is computed with and the pragmas rendered,
+             overridden by render_pragmas if it is set.
+
+    ## Returns:
+         * Dict[str, Any]: The statistics of the circuit:\n
+             - "number_qubits", "number_modes" and "number_classical_registers" (int).\n
+             - "depth" (int): The number of columns of the drawn circuit in the layout mode.\n
+             - "gate_counts" (Dict[str, int]): The number of operations of each name, pragmas and
+               register definitions excepted.\n
+             - "two_qubit_gate_count" and "measurement_count" (int).\n
+             - "symbolic_parameters" (List[str]): The symbolic parameters used by the operations.\n
+             - "hidden_pragmas" (Dict[str, int]): The number of pragmas of each name not rendered.\n
+
+    ## Raises:
+         * TypeError: Circuit conversion error.
+         * UnsupportedOperationError: Operation not supported.
     """
@@ -10,23 +10,69 @@
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{io::Cursor, path::PathBuf, str::FromStr};
+use std::{collections::HashMap, ffi::CString, io::Cursor, path::PathBuf, str::FromStr};
 
+use image::DynamicImage;
 use pyo3::{
-    exceptions::{PyTypeError, PyValueError},
+    exceptions::{PyTypeError, PyUserWarning, PyValueError},
     prelude::*,
+    types::PyDict,
 };
-use qoqo::convert_into_circuit;
-use roqollage::{circuit_into_typst_str, circuit_to_image, InitializationMode, RenderPragmas};
+use qoqo::{convert_into_circuit, QuantumProgramWrapper};
+use roqollage::{
+    circuit_into_lenient_typst_str, circuit_into_quantikz_str, circuit_into_statistics,
+    circuit_into_text_str, circuit_into_timing_typst_str, circuit_into_typst_str,
+    circuit_timing_to_image, circuit_to_image, circuit_to_lenient_image, circuit_to_pdf,
+    circuit_to_svg, circuits_diff_into_typst_str, circuits_diff_to_image, circuits_to_images,
+    circuits_to_lenient_images, device_into_typst_str, device_to_image, quantum_program_to_image,
+    render_typst_str_to_pdf, render_typst_str_to_svg, BackendConfig, PackageSource, TextCharset,
+};
+use roqoqo::devices::Device;
+
+use crate::{
+    convert_into_device, render_options_from_args, roqollage_error_to_py, RenderOptionsWrapper,
+};
+
+/// Creates the configuration of the Typst backend from the python arguments.
+///
+/// # Arguments
+///
+/// * `cache_dir` - The cache directory, the default one if None.
+/// * `font_paths` - The additional font files or directories.
+/// * `package_source` - The url or the local directory the Typst packages are loaded from.
+///
+/// # Returns
+///
+/// * `PyResult<BackendConfig>` - The configuration of the Typst backend.
+fn backend_config_from_args(
+    cache_dir: Option<PathBuf>,
+    font_paths: Option<Vec<PathBuf>>,
+    package_source: Option<String>,
+) -> PyResult<BackendConfig> {
+    let mut config = BackendConfig::default();
+    if let Some(cache_dir) = cache_dir {
+        config.cache_dir = cache_dir;
+    }
+    if let Some(font_paths) = font_paths {
+        config.font_paths = font_paths;
+    }
+    if let Some(package_source) = package_source {
+        config.package_source = PackageSource::from_str(&package_source)
+            .map_err(|x| roqollage_error_to_py("Package source not accepted", x))?;
+    }
+    Ok(config)
+}
 
-/// Saves the qoqo circuit as a png image
+/// Saves the qoqo circuit as a png or svg image, or as a pdf document
 ///
 /// Args:
 ///     circuit (Circuit): The qoqo circuit to be saved
-///     path (String): The path to where the image should be saved
+///     path (String): The path to where the image should be saved.
+///         The image is saved as a svg if the path ends with `.svg`, as a pdf if the path ends
+///         with `.pdf` and as a png otherwise.
 ///     pixel_per_point (f32): The pixels per point ration of the image.  
 ///        The higher the value, the bigger the image will be but the longer it will take to render  
-///      render_pragmas (bool): How to render Pragmas operations:  
+///      render_pragmas (Optional(str)): How to render Pragmas operations:  
 ///        `"all"` to render every pragmas.
 ///        `"none"` to not render any pragmas.
 ///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
@@ -34,41 +80,50 @@ use roqollage::{circuit_into_typst_str, circuit_to_image, InitializationMode, Re
 ///         "qubit" for "q[n]" State will be used if the parameter is not set.
 ///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
 ///         The default setting `None` does not create a new line.
+///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuit representation, e.g. the
+///         layout mode, the device, the statistics table, the number format or the wire labels.
+///         The arguments above override the corresponding options when they are set.
+///     lenient (bool): Whether to draw the operations not supported as placeholder boxes labeled
+///         with their name, with a UserWarning for each of them, instead of raising an error.
+///     cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+///         Defaults to the `qollage` directory in the user cache directory.
+///     font_paths (Optional(List[str])): Additional font files or directories containing font files.
+///     package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+///         or a local directory containing the Typst packages.
 ///
 /// Raises:
 ///     TypeError: Circuit conversion error
-///     ValueError: Operation not supported
+///     UnsupportedOperationError: Operation not supported
 #[pyfunction]
-#[pyo3(signature = (circuit, path=None, pixel_per_point=3.0, render_pragmas="all", initialization_mode=None, max_circuit_length=None, rounding_accuracy=None))]
+#[pyo3(signature = (circuit, path=None, pixel_per_point=3.0, render_pragmas=None, initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, options=None, lenient=false, cache_dir=None, font_paths=None, package_source=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn save_circuit(
     circuit: &Bound<PyAny>,
     path: Option<PathBuf>,
     pixel_per_point: f32,
-    render_pragmas: &str,
+    render_pragmas: Option<&str>,
     initialization_mode: Option<String>,
     max_circuit_length: Option<usize>,
     rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+    lenient: bool,
+    cache_dir: Option<PathBuf>,
+    font_paths: Option<Vec<PathBuf>>,
+    package_source: Option<String>,
 ) -> PyResult<()> {
     let circuit = convert_into_circuit(circuit).map_err(|x| {
         PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
     })?;
-    let initialization_mode = initialization_mode
-        .map(|mode: String| InitializationMode::from_str(mode.as_str()))
-        .transpose()
-        .map_err(|x| PyValueError::new_err(format!("Initialization mode not accepted: {x:?}")))?;
-    let image = circuit_to_image(
-        &circuit,
-        Some(pixel_per_point),
-        RenderPragmas::from_str(render_pragmas).map_err(|x| {
-            PyValueError::new_err(format!(
-                "Error: render_pragmas is not in a suitable format: {x:?}"
-            ))
-        })?,
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
         initialization_mode,
         max_circuit_length,
         rounding_accuracy,
-    )
-    .map_err(|x| PyValueError::new_err(format!("Error during Circuit drawing: {x:?}")))?;
+    )?;
+    let options = options.render_options();
+    let backend_config = backend_config_from_args(cache_dir, font_paths, package_source)?;
 
     let path = match path {
         Some(path) => {
@@ -76,7 +131,7 @@ pub fn save_circuit(
                 format!("{}/circuit.png", path.to_str().unwrap_or("."))
             } else {
                 let s = path.to_str().unwrap_or("circuit").to_owned();
-                if s.ends_with(".png") {
+                if s.ends_with(".png") || s.ends_with(".svg") || s.ends_with(".pdf") {
                     s
                 } else {
                     format!("{s}.png")
@@ -85,10 +140,186 @@ pub fn save_circuit(
         }
         None => "circuit.png".to_owned(),
     };
+    if path.ends_with(".svg") || path.ends_with(".pdf") {
+        let (typst_str, warnings) = if lenient {
+            let (typst_str, warnings) = circuit_into_lenient_typst_str(&circuit, &options)
+                .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
+            (Some(typst_str), warnings)
+        } else {
+            (None, Vec::new())
+        };
+        if path.ends_with(".svg") {
+            let svg = match typst_str {
+                Some(typst_str) => render_typst_str_to_svg(typst_str, Some(&backend_config)),
+                None => circuit_to_svg(&circuit, &options, Some(&backend_config)),
+            }
+            .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
+            std::fs::write(path, svg)
+                .map_err(|x| PyValueError::new_err(format!("Error during image saving: {x:?}")))?;
+        } else {
+            let pdf = match typst_str {
+                Some(typst_str) => render_typst_str_to_pdf(typst_str, Some(&backend_config)),
+                None => circuit_to_pdf(&circuit, &options, Some(&backend_config)),
+            }
+            .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
+            std::fs::write(path, pdf)
+                .map_err(|x| PyValueError::new_err(format!("Error during pdf saving: {x:?}")))?;
+        }
+        return emit_warnings(warnings);
+    }
+    let (image, warnings) = if lenient {
+        circuit_to_lenient_image(
+            &circuit,
+            Some(pixel_per_point),
+            &options,
+            Some(&backend_config),
+        )
+    } else {
+        circuit_to_image(
+            &circuit,
+            Some(pixel_per_point),
+            &options,
+            Some(&backend_config),
+        )
+        .map(|image| (image, Vec::new()))
+    }
+    .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
     image
         .save(path)
         .map_err(|x| PyValueError::new_err(format!("Error during image saving: {x:?}")))?;
-    Ok(())
+    emit_warnings(warnings)
+}
+
+/// Saves several qoqo circuits as png images, rendering them in parallel
+///
+/// The circuits are rendered on a thread pool with the GIL released.
+/// A circuit that can't be rendered doesn't prevent the other circuits from being saved.
+///
+/// Args:
+///     circuits (List[Circuit]): The qoqo circuits to be saved
+///     paths (List[String]): The paths to where the images should be saved, one per circuit.
+///         `.png` is appended to the paths not ending with `.png`.
+///     pixel_per_point (f32): The pixels per point ration of the images.  
+///        The higher the value, the bigger the images will be but the longer it will take to render  
+///      render_pragmas (Optional(str)): How to render Pragmas operations:  
+///        `"all"` to render every pragmas.
+///        `"none"` to not render any pragmas.
+///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
+///     initialization_mode (String): What to display at the begginning of the circuits. "state" for "|0>" and  
+///         "qubit" for "q[n]" State will be used if the parameter is not set.
+///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+///         The default setting `None` does not create a new line.
+///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuit representation, e.g. the
+///         layout mode, the device, the statistics table, the number format or the wire labels.
+///         The arguments above override the corresponding options when they are set.
+///     lenient (bool): Whether to draw the operations not supported as placeholder boxes labeled
+///         with their name, with a UserWarning for each of them, instead of raising an error.
+///     cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+///         Defaults to the `qollage` directory in the user cache directory.
+///     font_paths (Optional(List[str])): Additional font files or directories containing font files.
+///     package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+///         or a local directory containing the Typst packages.
+///
+/// Returns:
+///     List[Optional[str]]: For each circuit, None if it was saved and the error message otherwise.
+///
+/// Raises:
+///     TypeError: Circuit conversion error
+///     ValueError: The number of paths doesn't match the number of circuits | Typst backend error
+#[pyfunction]
+#[pyo3(signature = (circuits, paths, pixel_per_point=3.0, render_pragmas=None, initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, options=None, lenient=false, cache_dir=None, font_paths=None, package_source=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn save_circuits(
+    py: Python,
+    circuits: Vec<Bound<PyAny>>,
+    paths: Vec<PathBuf>,
+    pixel_per_point: f32,
+    render_pragmas: Option<&str>,
+    initialization_mode: Option<String>,
+    max_circuit_length: Option<usize>,
+    rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+    lenient: bool,
+    cache_dir: Option<PathBuf>,
+    font_paths: Option<Vec<PathBuf>>,
+    package_source: Option<String>,
+) -> PyResult<Vec<Option<String>>> {
+    if circuits.len() != paths.len() {
+        return Err(PyValueError::new_err(format!(
+            "Got {} circuits but {} paths.",
+            circuits.len(),
+            paths.len()
+        )));
+    }
+    let circuits = circuits
+        .iter()
+        .map(convert_into_circuit)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|x| {
+            PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+        })?;
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
+        initialization_mode,
+        max_circuit_length,
+        rounding_accuracy,
+    )?;
+    let options = options.render_options();
+    let backend_config = backend_config_from_args(cache_dir, font_paths, package_source)?;
+
+    let (errors, warnings): (Vec<Option<String>>, Vec<Vec<String>>) = py.allow_threads(|| {
+        let images = if lenient {
+            circuits_to_lenient_images(
+                &circuits,
+                Some(pixel_per_point),
+                &options,
+                Some(&backend_config),
+            )
+        } else {
+            circuits_to_images(
+                &circuits,
+                Some(pixel_per_point),
+                &options,
+                Some(&backend_config),
+            )
+            .map(|images| {
+                images
+                    .into_iter()
+                    .map(|image| image.map(|image| (image, Vec::new())))
+                    .collect()
+            })
+        }
+        .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
+        PyResult::Ok(
+            images
+                .into_iter()
+                .zip(paths)
+                .map(|(image, path)| {
+                    let mut path = path.to_str().unwrap_or("circuit").to_owned();
+                    if !path.ends_with(".png") {
+                        path.push_str(".png");
+                    }
+                    match image {
+                        Ok((image, warnings)) => (
+                            image
+                                .save(path)
+                                .err()
+                                .map(|x| format!("Error during image saving: {x:?}")),
+                            warnings,
+                        ),
+                        Err(x) => (
+                            Some(format!("Error during Circuit drawing: {x}")),
+                            Vec::new(),
+                        ),
+                    }
+                })
+                .unzip(),
+        )
+    })?;
+    emit_warnings(warnings.into_iter().flatten().collect())?;
+    Ok(errors)
 }
 
 /// Displays the qoqo circuit as an image output
@@ -97,7 +328,7 @@ pub fn save_circuit(
 ///     circuit (Circuit): The qoqo circuit to draw
 ///     pixel_per_point (Option<f32>): The pixels per point ration of the image.  
 ///        The higher the value, the bigger the image will be but the longer it will take to render  
-///     render_pragmas (bool): How to render Pragmas operations:  
+///     render_pragmas (Optional(str)): How to render Pragmas operations:  
 ///        `"all"` to render every pragmas.
 ///        `"none"` to not render any pragmas.
 ///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
@@ -106,36 +337,95 @@ pub fn save_circuit(
 ///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
 ///         The default setting `None` does not create a new line.
 ///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuit representation, e.g. the
+///         layout mode, the device, the statistics table, the number format or the wire labels.
+///         The arguments above override the corresponding options when they are set.
+///     lenient (bool): Whether to draw the operations not supported as placeholder boxes labeled
+///         with their name, with a UserWarning for each of them, instead of raising an error.
+///     cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+///         Defaults to the `qollage` directory in the user cache directory.
+///     font_paths (Optional(List[str])): Additional font files or directories containing font files.
+///     package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+///         or a local directory containing the Typst packages.
 ///
 /// Raises:
 ///     TypeError: Circuit conversion error
-///     ValueError: Operation not supported
+///     UnsupportedOperationError: Operation not supported
 #[pyfunction]
-#[pyo3(signature = (circuit, pixel_per_point=3.0, render_pragmas="All", initialization_mode=None, max_circuit_length=None, rounding_accuracy=None))]
+#[pyo3(signature = (circuit, pixel_per_point=3.0, render_pragmas=None, initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, options=None, lenient=false, cache_dir=None, font_paths=None, package_source=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn draw_circuit(
     circuit: &Bound<PyAny>,
     pixel_per_point: f32,
-    render_pragmas: &str,
+    render_pragmas: Option<&str>,
     initialization_mode: Option<String>,
     max_circuit_length: Option<usize>,
     rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+    lenient: bool,
+    cache_dir: Option<PathBuf>,
+    font_paths: Option<Vec<PathBuf>>,
+    package_source: Option<String>,
 ) -> PyResult<()> {
     let circuit = convert_into_circuit(circuit).map_err(|x| {
         PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
     })?;
-    let initialization_mode = initialization_mode
-        .map(|mode: String| InitializationMode::from_str(mode.as_str()))
-        .transpose()
-        .map_err(|x| PyValueError::new_err(format!("Initialization mode not accepted: {x:?}")))?;
-    let image = circuit_to_image(
-        &circuit,
-        Some(pixel_per_point),
-        RenderPragmas::from_str(render_pragmas).unwrap(),
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
         initialization_mode,
         max_circuit_length,
         rounding_accuracy,
-    )
-    .map_err(|x| PyValueError::new_err(format!("Error during Circuit drawing: {x:?}")))?;
+    )?;
+    let options = options.render_options();
+    let backend_config = backend_config_from_args(cache_dir, font_paths, package_source)?;
+    let image = if lenient {
+        let (image, warnings) = circuit_to_lenient_image(
+            &circuit,
+            Some(pixel_per_point),
+            &options,
+            Some(&backend_config),
+        )
+        .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
+        emit_warnings(warnings)?;
+        image
+    } else {
+        circuit_to_image(
+            &circuit,
+            Some(pixel_per_point),
+            &options,
+            Some(&backend_config),
+        )
+        .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?
+    };
+    display_image(image)
+}
+
+/// Emits the warnings of a lenient drawing as python UserWarnings.
+///
+/// # Arguments
+///
+/// * `warnings` - The warning messages.
+fn emit_warnings(warnings: Vec<String>) -> PyResult<()> {
+    Python::with_gil(|py| {
+        for warning in warnings {
+            PyErr::warn(
+                py,
+                &py.get_type::<PyUserWarning>(),
+                &CString::new(warning)?,
+                1,
+            )?;
+        }
+        Ok(())
+    })
+}
+
+/// Displays an image as an output with IPython.
+///
+/// # Arguments
+///
+/// * `image` - The image to display.
+fn display_image(image: DynamicImage) -> PyResult<()> {
     let mut buffer = Cursor::new(Vec::new());
     image
         .write_to(&mut buffer, image::ImageFormat::Png)
@@ -162,11 +452,79 @@ pub fn draw_circuit(
     Ok(())
 }
 
+/// Displays the qoqo quantum program as an image output
+///
+/// The constant circuit and each measurement circuit are drawn in labeled panels, along with
+/// the input parameters of the program and the input of the measurement.
+///
+/// Args:
+///     program (QuantumProgram): The qoqo quantum program to draw
+///     pixel_per_point (Option<f32>): The pixels per point ration of the image.  
+///        The higher the value, the bigger the image will be but the longer it will take to render  
+///     render_pragmas (Optional(str)): How to render Pragmas operations:  
+///        `"all"` to render every pragmas.
+///        `"none"` to not render any pragmas.
+///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
+///     initialization_mode (String): What to display at the begginning of the circuits. "state" for "|0>" and  
+///         "qubit" for "q[n]" State will be used if the parameter is not set.
+///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+///         The default setting `None` does not create a new line.
+///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuits representation, e.g. the
+///         layout mode or the number format. The arguments above override the corresponding
+///         options when they are set.
+///     cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+///         Defaults to the `qollage` directory in the user cache directory.
+///     font_paths (Optional(List[str])): Additional font files or directories containing font files.
+///     package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+///         or a local directory containing the Typst packages.
+///
+/// Raises:
+///     TypeError: QuantumProgram conversion error
+///     UnsupportedOperationError: Operation not supported
+#[pyfunction]
+#[pyo3(signature = (program, pixel_per_point=3.0, render_pragmas=None, initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, options=None, cache_dir=None, font_paths=None, package_source=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn draw_quantum_program(
+    program: &Bound<PyAny>,
+    pixel_per_point: f32,
+    render_pragmas: Option<&str>,
+    initialization_mode: Option<String>,
+    max_circuit_length: Option<usize>,
+    rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+    cache_dir: Option<PathBuf>,
+    font_paths: Option<Vec<PathBuf>>,
+    package_source: Option<String>,
+) -> PyResult<()> {
+    let program = QuantumProgramWrapper::from_pyany(program).map_err(|x| {
+        PyTypeError::new_err(format!(
+            "Cannot convert python object to QuantumProgram: {x:?}"
+        ))
+    })?;
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
+        initialization_mode,
+        max_circuit_length,
+        rounding_accuracy,
+    )?;
+    let backend_config = backend_config_from_args(cache_dir, font_paths, package_source)?;
+    let image = quantum_program_to_image(
+        &program,
+        Some(pixel_per_point),
+        &options.render_options(),
+        Some(&backend_config),
+    )
+    .map_err(|x| roqollage_error_to_py("Error during QuantumProgram drawing", x))?;
+    display_image(image)
+}
+
 /// Displays the qoqo circuit as an image output
 ///
 /// Args:
 ///     circuit (Circuit): The qoqo circuit to draw
-///     render_pragmas (bool): How to render Pragmas operations:  
+///     render_pragmas (Optional(str)): How to render Pragmas operations:  
 ///        `"all"` to render every pragmas.
 ///        `"none"` to not render any pragmas.
 ///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
@@ -175,32 +533,490 @@ pub fn draw_circuit(
 ///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
 ///         The default setting `None` does not create a new line.
 ///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuit representation, e.g. the
+///         layout mode, the device, the statistics table, the number format or the wire labels.
+///         The arguments above override the corresponding options when they are set.
+///     lenient (bool): Whether to draw the operations not supported as placeholder boxes labeled
+///         with their name, with a UserWarning for each of them, instead of raising an error.
 ///
 /// Raises:
 ///     TypeError: Circuit conversion error
-///     ValueError: Operation not supported
+///     UnsupportedOperationError: Operation not supported
 #[pyfunction]
-#[pyo3(signature = (circuit, render_pragmas="All", initialization_mode=None, max_circuit_length=None, rounding_accuracy=None))]
+#[pyo3(signature = (circuit, render_pragmas=None, initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, options=None, lenient=false))]
 pub fn circuit_to_typst_str(
     circuit: &Bound<PyAny>,
-    render_pragmas: &str,
+    render_pragmas: Option<&str>,
     initialization_mode: Option<String>,
     max_circuit_length: Option<usize>,
     rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+    lenient: bool,
 ) -> PyResult<String> {
     let circuit = convert_into_circuit(circuit).map_err(|x| {
         PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
     })?;
-    let initialization_mode = initialization_mode
-        .map(|mode: String| InitializationMode::from_str(mode.as_str()))
-        .transpose()
-        .map_err(|x| PyValueError::new_err(format!("Initialization mode not accepted: {x:?}")))?;
-    circuit_into_typst_str(
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
+        initialization_mode,
+        max_circuit_length,
+        rounding_accuracy,
+    )?;
+    let options = options.render_options();
+    if lenient {
+        let (typst_str, warnings) = circuit_into_lenient_typst_str(&circuit, &options)
+            .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
+        emit_warnings(warnings)?;
+        Ok(typst_str)
+    } else {
+        circuit_into_typst_str(&circuit, &options)
+            .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))
+    }
+}
+
+/// Returns the circuit's LaTeX representation, drawn with the quantikz package
+///
+/// Args:
+///     circuit (Circuit): The qoqo circuit to draw
+///     render_pragmas (Optional(str)): How to render Pragmas operations:  
+///        `"all"` to render every pragmas.
+///        `"none"` to not render any pragmas.
+///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
+///     initialization_mode (String): What to display at the begginning of the circuit. "state" for "|0>" and  
+///         "qubit" for "q[n]" State will be used if the parameter is not set.
+///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before starting a new
+///         quantikz environment. The default setting `None` does not split the circuit.
+///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuit representation, e.g. the
+///         layout mode, the number format or the wire labels. The arguments above override the
+///         corresponding options when they are set.
+///
+/// Returns:
+///     str: The circuit's quantikz representation.
+///
+/// Raises:
+///     TypeError: Circuit conversion error
+///     UnsupportedOperationError: Operation not supported
+#[pyfunction]
+#[pyo3(signature = (circuit, render_pragmas=None, initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, options=None))]
+pub fn circuit_to_quantikz_str(
+    circuit: &Bound<PyAny>,
+    render_pragmas: Option<&str>,
+    initialization_mode: Option<String>,
+    max_circuit_length: Option<usize>,
+    rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+) -> PyResult<String> {
+    let circuit = convert_into_circuit(circuit).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
+        initialization_mode,
+        max_circuit_length,
+        rounding_accuracy,
+    )?;
+    circuit_into_quantikz_str(&circuit, &options.render_options())
+        .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))
+}
+
+/// Returns the circuit's text representation, drawn with box-drawing or ASCII characters
+///
+/// Args:
+///     circuit (Circuit): The qoqo circuit to draw
+///     render_pragmas (Optional(str)): How to render Pragmas operations:  
+///        `"all"` to render every pragmas.
+///        `"none"` to not render any pragmas.
+///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
+///     initialization_mode (String): What to display at the begginning of the circuit. "state" for "|0>" and  
+///         "qubit" for "q[n]" State will be used if the parameter is not set.
+///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuit representation, e.g. the
+///         layout mode, the number format or the wire labels. The arguments above override the
+///         corresponding options when they are set.
+///     charset (str): The characters used to draw the circuit. "unicode" for box-drawing
+///         characters and "ascii" for plain ASCII characters.
+///
+/// Returns:
+///     str: The circuit's text representation.
+///
+/// Raises:
+///     TypeError: Circuit conversion error
+///     UnsupportedOperationError: Operation not supported
+#[pyfunction]
+#[pyo3(signature = (circuit, render_pragmas=None, initialization_mode=None, rounding_accuracy=None, options=None, charset="unicode"))]
+pub fn circuit_to_text_str(
+    circuit: &Bound<PyAny>,
+    render_pragmas: Option<&str>,
+    initialization_mode: Option<String>,
+    rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+    charset: &str,
+) -> PyResult<String> {
+    let circuit = convert_into_circuit(circuit).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
+        initialization_mode,
+        None,
+        rounding_accuracy,
+    )?;
+    let charset = TextCharset::from_str(charset)
+        .map_err(|x| roqollage_error_to_py("Charset not accepted", x))?;
+    circuit_into_text_str(&circuit, charset, &options.render_options())
+        .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))
+}
+
+/// Displays the connectivity graph of a qoqo device as an image output
+///
+/// The qubits are annotated with their single-qubit gate times and decoherence rates and the
+/// edges with their two-qubit gate times.
+///
+/// Args:
+///     device (Device): The qoqo device to draw (AllToAllDevice, SquareLatticeDevice, GenericDevice...)
+///     pixel_per_point (Option<f32>): The pixels per point ration of the image.  
+///        The higher the value, the bigger the image will be but the longer it will take to render  
+///     options (Optional(RenderOptions)): The options giving the format of the displayed numbers,
+///         the other options are not used.
+///     cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+///         Defaults to the `qollage` directory in the user cache directory.
+///     font_paths (Optional(List[str])): Additional font files or directories containing font files.
+///     package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+///         or a local directory containing the Typst packages.
+///
+/// Raises:
+///     TypeError: Device conversion error
+///     ValueError: Drawing error
+#[pyfunction]
+#[pyo3(signature = (device, pixel_per_point=3.0, options=None, cache_dir=None, font_paths=None, package_source=None))]
+pub fn draw_device(
+    device: &Bound<PyAny>,
+    pixel_per_point: f32,
+    options: Option<RenderOptionsWrapper>,
+    cache_dir: Option<PathBuf>,
+    font_paths: Option<Vec<PathBuf>>,
+    package_source: Option<String>,
+) -> PyResult<()> {
+    let device = convert_into_device(device)?;
+    let backend_config = backend_config_from_args(cache_dir, font_paths, package_source)?;
+    let image = device_to_image(
+        &device,
+        Some(pixel_per_point),
+        Some(options.unwrap_or_default().render_options().number_format),
+        Some(&backend_config),
+    )
+    .map_err(|x| roqollage_error_to_py("Error during Device drawing", x))?;
+    display_image(image)
+}
+
+/// Returns the Typst representation of the connectivity graph of a qoqo device
+///
+/// Args:
+///     device (Device): The qoqo device to draw (AllToAllDevice, SquareLatticeDevice, GenericDevice...)
+///     options (Optional(RenderOptions)): The options giving the format of the displayed numbers,
+///         the other options are not used.
+///
+/// Returns:
+///     str: The device's representation in Typst.
+///
+/// Raises:
+///     TypeError: Device conversion error
+#[pyfunction]
+#[pyo3(signature = (device, options=None))]
+pub fn device_to_typst_str(
+    device: &Bound<PyAny>,
+    options: Option<RenderOptionsWrapper>,
+) -> PyResult<String> {
+    let device = convert_into_device(device)?;
+    Ok(device_into_typst_str(
+        &device,
+        Some(options.unwrap_or_default().render_options().number_format),
+    ))
+}
+
+/// Displays the qoqo circuit on a time axis as an image output
+///
+/// The position and the width of each operation are proportional to its start time and its
+/// duration. The durations are taken from the `durations` dictionary, then from the gate times of
+/// the device, then from `default_duration`. PragmaSleep operations are drawn with their sleep
+/// time and the other pragmas only if they appear in `durations`.
+///
+/// Args:
+///     circuit (Circuit): The qoqo circuit to draw
+///     device (Optional(Device)): The device providing the gate times.
+///     durations (Optional(Dict[str, float])): The durations of the operations by name,
+///         overriding the gate times of the device.
+///     default_duration (Optional(float)): The duration of the operations without known duration.
+///         An error is raised for these operations if not set.
+///     pixel_per_point (Option<f32>): The pixels per point ration of the image.  
+///        The higher the value, the bigger the image will be but the longer it will take to render  
+///     options (Optional(RenderOptions)): The options giving the format of the displayed numbers,
+///         the other options are not used.
+///     cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+///         Defaults to the `qollage` directory in the user cache directory.
+///     font_paths (Optional(List[str])): Additional font files or directories containing font files.
+///     package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+///         or a local directory containing the Typst packages.
+///
+/// Raises:
+///     TypeError: Circuit or Device conversion error
+///     InvalidOperationError: Unknown operation duration | Symbolic sleep time
+#[pyfunction]
+#[pyo3(signature = (circuit, device=None, durations=None, default_duration=None, pixel_per_point=3.0, options=None, cache_dir=None, font_paths=None, package_source=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn draw_circuit_timing(
+    circuit: &Bound<PyAny>,
+    device: Option<&Bound<PyAny>>,
+    durations: Option<HashMap<String, f64>>,
+    default_duration: Option<f64>,
+    pixel_per_point: f32,
+    options: Option<RenderOptionsWrapper>,
+    cache_dir: Option<PathBuf>,
+    font_paths: Option<Vec<PathBuf>>,
+    package_source: Option<String>,
+) -> PyResult<()> {
+    let circuit = convert_into_circuit(circuit).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let device = device.map(convert_into_device).transpose()?;
+    let backend_config = backend_config_from_args(cache_dir, font_paths, package_source)?;
+    let image = circuit_timing_to_image(
+        &circuit,
+        Some(pixel_per_point),
+        device.as_ref().map(|device| device as &dyn Device),
+        &durations.unwrap_or_default(),
+        default_duration,
+        Some(options.unwrap_or_default().render_options().number_format),
+        Some(&backend_config),
+    )
+    .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
+    display_image(image)
+}
+
+/// Returns the Typst representation of the qoqo circuit on a time axis
+///
+/// Args:
+///     circuit (Circuit): The qoqo circuit to draw
+///     device (Optional(Device)): The device providing the gate times.
+///     durations (Optional(Dict[str, float])): The durations of the operations by name,
+///         overriding the gate times of the device.
+///     default_duration (Optional(float)): The duration of the operations without known duration.
+///         An error is raised for these operations if not set.
+///     options (Optional(RenderOptions)): The options giving the format of the displayed numbers,
+///         the other options are not used.
+///
+/// Returns:
+///     str: The circuit's timing representation in Typst.
+///
+/// Raises:
+///     TypeError: Circuit or Device conversion error
+///     InvalidOperationError: Unknown operation duration | Symbolic sleep time
+#[pyfunction]
+#[pyo3(signature = (circuit, device=None, durations=None, default_duration=None, options=None))]
+pub fn circuit_to_timing_typst_str(
+    circuit: &Bound<PyAny>,
+    device: Option<&Bound<PyAny>>,
+    durations: Option<HashMap<String, f64>>,
+    default_duration: Option<f64>,
+    options: Option<RenderOptionsWrapper>,
+) -> PyResult<String> {
+    let circuit = convert_into_circuit(circuit).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let device = device.map(convert_into_device).transpose()?;
+    circuit_into_timing_typst_str(
         &circuit,
-        RenderPragmas::from_str(render_pragmas).unwrap(),
+        device.as_ref().map(|device| device as &dyn Device),
+        &durations.unwrap_or_default(),
+        default_duration,
+        Some(options.unwrap_or_default().render_options().number_format),
+    )
+    .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))
+}
+
+/// Displays the differences between two qoqo circuits as an image output
+///
+/// The operations of both circuits are drawn in a single circuit, aligned on their longest
+/// common sequence of operations: the added operations are filled in green, the removed ones in
+/// red and the ones whose parameters changed in yellow.
+///
+/// Args:
+///     before (Circuit): The first qoqo circuit
+///     after (Circuit): The second qoqo circuit
+///     pixel_per_point (Option<f32>): The pixels per point ration of the image.  
+///        The higher the value, the bigger the image will be but the longer it will take to render  
+///     render_pragmas (Optional(str)): How to render Pragmas operations:  
+///        `"all"` to render every pragmas.
+///        `"none"` to not render any pragmas.
+///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
+///     initialization_mode (String): What to display at the begginning of the circuit. "state" for "|0>" and  
+///         "qubit" for "q[n]" State will be used if the parameter is not set.
+///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+///         The default setting `None` does not create a new line.
+///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuit representation, e.g. the
+///         layout mode or the number format. The arguments above override the corresponding
+///         options when they are set.
+///     cache_dir (Optional(str)): The directory where the font and the Typst packages are cached.
+///         Defaults to the `qollage` directory in the user cache directory.
+///     font_paths (Optional(List[str])): Additional font files or directories containing font files.
+///     package_source (Optional(str)): The url of the Typst packages registry (or of a mirror)
+///         or a local directory containing the Typst packages.
+///
+/// Raises:
+///     TypeError: Circuit conversion error
+///     UnsupportedOperationError: Operation not supported
+#[pyfunction]
+#[pyo3(signature = (before, after, pixel_per_point=3.0, render_pragmas=None, initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, options=None, cache_dir=None, font_paths=None, package_source=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn draw_circuits_diff(
+    before: &Bound<PyAny>,
+    after: &Bound<PyAny>,
+    pixel_per_point: f32,
+    render_pragmas: Option<&str>,
+    initialization_mode: Option<String>,
+    max_circuit_length: Option<usize>,
+    rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+    cache_dir: Option<PathBuf>,
+    font_paths: Option<Vec<PathBuf>>,
+    package_source: Option<String>,
+) -> PyResult<()> {
+    let before = convert_into_circuit(before).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let after = convert_into_circuit(after).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
         initialization_mode,
         max_circuit_length,
         rounding_accuracy,
+    )?;
+    let backend_config = backend_config_from_args(cache_dir, font_paths, package_source)?;
+    let image = circuits_diff_to_image(
+        &before,
+        &after,
+        Some(pixel_per_point),
+        &options.render_options(),
+        Some(&backend_config),
     )
-    .map_err(|x| PyValueError::new_err(format!("Error during Circuit drawing: {x:?}")))
+    .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))?;
+    display_image(image)
+}
+
+/// Returns the Typst representation of the differences between two qoqo circuits
+///
+/// Args:
+///     before (Circuit): The first qoqo circuit
+///     after (Circuit): The second qoqo circuit
+///     render_pragmas (Optional(str)): How to render Pragmas operations:  
+///        `"all"` to render every pragmas.
+///        `"none"` to not render any pragmas.
+///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
+///     initialization_mode (String): What to display at the begginning of the circuit. "state" for "|0>" and  
+///         "qubit" for "q[n]" State will be used if the parameter is not set.
+///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+///         The default setting `None` does not create a new line.
+///    rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     options (Optional(RenderOptions)): The options of the circuit representation, e.g. the
+///         layout mode or the number format. The arguments above override the corresponding
+///         options when they are set.
+///
+/// Returns:
+///     str: The representation of the differences in Typst.
+///
+/// Raises:
+///     TypeError: Circuit conversion error
+///     UnsupportedOperationError: Operation not supported
+#[pyfunction]
+#[pyo3(signature = (before, after, render_pragmas=None, initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, options=None))]
+pub fn circuits_diff_to_typst_str(
+    before: &Bound<PyAny>,
+    after: &Bound<PyAny>,
+    render_pragmas: Option<&str>,
+    initialization_mode: Option<String>,
+    max_circuit_length: Option<usize>,
+    rounding_accuracy: Option<usize>,
+    options: Option<RenderOptionsWrapper>,
+) -> PyResult<String> {
+    let before = convert_into_circuit(before).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let after = convert_into_circuit(after).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let options = render_options_from_args(
+        options,
+        render_pragmas,
+        initialization_mode,
+        max_circuit_length,
+        rounding_accuracy,
+    )?;
+    circuits_diff_into_typst_str(&before, &after, &options.render_options())
+        .map_err(|x| roqollage_error_to_py("Error during Circuit drawing", x))
+}
+
+/// Returns the statistics of the qoqo circuit
+///
+/// Args:
+///     circuit (Circuit): The qoqo circuit to inspect
+///     render_pragmas (Optional(str)): How to render Pragmas operations:  
+///        `"all"` to render every pragmas.
+///        `"none"` to not render any pragmas.
+///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.  
+///     options (Optional(RenderOptions)): The options giving the layout mode the depth is computed
+///         with and the pragmas rendered, overridden by render_pragmas if it is set.
+///
+/// Returns:
+///     Dict[str, Any]: The number of qubits, bosonic modes and classical registers, the depth
+///         of the drawn circuit, the number of gates of each name, of two-qubit gates and of
+///         measurements, the symbolic parameters and the number of hidden pragmas of each name.
+///         The operations of the PragmaLoop and PragmaConditional bodies are counted once.
+///
+/// Raises:
+///     TypeError: Circuit conversion error
+///     UnsupportedOperationError: Operation not supported
+#[pyfunction]
+#[pyo3(signature = (circuit, render_pragmas=None, options=None))]
+pub fn circuit_statistics<'py>(
+    py: Python<'py>,
+    circuit: &Bound<'py, PyAny>,
+    render_pragmas: Option<&str>,
+    options: Option<RenderOptionsWrapper>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let circuit = convert_into_circuit(circuit).map_err(|x| {
+        PyTypeError::new_err(format!("Cannot convert python object to Circuit: {x:?}"))
+    })?;
+    let options = render_options_from_args(options, render_pragmas, None, None, None)?;
+    let options = options.render_options();
+    let statistics =
+        circuit_into_statistics(&circuit, options.render_pragmas, Some(options.layout_mode))
+            .map_err(|x| roqollage_error_to_py("Error during Circuit inspection", x))?;
+    let dict = PyDict::new(py);
+    dict.set_item("number_qubits", statistics.number_qubits)?;
+    dict.set_item("number_modes", statistics.number_modes)?;
+    dict.set_item(
+        "number_classical_registers",
+        statistics.number_classical_registers,
+    )?;
+    dict.set_item("depth", statistics.depth)?;
+    dict.set_item("gate_counts", statistics.gate_counts)?;
+    dict.set_item("two_qubit_gate_count", statistics.two_qubit_gate_count)?;
+    dict.set_item("measurement_count", statistics.measurement_count)?;
+    dict.set_item(
+        "symbolic_parameters",
+        statistics
+            .symbolic_parameters
+            .into_iter()
+            .collect::<Vec<String>>(),
+    )?;
+    dict.set_item("hidden_pragmas", statistics.hidden_pragmas)?;
+    Ok(dict)
 }
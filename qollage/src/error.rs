@@ -0,0 +1,119 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::{create_exception, exceptions::PyValueError, prelude::*};
+use roqollage::RoqollageError;
+
+create_exception!(
+    qollage,
+    QollageError,
+    PyValueError,
+    "Base class of the errors raised by qollage when drawing."
+);
+create_exception!(
+    qollage,
+    NetworkError,
+    QollageError,
+    "A font or a Typst package couldn't be downloaded."
+);
+create_exception!(
+    qollage,
+    CacheError,
+    QollageError,
+    "A file of the cache directory couldn't be created, read or written."
+);
+create_exception!(
+    qollage,
+    TypstCompileError,
+    QollageError,
+    "The Typst compiler rejected the generated source."
+);
+create_exception!(
+    qollage,
+    UnsupportedOperationError,
+    QollageError,
+    "An operation of the circuit is not supported by the Typst backend."
+);
+create_exception!(
+    qollage,
+    InvalidOperationError,
+    QollageError,
+    "An operation of the circuit can't be drawn."
+);
+create_exception!(
+    qollage,
+    InvalidOptionError,
+    QollageError,
+    "An option given to the drawing function is not valid."
+);
+create_exception!(
+    qollage,
+    RenderError,
+    QollageError,
+    "The compiled document couldn't be exported to an image or a document."
+);
+
+/// Converts a roqollage error to the matching python exception.
+///
+/// The index of the operation causing the error, if any, is set as the `index` attribute of the
+/// exception.
+///
+/// # Arguments
+///
+/// * `context` - What was being done when the error happened, prepended to the message.
+/// * `err` - The error to convert.
+///
+/// # Returns
+///
+/// * `PyErr` - The python exception.
+pub(crate) fn roqollage_error_to_py(context: &str, err: RoqollageError) -> PyErr {
+    let msg = format!("{context}: {err}");
+    let index = err.index();
+    let py_err = match err {
+        RoqollageError::NetworkError { .. } => NetworkError::new_err(msg),
+        RoqollageError::CacheError { .. } => CacheError::new_err(msg),
+        RoqollageError::TypstCompileError { .. } => TypstCompileError::new_err(msg),
+        RoqollageError::UnsupportedOperation { .. } => UnsupportedOperationError::new_err(msg),
+        RoqollageError::InvalidOperation { .. } => InvalidOperationError::new_err(msg),
+        RoqollageError::InvalidOption { .. } => InvalidOptionError::new_err(msg),
+        RoqollageError::RenderError { .. } => RenderError::new_err(msg),
+    };
+    Python::with_gil(|py| {
+        // Setting an attribute on a freshly created exception can't fail.
+        let _ = py_err.value(py).setattr("index", index);
+    });
+    py_err
+}
+
+/// Registers the qollage exceptions in the python module.
+///
+/// # Arguments
+///
+/// * `module` - The qollage python module.
+pub(crate) fn add_exceptions(module: &Bound<PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add("QollageError", py.get_type::<QollageError>())?;
+    module.add("NetworkError", py.get_type::<NetworkError>())?;
+    module.add("CacheError", py.get_type::<CacheError>())?;
+    module.add("TypstCompileError", py.get_type::<TypstCompileError>())?;
+    module.add(
+        "UnsupportedOperationError",
+        py.get_type::<UnsupportedOperationError>(),
+    )?;
+    module.add(
+        "InvalidOperationError",
+        py.get_type::<InvalidOperationError>(),
+    )?;
+    module.add("InvalidOptionError", py.get_type::<InvalidOptionError>())?;
+    module.add("RenderError", py.get_type::<RenderError>())?;
+    Ok(())
+}
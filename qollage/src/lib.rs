@@ -27,11 +27,28 @@ use pyo3::prelude::*;
 
 mod backend;
 pub use backend::*;
+mod error;
+pub use error::*;
+mod options;
+pub use options::*;
 
 #[pymodule]
 fn qollage(_py: Python, module: &Bound<PyModule>) -> PyResult<()> {
+    add_exceptions(module)?;
+    module.add_class::<RenderOptionsWrapper>()?;
     module.add_function(wrap_pyfunction!(draw_circuit, module)?)?;
+    module.add_function(wrap_pyfunction!(draw_quantum_program, module)?)?;
+    module.add_function(wrap_pyfunction!(draw_device, module)?)?;
+    module.add_function(wrap_pyfunction!(draw_circuit_timing, module)?)?;
+    module.add_function(wrap_pyfunction!(draw_circuits_diff, module)?)?;
     module.add_function(wrap_pyfunction!(save_circuit, module)?)?;
+    module.add_function(wrap_pyfunction!(save_circuits, module)?)?;
     module.add_function(wrap_pyfunction!(circuit_to_typst_str, module)?)?;
+    module.add_function(wrap_pyfunction!(circuit_to_text_str, module)?)?;
+    module.add_function(wrap_pyfunction!(circuit_to_quantikz_str, module)?)?;
+    module.add_function(wrap_pyfunction!(device_to_typst_str, module)?)?;
+    module.add_function(wrap_pyfunction!(circuit_to_timing_typst_str, module)?)?;
+    module.add_function(wrap_pyfunction!(circuits_diff_to_typst_str, module)?)?;
+    module.add_function(wrap_pyfunction!(circuit_statistics, module)?)?;
     Ok(())
 }
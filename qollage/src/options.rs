@@ -0,0 +1,264 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, str::FromStr};
+
+use pyo3::{exceptions::PyTypeError, prelude::*};
+use qoqo::devices::GenericDeviceWrapper;
+use roqollage::{
+    AngleUnit, ComplexStyle, FloatStyle, InitializationMode, LayoutMode, NumberFormat,
+    RenderOptions, RenderPragmas, WireLabels,
+};
+use roqoqo::devices::{Device, GenericDevice};
+
+use crate::roqollage_error_to_py;
+
+/// Options of the representation of a circuit, shared by the drawing functions
+///
+/// Args:
+///     render_pragmas (str): How to render Pragmas operations:
+///        `"all"` to render every pragmas.
+///        `"none"` to not render any pragmas.
+///        `"PragmaOperation1, PragmaOperation2"` to render only some pragmas.
+///     initialization_mode (Optional(str)): What to display at the begginning of the circuit.
+///         "state" for "|0>" and "qubit" for "q[n]". State will be used if the parameter is not set.
+///     max_circuit_length (Optional(int)): The maximum number of gates per qubit before going to a new line.
+///         The default setting `None` does not create a new line.
+///     rounding_accuracy (Optional(int)): The number of digits to round to when displaying floats.
+///     layout_mode (Optional(str)): How to place the operations in the columns. "sequential" to
+///         give each operation its own column, "asap" to place each operation as early as possible
+///         and "alap" to place each operation as late as possible. Sequential is used if the
+///         parameter is not set.
+///     page_per_chunk (bool): Whether to put each line of the circuit on a new page.
+///         Only used when saving a pdf document.
+///     device (Optional(Device)): The device the circuit is checked against. The gates it doesn't
+///         support and the two-qubit gates on qubits it doesn't connect are highlighted and listed
+///         in a legend below the circuit.
+///     statistics (bool): Whether to add a table summarizing the circuit below the circuit.
+///     float_style (Optional(str)): How the floats are written. "symbolic" to write the multiples
+///         of π, the fractions and the square roots symbolically, "decimal" to write decimal
+///         numbers and "degrees" to write the multiples of π in degrees. Symbolic is used if the
+///         parameter is not set.
+///     max_denominator (Optional(int)): The largest denominator of the fractions recognized in the
+///         floats, 16 if the parameter is not set.
+///     angle_unit (Optional(str)): The unit of the float rotation angles of the operations,
+///         "radians", "degrees" or "turns", the other parameters are left unchanged. Radians are
+///         used if the parameter is not set.
+///     max_state_size (Optional(int)): The largest number of kets of the state vectors, and of
+///         rows and columns of the density matrices, displayed before an ellipsis. 8 if the
+///         parameter is not set.
+///     complex_style (Optional(str)): How the complex numbers are written. "cartesian" for a real
+///         and an imaginary part and "polar" for a modulus and a phase. Cartesian is used if the
+///         parameter is not set.
+///     qubit_labels (Optional(Dict[int, str])): The labels of the qubit wires by qubit index, e.g.
+///         `{0: "anc", 1: "data_0"}`, replacing "q[n]". The labels are Typst math, in which the
+///         words that aren't Typst symbols are written as text.
+///     mode_labels (Optional(Dict[int, str])): The labels of the bosonic mode wires by mode index,
+///         replacing "b[n]".
+///     register_labels (Optional(Dict[str, str])): The labels of the classical register wires by
+///         register name, replacing the register names.
+///     initial_states (Optional(Dict[int, str])): The initial states of the qubit wires by qubit
+///         index, e.g. `{0: "|+>", 1: "|1>"}`, replacing "|0>".
+///
+/// Raises:
+///     TypeError: Device conversion error
+///     InvalidOptionError: Option not accepted
+#[pyclass(name = "RenderOptions", module = "qollage")]
+#[derive(Debug, Clone)]
+pub struct RenderOptionsWrapper {
+    render_pragmas: RenderPragmas,
+    initialization_mode: Option<InitializationMode>,
+    max_length: Option<usize>,
+    number_format: NumberFormat,
+    layout_mode: LayoutMode,
+    page_per_chunk: bool,
+    device: Option<GenericDevice>,
+    statistics: bool,
+    wire_labels: WireLabels,
+}
+
+impl Default for RenderOptionsWrapper {
+    fn default() -> Self {
+        let options = RenderOptions::default();
+        Self {
+            render_pragmas: options.render_pragmas,
+            initialization_mode: options.initialization_mode,
+            max_length: options.max_length,
+            number_format: options.number_format,
+            layout_mode: options.layout_mode,
+            page_per_chunk: options.page_per_chunk,
+            device: None,
+            statistics: options.statistics,
+            wire_labels: options.wire_labels,
+        }
+    }
+}
+
+#[pymethods]
+impl RenderOptionsWrapper {
+    /// Creates the options of the representation of a circuit.
+    #[new]
+    #[pyo3(signature = (render_pragmas="all", initialization_mode=None, max_circuit_length=None, rounding_accuracy=None, layout_mode=None, page_per_chunk=false, device=None, statistics=false, float_style=None, max_denominator=None, angle_unit=None, max_state_size=None, complex_style=None, qubit_labels=None, mode_labels=None, register_labels=None, initial_states=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        render_pragmas: &str,
+        initialization_mode: Option<String>,
+        max_circuit_length: Option<usize>,
+        rounding_accuracy: Option<usize>,
+        layout_mode: Option<String>,
+        page_per_chunk: bool,
+        device: Option<&Bound<PyAny>>,
+        statistics: bool,
+        float_style: Option<String>,
+        max_denominator: Option<u64>,
+        angle_unit: Option<String>,
+        max_state_size: Option<usize>,
+        complex_style: Option<String>,
+        qubit_labels: Option<HashMap<usize, String>>,
+        mode_labels: Option<HashMap<usize, String>>,
+        register_labels: Option<HashMap<String, String>>,
+        initial_states: Option<HashMap<usize, String>>,
+    ) -> PyResult<Self> {
+        let options = render_options_from_args(
+            None,
+            Some(render_pragmas),
+            initialization_mode,
+            max_circuit_length,
+            rounding_accuracy,
+        )?;
+        let number_format = NumberFormat {
+            float_style: float_style
+                .map(|style| FloatStyle::from_str(&style))
+                .transpose()
+                .map_err(|x| roqollage_error_to_py("Float style not accepted", x))?
+                .unwrap_or_default(),
+            max_denominator: max_denominator.unwrap_or(options.number_format.max_denominator),
+            angle_unit: angle_unit
+                .map(|unit| AngleUnit::from_str(&unit))
+                .transpose()
+                .map_err(|x| roqollage_error_to_py("Angle unit not accepted", x))?
+                .unwrap_or_default(),
+            max_state_size: max_state_size.unwrap_or(options.number_format.max_state_size),
+            complex_style: complex_style
+                .map(|style| ComplexStyle::from_str(&style))
+                .transpose()
+                .map_err(|x| roqollage_error_to_py("Complex style not accepted", x))?
+                .unwrap_or_default(),
+            ..options.number_format
+        };
+        Ok(Self {
+            number_format,
+            layout_mode: layout_mode
+                .map(|mode| LayoutMode::from_str(&mode))
+                .transpose()
+                .map_err(|x| roqollage_error_to_py("Layout mode not accepted", x))?
+                .unwrap_or_default(),
+            page_per_chunk,
+            device: device.map(convert_into_device).transpose()?,
+            statistics,
+            wire_labels: WireLabels {
+                qubits: qubit_labels.unwrap_or_default(),
+                modes: mode_labels.unwrap_or_default(),
+                registers: register_labels.unwrap_or_default(),
+                initial_states: initial_states.unwrap_or_default(),
+            },
+            ..options
+        })
+    }
+}
+
+impl RenderOptionsWrapper {
+    /// Returns the roqollage options, borrowing the device.
+    ///
+    /// # Returns
+    ///
+    /// * `RenderOptions` - The options of the representation of a circuit.
+    pub fn render_options(&self) -> RenderOptions<'_> {
+        RenderOptions {
+            render_pragmas: self.render_pragmas.clone(),
+            initialization_mode: self.initialization_mode,
+            max_length: self.max_length,
+            number_format: self.number_format,
+            layout_mode: self.layout_mode,
+            page_per_chunk: self.page_per_chunk,
+            device: self
+                .device
+                .as_ref()
+                .map(|device| device as &(dyn Device + Sync)),
+            statistics: self.statistics,
+            wire_labels: self.wire_labels.clone(),
+        }
+    }
+}
+
+/// Creates the options of the representation of a circuit from the python arguments.
+///
+/// The arguments set override the corresponding fields of the options object.
+///
+/// # Arguments
+///
+/// * `options` - The options object, the default options if None.
+/// * `render_pragmas` - How to render Pragmas operations, the one of the options if None.
+/// * `initialization_mode` - What to display at the beginning of the circuit, the one of the
+///   options if None.
+/// * `max_circuit_length` - The maximum length of a circuit line, the one of the options if None.
+/// * `rounding_accuracy` - The number of digits to round to, the one of the options if None.
+///
+/// # Returns
+///
+/// * `PyResult<RenderOptionsWrapper>` - The options of the representation of a circuit.
+pub(crate) fn render_options_from_args(
+    options: Option<RenderOptionsWrapper>,
+    render_pragmas: Option<&str>,
+    initialization_mode: Option<String>,
+    max_circuit_length: Option<usize>,
+    rounding_accuracy: Option<usize>,
+) -> PyResult<RenderOptionsWrapper> {
+    let mut options = options.unwrap_or_default();
+    if let Some(render_pragmas) = render_pragmas {
+        options.render_pragmas = RenderPragmas::from_str(render_pragmas).map_err(|x| {
+            roqollage_error_to_py("Error: render_pragmas is not in a suitable format", x)
+        })?;
+    }
+    if let Some(initialization_mode) = initialization_mode {
+        options.initialization_mode = Some(
+            InitializationMode::from_str(&initialization_mode)
+                .map_err(|x| roqollage_error_to_py("Initialization mode not accepted", x))?,
+        );
+    }
+    if max_circuit_length.is_some() {
+        options.max_length = max_circuit_length;
+    }
+    if let Some(rounding_accuracy) = rounding_accuracy {
+        options.number_format.rounding_accuracy = rounding_accuracy;
+    }
+    Ok(options)
+}
+
+/// Converts a python qoqo device to a generic device.
+///
+/// Every qoqo device (AllToAllDevice, SquareLatticeDevice, GenericDevice or a device of a
+/// hardware package) can be turned into a GenericDevice with its `generic_device` method.
+///
+/// # Arguments
+///
+/// * `device` - The python device to convert.
+///
+/// # Returns
+///
+/// * `PyResult<GenericDevice>` - The converted device.
+pub(crate) fn convert_into_device(device: &Bound<PyAny>) -> PyResult<GenericDevice> {
+    device
+        .call_method0("generic_device")
+        .and_then(|generic_device| GenericDeviceWrapper::from_pyany(&generic_device))
+        .map_err(|x| PyTypeError::new_err(format!("Cannot convert python object to Device: {x:?}")))
+}
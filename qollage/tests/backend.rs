@@ -12,14 +12,36 @@
 //
 //! Testing the qollage Backend
 
-use std::{fs, path::Path};
+use std::{collections::HashMap, fs, path::Path};
 
-use pyo3::{types::PyAnyMethods, Bound, Py, Python};
-use qollage::{circuit_to_typst_str, draw_circuit, save_circuit};
-use qoqo::{operations::convert_operation_to_pyobject, CircuitWrapper};
+use pyo3::{
+    exceptions::PyValueError,
+    types::{PyAnyMethods, PyDictMethods},
+    Bound, Py, Python,
+};
+use qollage::{
+    circuit_statistics, circuit_to_quantikz_str, circuit_to_text_str, circuit_to_timing_typst_str,
+    circuit_to_typst_str, circuits_diff_to_typst_str, device_to_typst_str, draw_circuit,
+    draw_circuit_timing, draw_circuits_diff, draw_device, draw_quantum_program, save_circuit,
+    save_circuits, InvalidOptionError, QollageError, RenderOptionsWrapper,
+    UnsupportedOperationError,
+};
+use qoqo::{
+    devices::{AllToAllDeviceWrapper, SquareLatticeDeviceWrapper},
+    operations::convert_operation_to_pyobject,
+    CircuitWrapper, QuantumProgramWrapper,
+};
 use qoqo_calculator::CalculatorFloat;
 use qoqo_calculator_pyo3::CalculatorFloatWrapper;
-use roqoqo::{operations::*, Circuit};
+use roqoqo::{
+    measurements::{ClassicalRegister, PauliZProduct, PauliZProductInput},
+    operations::*,
+    Circuit, QuantumProgram,
+};
+use struqture::{
+    prelude::*,
+    spins::{PauliHamiltonian, PauliProduct},
+};
 
 // helper functions
 fn circuitpy_from_circuitru(py: Python, circuit: Circuit) -> Bound<CircuitWrapper> {
@@ -33,6 +55,15 @@ fn circuitpy_from_circuitru(py: Python, circuit: Circuit) -> Bound<CircuitWrappe
     circuitpy.to_owned()
 }
 
+/// Returns an operation the backend doesn't draw.
+fn constant_hamiltonian() -> ApplyConstantPauliHamiltonian {
+    let mut hamiltonian = PauliHamiltonian::new();
+    hamiltonian
+        .add_operator_product(PauliProduct::new().z(0).x(1), 1.0.into())
+        .unwrap();
+    ApplyConstantPauliHamiltonian::new(hamiltonian, 1.0.into())
+}
+
 #[test]
 fn test_file() {
     let mut circuit = Circuit::new();
@@ -62,7 +93,12 @@ fn test_file() {
             &circuitpy,
             None,
             1.5,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false,
             None,
             None,
             None
@@ -72,7 +108,12 @@ fn test_file() {
             &circuitpy,
             Some("..".into()),
             1.5,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false,
             None,
             None,
             None
@@ -82,7 +123,12 @@ fn test_file() {
             &circuitpy,
             Some("qollage/circuit.png".into()),
             1.5,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false,
             None,
             None,
             None
@@ -92,7 +138,12 @@ fn test_file() {
             &circuitpy,
             Some("../roqollage/circuit".into()),
             1.5,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false,
             None,
             None,
             None
@@ -102,9 +153,50 @@ fn test_file() {
             &circuitpy,
             None,
             1.5,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
             Some("Qubit".to_owned()),
             None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None
+        )
+        .is_ok());
+        assert!(save_circuit(
+            &circuitpy,
+            Some("circuit.svg".into()),
+            1.5,
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None
+        )
+        .is_ok());
+        assert!(save_circuit(
+            &circuitpy,
+            Some("circuit.pdf".into()),
+            1.5,
+            Some("PragmaOverrotation"),
+            None,
+            Some(3),
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all", None, None, None, None, true, None, false, None, None, None, None, None,
+                    None, None, None, None
+                )
+                .unwrap()
+            ),
+            false,
+            None,
+            None,
             None
         )
         .is_ok());
@@ -114,6 +206,8 @@ fn test_file() {
         "../circuit.png",
         "qollage/circuit.png",
         "../roqollage/circuit.png",
+        "circuit.svg",
+        "circuit.pdf",
     ];
     for path_str in paths {
         let read_in_path = Path::new(path_str);
@@ -122,6 +216,71 @@ fn test_file() {
     }
 }
 
+#[test]
+fn test_files() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+    let mut unsupported_circuit = circuit.clone();
+    unsupported_circuit.add_operation(PragmaStopParallelBlock::new(vec![], CalculatorFloat::ONE));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+        let unsupported_circuitpy = circuitpy_from_circuitru(py, unsupported_circuit);
+        let circuits = vec![
+            circuitpy.as_any().clone(),
+            unsupported_circuitpy.as_any().clone(),
+            circuitpy.as_any().clone(),
+        ];
+        let results = save_circuits(
+            py,
+            circuits.clone(),
+            vec![
+                "circuit_1.png".into(),
+                "circuit_unsupported.png".into(),
+                "circuit_2".into(),
+            ],
+            1.0,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(results[0].is_none());
+        assert!(results[1].is_some());
+        assert!(results[2].is_none());
+        assert!(save_circuits(
+            py,
+            circuits,
+            vec!["circuit_1.png".into()],
+            1.0,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None
+        )
+        .is_err());
+    });
+    assert!(!Path::new("circuit_unsupported.png").exists());
+    for path_str in ["circuit_1.png", "circuit_2.png"] {
+        let read_in_path = Path::new(path_str);
+        assert!(read_in_path.exists());
+        fs::remove_file(read_in_path).unwrap();
+    }
+}
+
 #[test]
 fn test_file_error() {
     let mut circuit = Circuit::new();
@@ -160,7 +319,12 @@ fn test_file_error() {
             calc.bind(py),
             None,
             1.5,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false,
             None,
             None,
             None
@@ -170,13 +334,21 @@ fn test_file_error() {
             &circuitpy,
             None,
             1.5,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
             Some("not_a_mode".to_owned()),
             None,
+            None,
+            None,
+            false,
+            None,
+            None,
             None
         )
         .is_err());
-        assert!(save_circuit(&circuitpy, None, 1.5, "all", None, None, None).is_err());
+        assert!(save_circuit(
+            &circuitpy, None, 1.5, None, None, None, None, None, false, None, None, None
+        )
+        .is_err());
     });
 }
 
@@ -206,10 +378,196 @@ fn test_str() {
     Python::with_gil(|py| {
         let circuitpy = circuitpy_from_circuitru(py, circuit);
 
-        assert!(circuit_to_typst_str(&circuitpy, "", None, None, None).is_ok());
-        assert!(
-            circuit_to_typst_str(&circuitpy, "all", Some("Qubit".to_owned()), None, None).is_ok()
-        );
+        assert!(circuit_to_typst_str(&circuitpy, Some(""), None, None, None, None, false).is_ok());
+        assert!(circuit_to_typst_str(
+            &circuitpy,
+            None,
+            Some("Qubit".to_owned()),
+            None,
+            None,
+            None,
+            false
+        )
+        .is_ok());
+        assert!(circuit_to_typst_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    Some("asap".to_owned()),
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None
+                )
+                .unwrap()
+            ),
+            false
+        )
+        .is_ok());
+        assert!(circuit_to_typst_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    Some("alap".to_owned()),
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None
+                )
+                .unwrap()
+            ),
+            false
+        )
+        .is_ok());
+    });
+}
+
+#[test]
+fn test_text_str() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::from("theta")));
+    circuit.add_operation(CNOT::new(0, 1));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+
+        let text = circuit_to_text_str(&circuitpy, None, None, None, None, "unicode").unwrap();
+        assert!(text.contains("┤H├"));
+        let text = circuit_to_text_str(
+            &circuitpy,
+            Some("none"),
+            Some("qubit".to_owned()),
+            Some(2),
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    Some("asap".to_owned()),
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+            "ascii",
+        )
+        .unwrap();
+        assert!(text.is_ascii());
+        assert!(text.starts_with("q[0]"));
+        assert!(circuit_to_text_str(&circuitpy, None, None, None, None, "latin1").is_err());
+    });
+}
+
+#[test]
+fn test_quantikz_str() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::from("theta")));
+    circuit.add_operation(CNOT::new(0, 1));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+
+        let latex = circuit_to_quantikz_str(&circuitpy, None, None, None, None, None).unwrap();
+        assert!(latex.starts_with("\\begin{quantikz}"));
+        assert!(latex.contains("\\ctrl{1}"));
+        let latex = circuit_to_quantikz_str(
+            &circuitpy,
+            Some("none"),
+            Some("qubit".to_owned()),
+            Some(1),
+            Some(2),
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    Some("asap".to_owned()),
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+        )
+        .unwrap();
+        assert!(latex.matches("\\begin{quantikz}").count() > 1);
+        assert!(RenderOptionsWrapper::new(
+            "all",
+            None,
+            None,
+            None,
+            Some("fast".to_owned()),
+            false,
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err());
     });
 }
 
@@ -247,18 +605,47 @@ fn test_str_error() {
         )
         .unwrap();
 
-        assert!(
-            circuit_to_typst_str(calc.bind(py), "PragmaOverrotation", None, None, None).is_err()
-        );
+        assert!(circuit_to_typst_str(
+            calc.bind(py),
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false
+        )
+        .is_err());
         assert!(circuit_to_typst_str(
             &circuitpy,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
+            Some("not_a_mode".to_owned()),
+            None,
+            None,
+            None,
+            false
+        )
+        .is_err());
+        assert!(RenderOptionsWrapper::new(
+            "all",
+            None,
+            None,
+            None,
             Some("not_a_mode".to_owned()),
+            false,
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             None,
             None
         )
         .is_err());
-        assert!(circuit_to_typst_str(&circuitpy, "", None, None, None).is_err());
+        assert!(circuit_to_typst_str(&circuitpy, Some(""), None, None, None, None, false).is_err());
     });
 }
 
@@ -288,13 +675,31 @@ fn test_draw() {
     Python::with_gil(|py| {
         let circuitpy = circuitpy_from_circuitru(py, circuit);
 
-        assert!(draw_circuit(&circuitpy, 0.5, "none", None, None, None).is_ok());
         assert!(draw_circuit(
             &circuitpy,
             0.5,
-            "PragmaOverrotation",
+            Some("none"),
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None
+        )
+        .is_ok());
+        assert!(draw_circuit(
+            &circuitpy,
+            0.5,
+            Some("PragmaOverrotation"),
             Some("State".to_owned()),
             None,
+            None,
+            None,
+            false,
+            None,
+            None,
             None
         )
         .is_ok());
@@ -340,16 +745,879 @@ fn test_draw_error() {
         )
         .unwrap();
 
-        assert!(draw_circuit(calc.bind(py), 0.5, "PragmaOverrotation", None, None, None).is_err());
+        assert!(draw_circuit(
+            calc.bind(py),
+            0.5,
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None
+        )
+        .is_err());
         assert!(draw_circuit(
             &circuitpy,
             0.5,
-            "PragmaOverrotation",
+            Some("PragmaOverrotation"),
             Some("not_a_mode".to_owned()),
             None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None
+        )
+        .is_err());
+        assert!(draw_circuit(
+            &circuitpy,
+            0.5,
+            Some("PragmaOverrotation"),
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
             None
         )
         .is_err());
-        assert!(draw_circuit(&circuitpy, 0.5, "PragmaOverrotation", None, None, None).is_err());
+    });
+}
+
+#[test]
+fn test_draw_quantum_program() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::from("theta")));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(PragmaRepeatedMeasurement::new("ro".to_owned(), 10, None));
+
+    let mut input = PauliZProductInput::new(2, false);
+    let index = input
+        .add_pauliz_product("ro".to_owned(), vec![0, 1])
+        .unwrap();
+    input
+        .add_linear_exp_val("zz".to_owned(), [(index, 1.0)].into_iter().collect())
+        .unwrap();
+    let pauli_z_program = QuantumProgram::PauliZProduct {
+        measurement: PauliZProduct {
+            constant_circuit: Some(circuit.clone()),
+            circuits: vec![circuit.clone()],
+            input,
+        },
+        input_parameter_names: vec!["theta".to_owned()],
+    };
+    let classical_program = QuantumProgram::ClassicalRegister {
+        measurement: ClassicalRegister {
+            constant_circuit: None,
+            circuits: vec![circuit.clone()],
+        },
+        input_parameter_names: vec![],
+    };
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        for program in [pauli_z_program, classical_program] {
+            let programpy = Py::new(py, QuantumProgramWrapper { internal: program }).unwrap();
+            assert!(draw_quantum_program(
+                programpy.bind(py),
+                0.5,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None
+            )
+            .is_ok());
+        }
+
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+        assert!(draw_quantum_program(
+            &circuitpy, 0.5, None, None, None, None, None, None, None, None
+        )
+        .is_err());
+    });
+}
+
+#[test]
+fn test_device() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let square_lattice = py
+            .get_type::<SquareLatticeDeviceWrapper>()
+            .call1((2, 2, vec!["RotateZ"], vec!["CNOT"], 1.0))
+            .unwrap();
+        let typst_str = device_to_typst_str(&square_lattice, None).unwrap();
+        assert!(typst_str.contains("Qubits: 4, two-qubit edges: 4"));
+        assert!(draw_device(&square_lattice, 0.5, None, None, None, None).is_ok());
+
+        let all_to_all = py
+            .get_type::<AllToAllDeviceWrapper>()
+            .call1((3, vec!["RotateX"], vec!["CZ"], 0.5))
+            .unwrap();
+        let typst_str = device_to_typst_str(
+            &all_to_all,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    Some(1),
+                    None,
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+        )
+        .unwrap();
+        assert!(typst_str.contains("Qubits: 3, two-qubit edges: 3"));
+
+        let mut circuit = Circuit::new();
+        circuit.add_operation(CNOT::new(0, 3));
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+        let typst_str = circuit_to_typst_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    Some(&square_lattice),
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+            false,
+        )
+        .unwrap();
+        assert!(typst_str.contains("*Device violations: 1*"));
+        assert!(device_to_typst_str(circuitpy.as_any(), None).is_err());
+        assert!(RenderOptionsWrapper::new(
+            "all",
+            None,
+            None,
+            None,
+            None,
+            false,
+            Some(circuitpy.as_any()),
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err());
+    });
+}
+
+#[test]
+fn test_circuit_timing() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateZ::new(0, CalculatorFloat::from(0.5)));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(PragmaSleep::new(vec![1], CalculatorFloat::from(2.0)));
+    circuit.add_operation(Hadamard::new(1));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+        let device = py
+            .get_type::<AllToAllDeviceWrapper>()
+            .call1((2, vec!["RotateZ"], vec!["CNOT"], 0.5))
+            .unwrap();
+        let durations: std::collections::HashMap<String, f64> =
+            [("Hadamard".to_owned(), 0.2)].into_iter().collect();
+
+        let typst_str = circuit_to_timing_typst_str(
+            &circuitpy,
+            Some(&device),
+            Some(durations.clone()),
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(typst_str.contains("[Time]"));
+        assert!(circuit_to_timing_typst_str(&circuitpy, Some(&device), None, None, None).is_err());
+        assert!(circuit_to_timing_typst_str(&circuitpy, None, None, Some(1.0), None).is_ok());
+        assert!(draw_circuit_timing(
+            &circuitpy,
+            Some(&device),
+            Some(durations),
+            None,
+            0.5,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_ok());
+    });
+}
+
+#[test]
+fn test_circuits_diff() {
+    let mut before = Circuit::new();
+    before.add_operation(Hadamard::new(0));
+    before.add_operation(RotateX::new(1, CalculatorFloat::from(0.3)));
+    let mut after = Circuit::new();
+    after.add_operation(Hadamard::new(0));
+    after.add_operation(RotateX::new(1, CalculatorFloat::from(0.5)));
+    after.add_operation(CNOT::new(0, 1));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let beforepy = circuitpy_from_circuitru(py, before);
+        let afterpy = circuitpy_from_circuitru(py, after);
+
+        let typst_str =
+            circuits_diff_to_typst_str(&beforepy, &afterpy, None, None, None, None, None).unwrap();
+        assert!(typst_str.contains("added: 1"));
+        assert!(typst_str.contains("changed: 1"));
+        assert!(circuits_diff_to_typst_str(
+            &beforepy,
+            &afterpy,
+            None,
+            Some("wrong".to_owned()),
+            None,
+            None,
+            None
+        )
+        .is_err());
+        assert!(draw_circuits_diff(
+            &beforepy, &afterpy, 0.5, None, None, None, None, None, None, None, None
+        )
+        .is_ok());
+    });
+}
+
+#[test]
+fn test_circuit_statistics() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(RotateZ::new(1, CalculatorFloat::from("theta")));
+    circuit.add_operation(PragmaSleep::new(vec![1], CalculatorFloat::from(1.0)));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+
+        let statistics = circuit_statistics(py, &circuitpy, Some("None"), None).unwrap();
+        let number_qubits: usize = statistics
+            .get_item("number_qubits")
+            .unwrap()
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(number_qubits, 2);
+        let two_qubit_gate_count: usize = statistics
+            .get_item("two_qubit_gate_count")
+            .unwrap()
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(two_qubit_gate_count, 1);
+        let symbolic_parameters: Vec<String> = statistics
+            .get_item("symbolic_parameters")
+            .unwrap()
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(symbolic_parameters, vec!["theta".to_owned()]);
+        let hidden_pragmas: std::collections::HashMap<String, usize> = statistics
+            .get_item("hidden_pragmas")
+            .unwrap()
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(hidden_pragmas.get("PragmaSleep"), Some(&1));
+        assert!(RenderOptionsWrapper::new(
+            "all",
+            None,
+            None,
+            None,
+            Some("wrong".to_owned()),
+            false,
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err());
+
+        let typst_str = circuit_to_typst_str(
+            &circuitpy,
+            Some("None"),
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all", None, None, None, None, false, None, true, None, None, None, None, None,
+                    None, None, None, None,
+                )
+                .unwrap(),
+            ),
+            false,
+        )
+        .unwrap();
+        assert!(typst_str.contains("Circuit statistics"));
+    });
+}
+
+#[test]
+fn test_lenient() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(constant_hamiltonian());
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+
+        assert!(circuit_to_typst_str(&circuitpy, None, None, None, None, None, false).is_err());
+        let typst_str =
+            circuit_to_typst_str(&circuitpy, None, None, None, None, None, true).unwrap();
+        assert!(typst_str.contains("ApplyConstantPauliHamiltonian"));
+        assert!(draw_circuit(
+            &circuitpy, 0.5, None, None, None, None, None, true, None, None, None
+        )
+        .is_ok());
+        assert!(save_circuit(
+            &circuitpy,
+            Some("lenient_circuit.svg".into()),
+            1.0,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None
+        )
+        .is_err());
+        for path in ["lenient_circuit.svg", "lenient_circuit.pdf"] {
+            assert!(save_circuit(
+                &circuitpy,
+                Some(path.into()),
+                1.0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                true,
+                None,
+                None,
+                None
+            )
+            .is_ok());
+        }
+        let results = save_circuits(
+            py,
+            vec![circuitpy.as_any().clone()],
+            vec!["lenient_circuit.png".into()],
+            1.0,
+            None,
+            None,
+            None,
+            None,
+            None,
+            true,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(results[0].is_none());
+    });
+    for path_str in [
+        "lenient_circuit.svg",
+        "lenient_circuit.pdf",
+        "lenient_circuit.png",
+    ] {
+        let read_in_path = Path::new(path_str);
+        assert!(read_in_path.exists());
+        fs::remove_file(read_in_path).unwrap();
+    }
+}
+
+#[test]
+fn test_render_options() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(CNOT::new(0, 3));
+    circuit.add_operation(PragmaSleep::new(vec![0], 1.0.into()));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+        let square_lattice = py
+            .get_type::<SquareLatticeDeviceWrapper>()
+            .call1((2, 2, vec!["RotateZ"], vec!["CNOT"], 1.0))
+            .unwrap();
+        let options = RenderOptionsWrapper::new(
+            "none",
+            None,
+            None,
+            None,
+            None,
+            false,
+            Some(&square_lattice),
+            true,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let typst_str = circuit_to_typst_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            None,
+            Some(options.clone()),
+            false,
+        )
+        .unwrap();
+        assert!(typst_str.contains("*Device violations: 1*"));
+        assert!(typst_str.contains("Circuit statistics"));
+        assert!(!typst_str.contains("\"Sleep\""));
+        let typst_str = circuit_to_typst_str(
+            &circuitpy,
+            Some("all"),
+            None,
+            None,
+            None,
+            Some(options.clone()),
+            false,
+        )
+        .unwrap();
+        assert!(typst_str.contains("\"Sleep\""));
+
+        assert!(save_circuit(
+            &circuitpy,
+            Some("options_circuit.svg".into()),
+            1.0,
+            None,
+            None,
+            None,
+            None,
+            Some(options.clone()),
+            false,
+            None,
+            None,
+            None
+        )
+        .is_ok());
+        let results = save_circuits(
+            py,
+            vec![circuitpy.as_any().clone()],
+            vec!["options_circuit.png".into()],
+            1.0,
+            None,
+            None,
+            None,
+            None,
+            Some(options),
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(results[0].is_none());
+    });
+    for path_str in ["options_circuit.svg", "options_circuit.png"] {
+        let read_in_path = Path::new(path_str);
+        assert!(read_in_path.exists());
+        fs::remove_file(read_in_path).unwrap();
+    }
+}
+
+#[test]
+fn test_exceptions() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(constant_hamiltonian());
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+
+        let err =
+            circuit_to_typst_str(&circuitpy, None, None, None, None, None, false).unwrap_err();
+        assert!(err.is_instance_of::<UnsupportedOperationError>(py));
+        assert!(err.is_instance_of::<QollageError>(py));
+        assert!(err.is_instance_of::<PyValueError>(py));
+        assert_eq!(
+            err.value(py)
+                .getattr("index")
+                .unwrap()
+                .extract::<Option<usize>>()
+                .unwrap(),
+            Some(1)
+        );
+
+        let err = RenderOptionsWrapper::new(
+            "all",
+            None,
+            None,
+            None,
+            Some("diagonal".to_owned()),
+            false,
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.is_instance_of::<InvalidOptionError>(py));
+    });
+}
+
+#[test]
+fn test_float_style() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateX::new(0, (5.0 * std::f64::consts::PI / 8.0).into()));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+
+        let typst_str =
+            circuit_to_typst_str(&circuitpy, None, None, None, None, None, false).unwrap();
+        assert!(typst_str.contains("(5pi)/8"));
+        let typst_str = circuit_to_typst_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    None,
+                    false,
+                    Some("degrees".to_owned()),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+            false,
+        )
+        .unwrap();
+        assert!(typst_str.contains("112.5°"));
+        let typst_str = circuit_to_typst_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    None,
+                    false,
+                    Some("decimal".to_owned()),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+            false,
+        )
+        .unwrap();
+        assert!(typst_str.contains("1.963"));
+        let err = RenderOptionsWrapper::new(
+            "all",
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            false,
+            Some("roman".to_owned()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.is_instance_of::<InvalidOptionError>(py));
+    });
+}
+
+#[test]
+fn test_angle_unit() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::FRAC_PI_2));
+    circuit.add_operation(PMInteraction::new(0, 1, CalculatorFloat::FRAC_PI_2));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+
+        let typst_str = circuit_to_typst_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    Some("degrees".to_owned()),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+            false,
+        )
+        .unwrap();
+        assert!(typst_str.contains("\"Rx\"(90°)"));
+        assert!(typst_str.contains("\"PMInteraction\"(pi/2)"));
+        let text = circuit_to_text_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    Some("turns".to_owned()),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+            "unicode",
+        )
+        .unwrap();
+        assert!(text.contains("Rx(1/4 tr)"));
+        let err = RenderOptionsWrapper::new(
+            "all",
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            false,
+            None,
+            None,
+            Some("gradians".to_owned()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.is_instance_of::<InvalidOptionError>(py));
+    });
+}
+
+#[test]
+fn test_wire_labels() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(Squeezing::new(
+        0,
+        CalculatorFloat::ZERO,
+        CalculatorFloat::ZERO,
+    ));
+    circuit.add_operation(MeasureQubit::new(1, "ro".to_owned(), 0));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let circuitpy = circuitpy_from_circuitru(py, circuit);
+
+        let typst_str = circuit_to_typst_str(
+            &circuitpy,
+            None,
+            Some("qubit".to_owned()),
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(HashMap::from([(0, "anc".to_owned())])),
+                    None,
+                    Some(HashMap::from([("ro".to_owned(), "readout".to_owned())])),
+                    Some(HashMap::from([(1, "|1>".to_owned())])),
+                )
+                .unwrap(),
+            ),
+            false,
+        )
+        .unwrap();
+        assert!(typst_str.contains("lstick($\"anc\"$, label: \"Qubits\")"));
+        assert!(typst_str.contains("lstick($q[1]: |1>$)"));
+        assert!(typst_str.contains("lstick($b[0]$, label: \"Bosons\")"));
+        assert!(typst_str.contains("\"readout : \""));
+        let text = circuit_to_text_str(
+            &circuitpy,
+            None,
+            None,
+            None,
+            Some(
+                RenderOptionsWrapper::new(
+                    "all",
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(HashMap::from([(0, "a_1".to_owned())])),
+                    None,
+                    None,
+                )
+                .unwrap(),
+            ),
+            "ascii",
+        )
+        .unwrap();
+        assert!(text.contains("a_1: |0>"));
     });
 }
@@ -0,0 +1,87 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The shared constants are only used to fetch the embedded resources.
+#![cfg_attr(not(feature = "embedded_resources"), allow(dead_code))]
+
+include!("src/resources.rs");
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/resources.rs");
+    #[cfg(feature = "embedded_resources")]
+    {
+        let out_dir = std::path::PathBuf::from(
+            std::env::var("OUT_DIR").expect("Cargo didn't set the OUT_DIR variable."),
+        );
+        fetch_resource(
+            "ROQOLLAGE_FONT_PATH",
+            FONT_URL,
+            &out_dir.join("FiraMath.otf"),
+        );
+        fetch_resource(
+            "ROQOLLAGE_QUILL_PATH",
+            &package_url(QUILL_PACKAGE),
+            &out_dir.join("quill.tar.gz"),
+        );
+    }
+}
+
+/// Returns the url of the archive of a Typst package in the default registry.
+///
+/// # Arguments
+///
+/// * `spec` - The specification of the package, in the `@namespace/name:version` format.
+#[cfg(feature = "embedded_resources")]
+fn package_url(spec: &str) -> String {
+    let (namespace, name, version) = spec
+        .strip_prefix('@')
+        .and_then(|spec| spec.split_once('/'))
+        .and_then(|(namespace, spec)| {
+            spec.split_once(':')
+                .map(|(name, version)| (namespace, name, version))
+        })
+        .unwrap_or_else(|| panic!("Invalid package specification {spec}."));
+    format!("{DEFAULT_PACKAGE_URL}/{namespace}/{name}-{version}.tar.gz")
+}
+
+/// Copies a resource to embed in the binary into the output directory.
+///
+/// The resource is read from the path given by the environment variable if it is set,
+/// and downloaded otherwise. Without network access, the environment variable has to be set.
+///
+/// # Arguments
+///
+/// * `env_var` - The environment variable pointing to a local copy of the resource.
+/// * `url` - The url to download the resource from.
+/// * `destination` - The path where to save the resource.
+#[cfg(feature = "embedded_resources")]
+fn fetch_resource(env_var: &str, url: &str, destination: &std::path::Path) {
+    println!("cargo:rerun-if-env-changed={env_var}");
+    let data = match std::env::var(env_var) {
+        Ok(path) => {
+            println!("cargo:rerun-if-changed={path}");
+            std::fs::read(&path).unwrap_or_else(|err| panic!("Couldn't read {path}: {err}."))
+        }
+        Err(_) => ureq::get(url)
+            .call()
+            .and_then(|response| response.into_body().read_to_vec())
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Couldn't download {url}: {err}. To build the embedded_resources feature \
+                     without network access, download it beforehand and set {env_var} to its path."
+                )
+            }),
+    };
+    std::fs::write(destination, data)
+        .unwrap_or_else(|err| panic!("Couldn't write {}: {err}.", destination.display()));
+}
@@ -13,24 +13,44 @@
 use std::{
     collections::HashMap,
     io::{Cursor, Write},
-    path::PathBuf,
+    ops::Range,
+    path::{Path, PathBuf},
     str::FromStr,
-    sync::RwLock,
+    sync::{Arc, Mutex, RwLock},
 };
 
+#[cfg(feature = "embedded_resources")]
+use std::{io::Read, sync::OnceLock};
+
 use image::DynamicImage;
-use roqoqo::{Circuit, RoqoqoBackendError, RoqoqoError};
+use rayon::prelude::*;
+use roqoqo::{
+    devices::Device,
+    operations::{InvolveQubits, InvolvedQubits, Operate, Operation},
+    Circuit,
+};
+use typst::syntax::package::PackageSpec;
 use typst::{
-    diag::{FileError, FileResult, PackageError},
+    diag::{FileError, FileResult, PackageError, SourceDiagnostic},
     foundations::{Bytes, Datetime},
     layout::PagedDocument,
-    syntax::{FileId, Source},
+    syntax::{FileId, Source, VirtualPath},
     text::{Font, FontBook},
     utils::LazyHash,
     Library,
 };
 
-use crate::{add_gate, effective_len, flatten_multiple_vec};
+use crate::{
+    circuit_into_highlighted_cells, circuit_into_statistics, device_violations, effective_len,
+    statistics_table, violation_highlights, violations_legend, Cell, LayoutMode, NumberFormat,
+    RoqollageError, TypstDiagnostic, Wire, WireLabels, DEFAULT_PACKAGE_URL, FONT_URL,
+    QUILL_PACKAGE,
+};
+
+/// Page and font settings of the Typst documents.
+pub(crate) const TYPST_PREAMBLE: &str = r#"#set page(width: auto, height: auto, margin: 5pt)
+#show math.equation: set text(font: "Fira Math")
+"#;
 
 /// Typst Backend
 ///
@@ -38,6 +58,11 @@ use crate::{add_gate, effective_len, flatten_multiple_vec};
 ///
 /// This backend will be used to compile a typst string to an image.
 /// It has to implement the typst::World trait.
+///
+/// The backend can be kept alive to render several Typst strings or circuits: the fonts,
+/// the standard library and the loaded package files are shared between the renders and
+/// the compilation results cached by comemo are reused. The cached results are not evicted by
+/// the backend, the owner of a long-lived backend drops the old ones with `comemo::evict`.
 #[derive(Debug)]
 pub struct TypstBackend {
     /// Typst standard library used by the backend.
@@ -46,22 +71,62 @@ pub struct TypstBackend {
     book: LazyHash<FontBook>,
     /// Typst source file to be compiled.
     source: Source,
-    /// Typst dependency files used during compilation.
-    files: RwLock<HashMap<FileId, Bytes>>,
+    /// Typst dependency files used during compilation, shared with the forked backends.
+    files: Arc<RwLock<HashMap<FileId, Bytes>>>,
     /// Collection of fonts.
     fonts: Vec<Font>,
     /// Current time.
     time: time::OffsetDateTime,
-    /// Path to the cache directory containing the font files and dependencies.
-    dependencies: PathBuf,
+    /// Path to the directory containing the Typst packages.
+    packages: PathBuf,
+    /// Url to download the missing Typst packages from, if they are not read from a local directory.
+    package_url: Option<String>,
+    /// Lock preventing several forked backends from downloading the same package at once.
+    package_lock: Arc<Mutex<()>>,
+}
+
+/// Byte range of the Typst code of each cell added by an operation, with the index of the
+/// operation in the circuit.
+type OperationRanges = Vec<(Range<usize>, usize)>;
+
+/// Configuration of the TypstBackend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendConfig {
+    /// Root of the cache directory where the font and the Typst packages are downloaded.
+    pub cache_dir: PathBuf,
+    /// Additional font files, or directories containing font files, available during compilation.
+    pub font_paths: Vec<PathBuf>,
+    /// Where the Typst packages are loaded from.
+    pub package_source: PackageSource,
+}
+
+/// Where the Typst packages are loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSource {
+    /// Download the packages from a registry (or a mirror of it) and cache them.
+    Url(String),
+    /// Read the packages from a local directory organised as `{namespace}/{name}/{version}`.
+    Directory(PathBuf),
 }
 
+/// FiraMath font embedded in the binary.
+#[cfg(feature = "embedded_resources")]
+const EMBEDDED_FONT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/FiraMath.otf"));
+
+/// Compressed archive of the quill package embedded in the binary.
+#[cfg(feature = "embedded_resources")]
+const EMBEDDED_QUILL: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/quill.tar.gz"));
+
+/// The quill package embedded in the binary.
+#[cfg(feature = "embedded_resources")]
+const EMBEDDED_QUILL_SPEC: &str = QUILL_PACKAGE;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// What to display at the left of the circuit.
 pub enum InitializationMode {
     /// States |0>.
     State,
-    /// Qubits q[n].
+    /// Qubits q[n] and bosonic modes b[n].
     Qubit,
 }
 
@@ -76,79 +141,503 @@ pub enum RenderPragmas {
     Partial(Vec<String>),
 }
 
+/// Options of the representation of a circuit.
+///
+/// Use `RenderOptions::default()` and set only the fields that differ from the defaults.
+#[derive(Clone)]
+pub struct RenderOptions<'a> {
+    /// Whether to render Pragma Operations or not.
+    pub render_pragmas: RenderPragmas,
+    /// What to display at the left of the circuit, nothing if None.
+    pub initialization_mode: Option<InitializationMode>,
+    /// The maximum length of a circuit line. If the circuit line is longer than this value, it
+    /// will be split into multiple chunks.
+    pub max_length: Option<usize>,
+    /// How the numbers are displayed.
+    pub number_format: NumberFormat,
+    /// How to place the operations in the columns.
+    pub layout_mode: LayoutMode,
+    /// Whether to put each chunk of the circuit on a new page. Only the PDF documents contain
+    /// all the pages, the images show the first one.
+    pub page_per_chunk: bool,
+    /// The device the circuit is checked against, no check if None.
+    pub device: Option<&'a (dyn Device + Sync)>,
+    /// Whether to add the table of the circuit statistics below the circuit.
+    pub statistics: bool,
+    /// The custom labels of the wires.
+    pub wire_labels: WireLabels,
+}
+
+// The devices don't implement Debug, only the other options are shown.
+impl std::fmt::Debug for RenderOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderOptions")
+            .field("render_pragmas", &self.render_pragmas)
+            .field("initialization_mode", &self.initialization_mode)
+            .field("max_length", &self.max_length)
+            .field("number_format", &self.number_format)
+            .field("layout_mode", &self.layout_mode)
+            .field("page_per_chunk", &self.page_per_chunk)
+            .field("statistics", &self.statistics)
+            .field("wire_labels", &self.wire_labels)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for RenderOptions<'_> {
+    fn default() -> Self {
+        Self {
+            render_pragmas: RenderPragmas::All,
+            initialization_mode: None,
+            max_length: None,
+            number_format: NumberFormat::default(),
+            layout_mode: LayoutMode::default(),
+            page_per_chunk: false,
+            device: None,
+            statistics: false,
+            wire_labels: WireLabels::default(),
+        }
+    }
+}
+
+impl Default for PackageSource {
+    fn default() -> Self {
+        PackageSource::Url(DEFAULT_PACKAGE_URL.to_owned())
+    }
+}
+
+impl FromStr for PackageSource {
+    type Err = RoqollageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("http://") || s.starts_with("https://") {
+            Ok(PackageSource::Url(s.trim_end_matches('/').to_owned()))
+        } else {
+            let path = PathBuf::from(s);
+            if path.is_dir() {
+                Ok(PackageSource::Directory(path))
+            } else {
+                Err(RoqollageError::InvalidOption {
+                    msg: format!(
+                        "Invalid package source: {s}, use an url or an existing directory."
+                    ),
+                })
+            }
+        }
+    }
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            cache_dir: default_cache_dir(),
+            font_paths: Vec::new(),
+            package_source: PackageSource::default(),
+        }
+    }
+}
+
+/// Returns the default cache directory of qollage.
+///
+/// This is the `qollage` directory in the cache directory of the user
+/// (`$XDG_CACHE_HOME` or `~/.cache` on Linux), or `.qollage` if it can not be determined.
+fn default_cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .map(|cache_dir| cache_dir.join("qollage"))
+        .unwrap_or_else(|| PathBuf::from(".qollage"))
+}
+
+/// Loads the fonts from the given font files and directories.
+///
+/// # Arguments
+///
+/// * `font_paths` - The font files and directories containing font files.
+///
+/// # Returns
+///
+/// * `Ok(Vec<Font>)` - All the fonts found.
+/// * `Err(RoqollageError)` - A font file couldn't be read.
+fn load_fonts(font_paths: &[PathBuf]) -> Result<Vec<Font>, RoqollageError> {
+    let mut fonts = Vec::new();
+    for font_path in font_paths {
+        let files = if font_path.is_dir() {
+            std::fs::read_dir(font_path)
+                .map_err(|err| RoqollageError::InvalidOption {
+                    msg: format!("Couldn't read the font directory {font_path:?}: {err}."),
+                })?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension()
+                        .and_then(|extension| extension.to_str())
+                        .map(|extension| {
+                            matches!(
+                                extension.to_lowercase().as_str(),
+                                "ttf" | "otf" | "ttc" | "otc"
+                            )
+                        })
+                        .unwrap_or(false)
+                })
+                .collect()
+        } else {
+            vec![font_path.to_owned()]
+        };
+        for file in files {
+            let bytes = std::fs::read(&file).map_err(|err| RoqollageError::InvalidOption {
+                msg: format!("Couldn't read the font file {file:?}: {err}."),
+            })?;
+            fonts.extend(Font::iter(Bytes::new(bytes)));
+        }
+    }
+    Ok(fonts)
+}
+
 impl TypstBackend {
+    /// Creates a new TypstBackend with the default configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `typst_str` - The typst source file.
+    pub fn new(typst_str: String) -> Result<Self, RoqollageError> {
+        Self::new_with_config(typst_str, &BackendConfig::default())
+    }
+
     /// Creates a new TypstBackend.
     ///
     /// # Arguments
     ///
     /// * `typst_str` - The typst source file.
-    pub fn new(typst_str: String) -> Result<Self, RoqoqoBackendError> {
-        let path = PathBuf::from(".qollage/fonts/FiraMath.otf");
-        let bytes = match std::fs::read(path.clone()) {
+    /// * `config` - The configuration of the backend.
+    pub fn new_with_config(
+        typst_str: String,
+        config: &BackendConfig,
+    ) -> Result<Self, RoqollageError> {
+        #[cfg(feature = "embedded_resources")]
+        let bytes = EMBEDDED_FONT.to_vec();
+        #[cfg(not(feature = "embedded_resources"))]
+        let bytes = match std::fs::read(config.cache_dir.join("fonts").join("FiraMath.otf")) {
             Ok(bytes) => bytes,
-            Err(_) => {
-                Self::download_font(path).map_err(|err| RoqoqoBackendError::NetworkError {
-                    msg: format!("Couldn't download the font: {err}"),
-                })?
-            }
+            Err(_) => Self::download_font(&config.cache_dir)?,
         };
         let buffer = Bytes::new(bytes);
-        let fonts = Font::new(buffer.clone(), 0).map_or_else(std::vec::Vec::new, |font| vec![font]);
+        let mut fonts =
+            Font::new(buffer.clone(), 0).map_or_else(std::vec::Vec::new, |font| vec![font]);
+        fonts.extend(load_fonts(&config.font_paths)?);
         let library = Library::builder().build();
+        let (packages, package_url) = match &config.package_source {
+            PackageSource::Url(url) => (config.cache_dir.join("packages"), Some(url.to_owned())),
+            PackageSource::Directory(directory) => (directory.to_owned(), None),
+        };
         Ok(Self {
             library: LazyHash::new(library),
             book: LazyHash::new(FontBook::from_fonts(&fonts)),
-            source: Source::detached(typst_str.clone()),
-            files: RwLock::new(HashMap::new()),
+            source: Source::new(FileId::new(None, VirtualPath::new("main.typ")), typst_str),
+            files: Arc::new(RwLock::new(HashMap::new())),
             fonts,
             time: time::OffsetDateTime::now_utc(),
-            dependencies: PathBuf::from_str(".qollage/cache").map_err(|_| {
-                RoqoqoBackendError::RoqoqoError(RoqoqoError::GenericError {
-                    msg: "Couldn't access `.qollage/cache` directory".to_owned(),
-                })
-            })?,
+            packages,
+            package_url,
+            package_lock: Arc::new(Mutex::new(())),
         })
     }
 
-    /// Downloads the FiraMath font.
+    /// Creates a new TypstBackend with another source file, sharing the fonts, the standard
+    /// library and the loaded package files of this backend.
+    ///
+    /// The forked backends can be used concurrently on different threads.
     ///
     /// # Arguments
     ///
-    /// * `path` `The path where to save the downloaded font file
-    fn download_font(path: PathBuf) -> Result<Vec<u8>, RoqoqoBackendError> {
-        std::fs::create_dir_all(
-            path.parent()
-                .unwrap_or(PathBuf::from(".qollage/fonts/").as_path()),
-        )
-        .map_err(|err| RoqoqoBackendError::GenericError {
+    /// * `typst_str` - The typst source file of the new backend.
+    pub fn fork(&self, typst_str: String) -> Self {
+        Self {
+            library: self.library.clone(),
+            book: self.book.clone(),
+            source: Source::new(self.source.id(), typst_str),
+            files: Arc::clone(&self.files),
+            fonts: self.fonts.clone(),
+            time: self.time,
+            packages: self.packages.clone(),
+            package_url: self.package_url.clone(),
+            package_lock: Arc::clone(&self.package_lock),
+        }
+    }
+
+    /// Replaces the main source file of the backend.
+    ///
+    /// The file id of the main source is kept, so that the compilation results cached
+    /// during the previous renders can be reused.
+    ///
+    /// # Arguments
+    ///
+    /// * `typst_str` - The new typst source file.
+    pub fn set_source(&mut self, typst_str: String) {
+        self.source.replace(&typst_str);
+    }
+
+    /// Compiles the main source file of the backend into a document.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PagedDocument)` - The document compiled from the main source file.
+    /// * `Err(RoqollageError)` - Error during the Typst compilation, a NetworkError if a
+    ///   package couldn't be downloaded.
+    pub fn compile(&self) -> Result<PagedDocument, RoqollageError> {
+        typst::compile(self).output.map_err(|err| {
+            let diagnostics = self.diagnostics(&err);
+            let download_failed = PackageError::NetworkFailed(None).to_string();
+            match diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.message.starts_with(&download_failed))
+            {
+                Some(diagnostic) => RoqollageError::NetworkError {
+                    msg: diagnostic.message.clone(),
+                },
+                None => RoqollageError::TypstCompileError { diagnostics },
+            }
+        })
+    }
+
+    /// Converts the diagnostics returned by the Typst compiler, locating them in the main source.
+    ///
+    /// # Arguments
+    ///
+    /// * `diagnostics` - The diagnostics to convert.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<TypstDiagnostic>` - The diagnostics with their range in the main source, if any.
+    fn diagnostics(&self, diagnostics: &[SourceDiagnostic]) -> Vec<TypstDiagnostic> {
+        diagnostics
+            .iter()
+            .map(|diag| {
+                let range = (diag.span.id() == Some(self.source.id()))
+                    .then(|| self.source.range(diag.span))
+                    .flatten();
+                TypstDiagnostic {
+                    index: None,
+                    operation: None,
+                    message: diag.message.to_string(),
+                    hints: diag.hints.iter().map(|hint| hint.to_string()).collect(),
+                    source: range
+                        .clone()
+                        .and_then(|range| self.source.text().get(range))
+                        .map(str::to_owned),
+                    range,
+                }
+            })
+            .collect()
+    }
+
+    /// Compiles the given typst string into an image.
+    ///
+    /// # Arguments
+    ///
+    /// * `typst_str` - The string to give to the typst compiler.
+    /// * `pixels_per_point` - The pixel per point ratio.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DynamicImage)` - The image generated from the typst string.
+    /// * `Err(RoqollageError)` - Error during the Typst compilation.
+    pub fn render_typst_str(
+        &mut self,
+        typst_str: String,
+        pixels_per_point: Option<f32>,
+    ) -> Result<DynamicImage, RoqollageError> {
+        self.set_source(typst_str);
+        document_to_image(&self.compile()?, pixels_per_point)
+    }
+
+    /// Compiles the given typst string into a SVG image.
+    ///
+    /// # Arguments
+    ///
+    /// * `typst_str` - The string to give to the typst compiler.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The SVG image generated from the typst string.
+    /// * `Err(RoqollageError)` - Error during the Typst compilation.
+    pub fn render_typst_str_to_svg(&mut self, typst_str: String) -> Result<String, RoqollageError> {
+        self.set_source(typst_str);
+        let doc = self.compile()?;
+        let page = doc
+            .pages
+            .first()
+            .ok_or_else(|| RoqollageError::RenderError {
+                msg: "Typst document has no pages.".to_owned(),
+            })?;
+        Ok(typst_svg::svg(page))
+    }
+
+    /// Compiles the given typst string into a PDF document containing all the pages.
+    ///
+    /// # Arguments
+    ///
+    /// * `typst_str` - The string to give to the typst compiler.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<u8>)` - The bytes of the PDF document generated from the typst string.
+    /// * `Err(RoqollageError)` - Error during the Typst compilation or the PDF export.
+    pub fn render_typst_str_to_pdf(
+        &mut self,
+        typst_str: String,
+    ) -> Result<Vec<u8>, RoqollageError> {
+        self.set_source(typst_str);
+        let doc = self.compile()?;
+        typst_pdf::pdf(&doc, &typst_pdf::PdfOptions::default()).map_err(|err| {
+            RoqollageError::RenderError {
+                msg: format!("Error during the PDF export: {}", format_diagnostics(&err)),
+            }
+        })
+    }
+
+    /// Converts a qoqo circuit to an image.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit to convert.
+    /// * `pixels_per_point` - The pixel per point ratio.
+    /// * `options` - The options of the circuit representation.
+    ///
+    /// # Returns
+    ///
+    /// * DynamicImage: The image reprensenting the circuit.
+    pub fn circuit_to_image(
+        &mut self,
+        circuit: &Circuit,
+        pixels_per_point: Option<f32>,
+        options: &RenderOptions,
+    ) -> Result<DynamicImage, RoqollageError> {
+        let (typst_str, ranges) = circuit_into_typst_document(circuit, options, None)?;
+        self.render_typst_str(typst_str, pixels_per_point)
+            .map_err(|err| locate_diagnostics(err, circuit, &ranges))
+    }
+
+    /// Converts a qoqo circuit to a SVG image.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit to convert.
+    /// * `options` - The options of the circuit representation.
+    ///
+    /// # Returns
+    ///
+    /// * String: The SVG image reprensenting the circuit.
+    pub fn circuit_to_svg(
+        &mut self,
+        circuit: &Circuit,
+        options: &RenderOptions,
+    ) -> Result<String, RoqollageError> {
+        let (typst_str, ranges) = circuit_into_typst_document(circuit, options, None)?;
+        self.render_typst_str_to_svg(typst_str)
+            .map_err(|err| locate_diagnostics(err, circuit, &ranges))
+    }
+
+    /// Converts a qoqo circuit to a PDF document.
+    ///
+    /// # Arguments
+    ///
+    /// * `circuit` - The circuit to convert.
+    /// * `options` - The options of the circuit representation.
+    ///
+    /// # Returns
+    ///
+    /// * Vec<u8>: The bytes of the PDF document reprensenting the circuit.
+    pub fn circuit_to_pdf(
+        &mut self,
+        circuit: &Circuit,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, RoqollageError> {
+        let (typst_str, ranges) = circuit_into_typst_document(circuit, options, None)?;
+        self.render_typst_str_to_pdf(typst_str)
+            .map_err(|err| locate_diagnostics(err, circuit, &ranges))
+    }
+
+    /// Downloads the FiraMath font into the `fonts` directory of the cache directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache_dir` - The cache directory of the backend.
+    #[cfg_attr(feature = "embedded_resources", allow(dead_code))]
+    fn download_font(cache_dir: &Path) -> Result<Vec<u8>, RoqollageError> {
+        let font_dir = cache_dir.join("fonts");
+        let path = font_dir.join("FiraMath.otf");
+        std::fs::create_dir_all(&font_dir).map_err(|err| RoqollageError::CacheError {
+            path: font_dir.clone(),
             msg: format!("Couldn't create the font directory: {err}."),
         })?;
-        let url = "https://mirrors.ctan.org/fonts/firamath/FiraMath-Regular.otf";
-
-        let response = ureq::get(url)
+        let response = ureq::get(FONT_URL)
             .call()
-            .map_err(|err| RoqoqoBackendError::NetworkError {
+            .map_err(|err| RoqollageError::NetworkError {
                 msg: format!("Couldn't download the font file: {err}."),
             })?;
         let data =
             response
                 .into_body()
                 .read_to_vec()
-                .map_err(|err| RoqoqoBackendError::NetworkError {
+                .map_err(|err| RoqollageError::NetworkError {
                     msg: format!("Couldn't read the font file: {err}."),
                 })?;
-        let mut file =
-            std::fs::File::create(&path).map_err(|err| RoqoqoBackendError::GenericError {
-                msg: format!("Couldn't create the font file: {err}."),
-            })?;
-        std::fs::File::write(&mut file, &data).map_err(|err| RoqoqoBackendError::GenericError {
+        let mut file = std::fs::File::create(&path).map_err(|err| RoqollageError::CacheError {
+            path: path.clone(),
+            msg: format!("Couldn't create the font file: {err}."),
+        })?;
+        std::fs::File::write(&mut file, &data).map_err(|err| RoqollageError::CacheError {
+            path: path.clone(),
             msg: format!("Couldn't write the font file: {err}."),
         })?;
-        std::fs::read(path).map_err(|err| RoqoqoBackendError::GenericError {
+        std::fs::read(&path).map_err(|err| RoqollageError::CacheError {
+            path: path.clone(),
             msg: format!("Couldn't read the font file: {err}"),
         })
     }
 
+    /// Downloads and unpacks a Typst package.
+    ///
+    /// The package is unpacked in a temporary directory renamed to the package directory at the
+    /// end, so that the other backends never read a partially unpacked package.
+    ///
+    /// # Arguments
+    ///
+    /// * `package` - The package to download.
+    /// * `package_path` - The directory the package is unpacked to.
+    fn download_package(&self, package: &PackageSpec, package_path: &Path) -> FileResult<()> {
+        let Some(package_url) = &self.package_url else {
+            return Err(FileError::Package(PackageError::NotFound(package.clone())));
+        };
+        let url = format!(
+            "{}/{}/{}-{}.tar.gz",
+            package_url, package.namespace, package.name, package.version,
+        );
+        let response = ureq::get(&url).call().map_err(|error| {
+            FileError::Package(PackageError::NetworkFailed(Some(
+                format!("{url}: {error}").into(),
+            )))
+        })?;
+        let data = response
+            .into_body()
+            .read_to_vec()
+            .map_err(|error| FileError::from_io(error.into_io(), package_path))?;
+        let decompressed_data = zune_inflate::DeflateDecoder::new(&data)
+            .decode_gzip()
+            .map_err(|error| {
+                FileError::Package(PackageError::MalformedArchive(Some(
+                    format!("Error during decompression:{error}.").into(),
+                )))
+            })?;
+        let unpack_path = package_path.with_file_name(format!(".{}.unpacking", package.version));
+        let mut archive = tar::Archive::new(decompressed_data.as_slice());
+        archive.unpack(&unpack_path).map_err(|error| {
+            FileError::Package(PackageError::MalformedArchive(Some(
+                format!("Error during unpacking:{error}.").into(),
+            )))
+        })?;
+        std::fs::rename(&unpack_path, package_path)
+            .map_err(|error| FileError::from_io(error, package_path))
+    }
+
     /// Returns the typst dependency file.
     ///
     /// # Arguments
@@ -164,34 +653,28 @@ impl TypstBackend {
             return Ok(bytes.clone());
         }
         if let Some(package) = id.package() {
+            #[cfg(feature = "embedded_resources")]
+            if let Some(bytes_content) =
+                embedded_package_file(package, id.vpath().as_rootless_path())
+            {
+                self.files
+                    .write()
+                    .expect("Backend couldn't access the files.")
+                    .insert(id, bytes_content.clone());
+                return Ok(bytes_content);
+            }
             let package_subdir =
                 format!("{}/{}/{}", package.namespace, package.name, package.version);
-            let package_path = self.dependencies.join(package_subdir);
+            let package_path = self.packages.join(package_subdir);
             if !package_path.exists() {
-                let url = format!(
-                    "https://packages.typst.org/{}/{}-{}.tar.gz",
-                    package.namespace, package.name, package.version,
-                );
-                let response = ureq::get(&url)
-                    .call()
-                    .map_err(|_| FileError::AccessDenied)?;
-                let data = response
-                    .into_body()
-                    .read_to_vec()
-                    .map_err(|error| FileError::from_io(error.into_io(), &package_path))?;
-                let decompressed_data = zune_inflate::DeflateDecoder::new(&data)
-                    .decode_gzip()
-                    .map_err(|error| {
-                        FileError::Package(PackageError::MalformedArchive(Some(
-                            format!("Error during decompression:{error}.").into(),
-                        )))
-                    })?;
-                let mut archive = tar::Archive::new(decompressed_data.as_slice());
-                archive.unpack(&package_path).map_err(|error| {
-                    FileError::Package(PackageError::MalformedArchive(Some(
-                        format!("Error during unpacking:{error}.").into(),
-                    )))
-                })?;
+                let _package_guard = self
+                    .package_lock
+                    .lock()
+                    .expect("Backend couldn't access the packages.");
+                // Another backend may have downloaded the package while this one was waiting.
+                if !package_path.exists() {
+                    self.download_package(package, &package_path)?;
+                }
             }
             if let Some(file_path) = id.vpath().resolve(&package_path) {
                 let contents = std::fs::read(&file_path)
@@ -208,6 +691,52 @@ impl TypstBackend {
     }
 }
 
+/// Returns a file of the Typst packages embedded in the binary.
+///
+/// The embedded archive is only unpacked once and kept in memory afterwards.
+///
+/// # Arguments
+///
+/// * `package` - The package the file belongs to.
+/// * `path` - The path of the file inside the package.
+///
+/// # Returns
+///
+/// * `Option<Bytes>` - The file content if the package and file are embedded.
+#[cfg(feature = "embedded_resources")]
+fn embedded_package_file(package: &PackageSpec, path: &Path) -> Option<Bytes> {
+    static FILES: OnceLock<HashMap<PathBuf, Bytes>> = OnceLock::new();
+    if package.to_string() != EMBEDDED_QUILL_SPEC {
+        return None;
+    }
+    FILES
+        .get_or_init(|| {
+            let mut files = HashMap::new();
+            let Ok(data) = zune_inflate::DeflateDecoder::new(EMBEDDED_QUILL).decode_gzip() else {
+                return files;
+            };
+            let mut archive = tar::Archive::new(data.as_slice());
+            if let Ok(entries) = archive.entries() {
+                for mut entry in entries.flatten() {
+                    let Ok(entry_path) = entry.path().map(|path| path.into_owned()) else {
+                        continue;
+                    };
+                    let mut contents = Vec::new();
+                    if entry.read_to_end(&mut contents).is_ok() {
+                        let entry_path = entry_path
+                            .strip_prefix("./")
+                            .map(Path::to_path_buf)
+                            .unwrap_or(entry_path);
+                        files.insert(entry_path, Bytes::new(contents));
+                    }
+                }
+            }
+            files
+        })
+        .get(path)
+        .cloned()
+}
+
 impl typst::World for TypstBackend {
     /// The standard library.
     fn library(&self) -> &LazyHash<Library> {
@@ -262,63 +791,20 @@ impl typst::World for TypstBackend {
 }
 
 impl FromStr for InitializationMode {
-    type Err = RoqoqoBackendError;
+    type Err = RoqollageError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "state" => Ok(InitializationMode::State),
             "qubit" => Ok(InitializationMode::Qubit),
-            _ => Err(RoqoqoBackendError::RoqoqoError(RoqoqoError::GenericError {
+            _ => Err(RoqollageError::InvalidOption {
                 msg: format!(r#"Invalid initialization mode: {s}, use `state` or `qubit`."#),
-            })),
+            }),
         }
     }
 }
 
-/// Replaces `replace_by_classical_len_{n}` by n_qubits + n_bosons + n.
-/// Needs to be done after going through all the circuit to know n_qubits and n_bosons.
-///
-/// # Arguments
-///
-/// * `bosonic_gate` - The bosonic gate in typst representation.
-/// * `n_qubits` - The number of qubits.
-/// * `n_bosons` - The number of bosons.
-fn replace_classical_index(
-    classical_gate: &String,
-    n_qubits: usize,
-    n_bosons: usize,
-    n_classical: usize,
-) -> String {
-    let mut output = classical_gate.to_owned();
-    for index in 0..n_classical + 1 {
-        let pattern = format!("replace_by_classical_len_{index}");
-        if output.contains(&pattern) {
-            output = output.replace(&pattern, &(index + n_qubits + n_bosons).to_string());
-        }
-    }
-    output
-}
-
-/// Replaces `replace_by_n_qubits_plus_{n}` by n_qubits + n.
-/// Needs to be done after going through all the circuit to know n_qubits.
-///
-/// # Arguments
-///
-/// * `bosonic_gate` - The bosonic gate in typst representation.
-/// * `n_qubits` - The number of qubits.
-/// * `n_bosons` - The number of bosons.
-fn replace_boson_index(bosonic_gate: &String, n_qubits: usize, n_bosons: usize) -> String {
-    let mut output = bosonic_gate.to_owned();
-    for boson in 0..n_bosons + 1 {
-        let pattern = format!("replace_by_n_qubits_plus_{boson}");
-        if output.contains(&pattern) {
-            output = output.replace(&pattern, &(boson + n_qubits).to_string());
-        }
-    }
-    output
-}
-
 impl FromStr for RenderPragmas {
-    type Err = RoqoqoBackendError;
+    type Err = RoqollageError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
@@ -334,56 +820,125 @@ impl FromStr for RenderPragmas {
     }
 }
 
+/// Formats the diagnostics returned by the Typst compiler.
+///
+/// ## Arguments
+///
+/// * `diagnostics` - The diagnostics to format.
+///
+/// ## Returns
+///
+/// * `String` - One line per diagnostic.
+fn format_diagnostics(diagnostics: &[SourceDiagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diag| {
+            format!(
+                "File: {:?}, Range: {:?}, Severity: {:?}, Message: {}, Hints: [{}]",
+                diag.span.id(),
+                diag.span.range(),
+                diag.severity,
+                diag.message,
+                diag.hints
+                    .iter()
+                    .map(|h| h.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Uses the Typst compiler to generate an image from the given typst string.
 ///
 /// ## Arguments
 ///
 /// * `typst_string` - The string to give to the typst compiler.
 /// * `pixels_per_point` - The pixel per point ratio.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
 ///
 /// ## Returns
 ///
 /// * `Ok(DynamicImage)` - The image generated from the typst string.
-/// * `Err(RoqoqoBackendError)` - Error during the Typst compilation.
+/// * `Err(RoqollageError)` - Error during the Typst compilation.
 pub fn render_typst_str(
     typst_str: String,
     pixels_per_point: Option<f32>,
-) -> Result<DynamicImage, RoqoqoBackendError> {
-    let typst_backend = TypstBackend::new(typst_str)?;
-    let doc: PagedDocument =
-        typst::compile(&typst_backend)
-            .output
-            .map_err(|err| RoqoqoBackendError::GenericError {
-                msg: format!(
-                    "Error during the Typst compilation: {}",
-                    err.iter()
-                        .map(|diag| {
-                            format!(
-                                "File: {:?}, Range: {:?}, Severity: {:?}, Message: {}, Hints: [{}]",
-                                diag.span.id(),
-                                diag.span.range(),
-                                diag.severity,
-                                diag.message,
-                                diag.hints
-                                    .iter()
-                                    .map(|h| h.as_str())
-                                    .collect::<Vec<_>>()
-                                    .join(", ")
-                            )
-                        })
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                ),
-            })?;
+    backend_config: Option<&BackendConfig>,
+) -> Result<DynamicImage, RoqollageError> {
+    backend_from_config(backend_config)?.render_typst_str(typst_str, pixels_per_point)
+}
+
+/// Uses the Typst compiler to generate a SVG image from the given typst string.
+///
+/// ## Arguments
+///
+/// * `typst_string` - The string to give to the typst compiler.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * `Ok(String)` - The SVG image generated from the typst string.
+/// * `Err(RoqollageError)` - Error during the Typst compilation.
+pub fn render_typst_str_to_svg(
+    typst_str: String,
+    backend_config: Option<&BackendConfig>,
+) -> Result<String, RoqollageError> {
+    backend_from_config(backend_config)?.render_typst_str_to_svg(typst_str)
+}
+
+/// Uses the Typst compiler to generate a PDF document from the given typst string.
+///
+/// Contrary to the image outputs, all the pages of the Typst document are kept.
+///
+/// ## Arguments
+///
+/// * `typst_string` - The string to give to the typst compiler.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * `Ok(Vec<u8>)` - The bytes of the PDF document generated from the typst string.
+/// * `Err(RoqollageError)` - Error during the Typst compilation or the PDF export.
+pub fn render_typst_str_to_pdf(
+    typst_str: String,
+    backend_config: Option<&BackendConfig>,
+) -> Result<Vec<u8>, RoqollageError> {
+    backend_from_config(backend_config)?.render_typst_str_to_pdf(typst_str)
+}
+
+/// Creates a TypstBackend with an empty source from the given configuration.
+///
+/// ## Arguments
+///
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+pub(crate) fn backend_from_config(
+    backend_config: Option<&BackendConfig>,
+) -> Result<TypstBackend, RoqollageError> {
+    match backend_config {
+        Some(config) => TypstBackend::new_with_config(String::new(), config),
+        None => TypstBackend::new(String::new()),
+    }
+}
+
+/// Returns the first page of a Typst document as an image.
+///
+/// ## Arguments
+///
+/// * `doc` - The compiled Typst document.
+/// * `pixels_per_point` - The pixel per point ratio.
+fn document_to_image(
+    doc: &PagedDocument,
+    pixels_per_point: Option<f32>,
+) -> Result<DynamicImage, RoqollageError> {
     let mut writer = Cursor::new(Vec::new());
     let pixmap = typst_render::render(
-        &doc.pages
+        doc.pages
             .first()
-            .ok_or("error")
-            .map_err(|_| RoqoqoBackendError::GenericError {
+            .ok_or_else(|| RoqollageError::RenderError {
                 msg: "Typst document has no pages.".to_owned(),
-            })?
-            .clone(),
+            })?,
         pixels_per_point.unwrap_or(3.0),
     );
     image::write_buffer_with_format(
@@ -394,31 +949,131 @@ pub fn render_typst_str(
         image::ColorType::Rgba8,
         image::ImageFormat::Png,
     )
-    .map_err(|err| RoqoqoBackendError::GenericError {
+    .map_err(|err| RoqollageError::RenderError {
         msg: err.to_string(),
     })?;
     let image = image::load_from_memory(&writer.into_inner()).map_err(|err| {
-        RoqoqoBackendError::GenericError {
+        RoqollageError::RenderError {
             msg: err.to_string(),
         }
     })?;
     Ok(image)
 }
 
-fn effective_split(vec: &mut Vec<String>, split_index: usize) -> (Vec<String>, Vec<String>, usize) {
+/// Writes the Typst representation of the cells of a wire, separated by commas.
+///
+/// # Arguments
+///
+/// * `typst_str` - The Typst code the cells are appended to.
+/// * `cells` - The cells of the wire.
+/// * `wire` - The wire the cells are placed on.
+/// * `n_qubits` - The number of qubit wires.
+/// * `n_bosons` - The number of bosonic mode wires.
+/// * `ranges` - The list the byte range of the code of each traced cell in `typst_str` is added
+///   to, with the index of the operation.
+fn cells_to_typst(
+    typst_str: &mut String,
+    cells: &[Cell],
+    wire: Wire,
+    n_qubits: usize,
+    n_bosons: usize,
+    ranges: &mut OperationRanges,
+) {
+    for (position, cell) in cells.iter().enumerate() {
+        if position > 0 {
+            typst_str.push_str(", ");
+        }
+        let start = typst_str.len();
+        typst_str.push_str(&cell.to_typst(wire, n_qubits, n_bosons));
+        if let Cell::Traced { index, .. } = cell {
+            ranges.push((start..typst_str.len(), *index));
+        }
+    }
+}
+
+/// Starts a new row of the current `quantum-circuit` call, ending the previous row if any.
+///
+/// # Arguments
+///
+/// * `typst_str` - The Typst code the row is appended to.
+/// * `first_row` - Whether no row has been written in the current call yet, unset by this call.
+fn start_row(typst_str: &mut String, first_row: &mut bool) {
+    if !*first_row {
+        typst_str.push_str("[\\ ],\n");
+    }
+    *first_row = false;
+}
+
+/// Returns a short description of an operation, e.g. `RotateX on qubit 3`.
+///
+/// # Arguments
+///
+/// * `operation` - The operation to describe.
+fn operation_description(operation: &Operation) -> String {
+    match operation.involved_qubits() {
+        InvolvedQubits::Set(qubits) if !qubits.is_empty() => {
+            let mut qubits: Vec<usize> = qubits.into_iter().collect();
+            qubits.sort();
+            format!(
+                "{} on qubit{} {}",
+                operation.hqslang(),
+                if qubits.len() > 1 { "s" } else { "" },
+                qubits
+                    .iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        }
+        InvolvedQubits::All => format!("{} on all qubits", operation.hqslang()),
+        _ => operation.hqslang().to_owned(),
+    }
+}
+
+/// Adds the operation whose Typst code caused each error to the diagnostics of a compilation error.
+///
+/// # Arguments
+///
+/// * `err` - The error returned when rendering the circuit.
+/// * `circuit` - The rendered circuit.
+/// * `ranges` - The byte range of the code of each cell added by an operation in the Typst source,
+///   with the index of the operation.
+///
+/// # Returns
+///
+/// * `RoqollageError` - The error, with the operations added to its diagnostics.
+fn locate_diagnostics(
+    err: RoqollageError,
+    circuit: &Circuit,
+    ranges: &[(Range<usize>, usize)],
+) -> RoqollageError {
+    match err {
+        RoqollageError::TypstCompileError { mut diagnostics } => {
+            for diagnostic in diagnostics.iter_mut() {
+                let Some(error_range) = &diagnostic.range else {
+                    continue;
+                };
+                let origin = ranges.iter().find(|(range, _)| {
+                    range.start <= error_range.start && error_range.start < range.end
+                });
+                if let Some((_, index)) = origin {
+                    diagnostic.index = Some(*index);
+                    diagnostic.operation = circuit.get(*index).map(operation_description);
+                }
+            }
+            RoqollageError::TypstCompileError { diagnostics }
+        }
+        err => err,
+    }
+}
+
+fn effective_split(vec: &mut Vec<Cell>, split_index: usize) -> (Vec<Cell>, Vec<Cell>, usize) {
     let mut first = vec![];
     let mut group_len = 0;
     while !vec.is_empty() && effective_len(first.as_slice()) < split_index.max(group_len) {
         let op = vec.remove(0);
-        if op.contains("gategroup") {
-            group_len = op
-                .split(',')
-                .nth(1)
-                .unwrap_or("0")
-                .trim()
-                .parse::<usize>()
-                .unwrap_or_default()
-                + first.len();
+        if let Cell::Group { length, .. } = op {
+            group_len = length + first.len();
         }
         first.push(op);
     }
@@ -426,13 +1081,13 @@ fn effective_split(vec: &mut Vec<String>, split_index: usize) -> (Vec<String>, V
 }
 
 fn split_gates(
-    gates_vec: &mut [Vec<String>],
+    gates_vec: &mut [Vec<Cell>],
     max_len: usize,
     new_len_map: &HashMap<i64, usize>,
-) -> Option<Vec<Vec<Vec<String>>>> {
+) -> Option<Vec<Vec<Vec<Cell>>>> {
     if !gates_vec.is_empty() && gates_vec[0].len() > max_len {
-        let mut chunks: Vec<Vec<Vec<String>>> = vec![];
-        let mut inner_chunks: Vec<Vec<String>> = vec![];
+        let mut chunks: Vec<Vec<Vec<Cell>>> = vec![];
+        let mut inner_chunks: Vec<Vec<Cell>> = vec![];
         for _ in 0..gates_vec.len() {
             inner_chunks.push(vec![]);
         }
@@ -478,7 +1133,7 @@ fn split_gates(
 }
 
 fn split_in_chunk_preprocess(
-    gates_vec: &[Vec<String>],
+    gates_vec: &[Vec<Cell>],
     max_len: usize,
     new_len_map: &mut HashMap<i64, usize>,
 ) -> bool {
@@ -539,76 +1194,150 @@ fn split_in_chunk_preprocess(
 
 /// Converts a qoqo circuit to a typst string.
 ///
+/// If a device is given in the options, the gates it doesn't support and the two-qubit gates
+/// acting on qubits it doesn't connect are highlighted, and a legend listing them is added below
+/// the circuit. If `statistics` is set, a table summarizing the circuit is added below the circuit.
+///
 ///  ## Arguments
 ///
 /// * `circuit` - The circuit to convert.
-/// * `render_pragmas` - Whether to render Pragma Operations or not.
-/// * `initialization_mode` - The initialization mode of the circuit representation.
-/// * `max_length` - The maximum length of a circuit line. If the circuit line is longer than this
-///   value, it will be split into multiple lines.
-/// * `rounding_accuracy` - The number of digits to round to when displaying floats.
+/// * `options` - The options of the circuit representation.
 ///
 /// ## Returns
 ///
 /// * `String` - The string representation of the circuit in Typst.
 pub fn circuit_into_typst_str(
     circuit: &Circuit,
-    render_pragmas: RenderPragmas,
-    initialization_mode: Option<InitializationMode>,
-    max_length: Option<usize>,
-    rounding_accuracy: Option<usize>,
-) -> Result<String, RoqoqoBackendError> {
-    let mut typst_str = r#"#set page(width: auto, height: auto, margin: 5pt)
-#show math.equation: set text(font: "Fira Math")
-#{ 
-    import "@preview/quill:0.7.1": *
-    quantum-circuit(
-"#
-    .to_owned();
-    let mut circuit_gates: Vec<Vec<String>> = Vec::new();
-    let mut bosonic_gates: Vec<Vec<String>> = Vec::new();
-    let mut classical_gates: Vec<Vec<String>> = Vec::new();
-    let mut circuit_lock: Vec<(usize, usize)> = Vec::new();
-    let mut bosonic_lock: Vec<(usize, usize)> = Vec::new();
-    let mut classical_lock: Vec<(usize, usize)> = Vec::new();
-    for operation in circuit.iter() {
-        add_gate(
-            &mut circuit_gates,
-            &mut bosonic_gates,
-            &mut classical_gates,
-            &mut circuit_lock,
-            &mut bosonic_lock,
-            &mut classical_lock,
-            operation,
-            &render_pragmas,
-            rounding_accuracy.unwrap_or(3),
+    options: &RenderOptions,
+) -> Result<String, RoqollageError> {
+    let (typst_str, _) = circuit_into_typst_document(circuit, options, None)?;
+    Ok(typst_str)
+}
+
+/// Converts a qoqo circuit to a typst string, drawing the operations not supported as placeholders.
+///
+/// Instead of failing, each operation not supported by the backend is drawn as a box labeled with
+/// its name over the qubits it acts on, or as a slice across the circuit if it acts on all the
+/// qubits, and a warning is returned for it.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `options` - The options of the circuit representation.
+///
+/// ## Returns
+///
+/// * `(String, Vec<String>)` - The string representation of the circuit in Typst and the warnings
+///   about the operations drawn as placeholders.
+pub fn circuit_into_lenient_typst_str(
+    circuit: &Circuit,
+    options: &RenderOptions,
+) -> Result<(String, Vec<String>), RoqollageError> {
+    let mut warnings = Vec::new();
+    let (typst_str, _) = circuit_into_typst_document(circuit, options, Some(&mut warnings))?;
+    Ok((typst_str, warnings))
+}
+
+/// Converts a qoqo circuit to a typst string, optionally putting each chunk on its own page.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `options` - The options of the circuit representation.
+/// * `warnings` - If set, the operations not supported are drawn as placeholders and a warning is
+///   added to this list for each of them instead of returning an error.
+///
+/// ## Returns
+///
+/// * `String` - The string representation of the circuit in Typst.
+/// * `OperationRanges` - The byte range of the code of each cell added by an operation
+///   in the string, with the index of the operation.
+fn circuit_into_typst_document(
+    circuit: &Circuit,
+    options: &RenderOptions,
+    warnings: Option<&mut Vec<String>>,
+) -> Result<(String, OperationRanges), RoqollageError> {
+    let violations = options
+        .device
+        .map(|device| device_violations(circuit, device))
+        .unwrap_or_default();
+    let statistics_str = if options.statistics {
+        statistics_table(&circuit_into_statistics(
+            circuit,
+            options.render_pragmas.clone(),
+            Some(options.layout_mode),
+        )?)
+    } else {
+        String::new()
+    };
+    let header = format!("{TYPST_PREAMBLE}#{{ \n    import \"{QUILL_PACKAGE}\": *\n");
+    let (quill_code, ranges) = circuit_into_quill_code(
+        circuit,
+        options,
+        &violation_highlights(&violations),
+        warnings,
+    )?;
+    let ranges = ranges
+        .into_iter()
+        .map(|(range, index)| (range.start + header.len()..range.end + header.len(), index))
+        .collect();
+    Ok((
+        format!(
+            "{header}{quill_code}}}\n{}{statistics_str}",
+            if options.device.is_some() {
+                violations_legend(&violations)
+            } else {
+                String::new()
+            }
+        ),
+        ranges,
+    ))
+}
+
+/// Converts a qoqo circuit to the Typst code drawing it with quill.
+///
+/// The code has to be placed in a code block importing the quill package. The device and
+/// statistics options are not used.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `options` - The options of the circuit representation.
+/// * `highlights` - The fill color of the highlighted operations, by index in the circuit.
+/// * `warnings` - If set, the operations not supported are drawn as placeholders and a warning is
+///   added to this list for each of them instead of returning an error.
+///
+/// ## Returns
+///
+/// * `String` - The Typst code of the `quantum-circuit` calls drawing the circuit.
+/// * `OperationRanges` - The byte range of the code of each cell added by an operation
+///   in the Typst code, with the index of the operation.
+pub(crate) fn circuit_into_quill_code(
+    circuit: &Circuit,
+    options: &RenderOptions,
+    highlights: &HashMap<usize, String>,
+    warnings: Option<&mut Vec<String>>,
+) -> Result<(String, OperationRanges), RoqollageError> {
+    let mut typst_str = "    quantum-circuit(\n".to_owned();
+    let mut first_row = true;
+    let mut ranges: OperationRanges = Vec::new();
+    let (mut circuit_gates, mut bosonic_gates, mut classical_gates) =
+        circuit_into_highlighted_cells(
+            circuit,
+            &options.render_pragmas,
+            options.number_format,
+            options.layout_mode,
+            highlights,
+            warnings,
         )?;
-    }
+    let wire_labels = &options.wire_labels;
+    wire_labels.relabel_registers(&mut classical_gates);
     let n_qubits = circuit_gates.len();
     let n_bosons = bosonic_gates.len();
-    let n_classical = classical_gates.len();
-    flatten_multiple_vec(
-        &mut circuit_gates,
-        &mut bosonic_gates,
-        (0..n_qubits).collect::<Vec<usize>>().as_slice(),
-        (0..n_bosons).collect::<Vec<usize>>().as_slice(),
-    );
-    flatten_multiple_vec(
-        &mut circuit_gates,
-        &mut classical_gates,
-        (0..n_qubits).collect::<Vec<usize>>().as_slice(),
-        (0..n_classical).collect::<Vec<usize>>().as_slice(),
-    );
-    flatten_multiple_vec(
-        &mut bosonic_gates,
-        &mut classical_gates,
-        (0..n_bosons).collect::<Vec<usize>>().as_slice(),
-        (0..n_classical).collect::<Vec<usize>>().as_slice(),
-    );
     let mut additional_circuit_gates = None;
     let mut additional_bosonic_gates = None;
     let mut additional_classical_gates = None;
-    if let Some(max_circuit_length) = max_length {
+    if let Some(max_circuit_length) = options.max_length {
         let mut new_len_map: HashMap<i64, usize> = HashMap::new();
         while !split_in_chunk_preprocess(&circuit_gates, max_circuit_length, &mut new_len_map) {}
         additional_circuit_gates =
@@ -620,31 +1349,29 @@ pub fn circuit_into_typst_str(
     }
     let mut is_first = true;
     for (qubit_index, gates) in circuit_gates.iter().enumerate() {
+        start_row(&mut typst_str, &mut first_row);
         typst_str.push_str(&format!(
-            "       lstick(${}${}), {} 1, {}[\\ ],\n",
-            match initialization_mode {
-                Some(InitializationMode::Qubit) => format!("q[{qubit_index}]"),
-                Some(InitializationMode::State) | None => "|0>".to_owned(),
-            },
+            "       lstick(${}${}), ",
+            wire_labels.wire_label(Wire::Qubit(qubit_index), options.initialization_mode),
             if is_first {
                 ", label: \"Qubits\""
             } else {
                 Default::default()
             },
-            gates
-                .iter()
-                .map(|gate| {
-                    if gate.contains("replace_by_n_qubits_") {
-                        replace_boson_index(gate, n_qubits, n_bosons)
-                    } else if gate.contains("replace_by_classical_len_") {
-                        replace_classical_index(gate, n_qubits, n_bosons, n_classical)
-                    } else {
-                        gate.to_owned()
-                    }
-                })
-                .chain(vec!["".to_owned()].into_iter())
-                .collect::<Vec<String>>()
-                .join(", "),
+        ));
+        cells_to_typst(
+            &mut typst_str,
+            gates,
+            Wire::Qubit(qubit_index),
+            n_qubits,
+            n_bosons,
+            &mut ranges,
+        );
+        if !gates.is_empty() {
+            typst_str.push_str(", ");
+        }
+        typst_str.push_str(&format!(
+            " 1, {}",
             if additional_circuit_gates.is_some() {
                 "rstick($···$),"
             } else {
@@ -655,18 +1382,26 @@ pub fn circuit_into_typst_str(
     }
     is_first = true;
     for (n_boson, gates) in bosonic_gates.iter().enumerate() {
+        start_row(&mut typst_str, &mut first_row);
         typst_str.push_str(&format!(
-            "       lstick(${}${}), {}, 1, {}[\\ ],\n",
-            match initialization_mode {
-                Some(InitializationMode::Qubit) => format!("q[{n_boson}]"),
-                Some(InitializationMode::State) | None => "|0>".to_owned(),
-            },
+            "       lstick(${}${}), ",
+            wire_labels.wire_label(Wire::Boson(n_boson), options.initialization_mode),
             if is_first {
                 ", label: \"Bosons\""
             } else {
                 Default::default()
             },
-            gates.join(", "),
+        ));
+        cells_to_typst(
+            &mut typst_str,
+            gates,
+            Wire::Boson(n_boson),
+            n_qubits,
+            n_bosons,
+            &mut ranges,
+        );
+        typst_str.push_str(&format!(
+            ", 1, {}",
             if additional_bosonic_gates.is_some() {
                 "rstick($···$),"
             } else {
@@ -675,10 +1410,20 @@ pub fn circuit_into_typst_str(
         ));
         is_first = false;
     }
-    for gates in classical_gates.iter() {
-        typst_str.push_str(&format!("       {}, 1, [\\ ],\n", gates.join(", ")));
+    for (index, gates) in classical_gates.iter().enumerate() {
+        start_row(&mut typst_str, &mut first_row);
+        typst_str.push_str("       ");
+        cells_to_typst(
+            &mut typst_str,
+            gates,
+            Wire::Classical(index),
+            n_qubits,
+            n_bosons,
+            &mut ranges,
+        );
+        typst_str.push_str(", 1, ");
     }
-    if max_length.is_some()
+    if options.max_length.is_some()
         && (additional_circuit_gates.is_some()
             || additional_bosonic_gates.is_some()
             || additional_classical_gates.is_some())
@@ -698,25 +1443,28 @@ pub fn circuit_into_typst_str(
                     )
             });
         for chunk_number in 0..number_of_chunks {
+            if options.page_per_chunk {
+                typst_str.push_str(")\n    pagebreak()\n    quantum-circuit(\n");
+                first_row = true;
+            }
             if let Some(ref add_circuit_gates) = additional_circuit_gates {
                 let current_chunk = &add_circuit_gates[chunk_number];
                 for (qubit_index, gates) in current_chunk.iter().enumerate() {
+                    start_row(&mut typst_str, &mut first_row);
                     typst_str.push_str(&format!(
-                        "lstick($···q[{}]$), {}, 1, {}[\\ ],\n",
-                        qubit_index,
-                        gates
-                            .iter()
-                            .map(|gate| {
-                                if gate.contains("replace_by_n_qubits_") {
-                                    replace_boson_index(gate, n_qubits, n_bosons)
-                                } else if gate.contains("replace_by_classical_len_") {
-                                    replace_classical_index(gate, n_qubits, n_bosons, n_classical)
-                                } else {
-                                    gate.to_owned()
-                                }
-                            })
-                            .collect::<Vec<String>>()
-                            .join(", "),
+                        "lstick($···{}$), ",
+                        wire_labels.wire_name(Wire::Qubit(qubit_index)),
+                    ));
+                    cells_to_typst(
+                        &mut typst_str,
+                        gates,
+                        Wire::Qubit(qubit_index),
+                        n_qubits,
+                        n_bosons,
+                        &mut ranges,
+                    );
+                    typst_str.push_str(&format!(
+                        ", 1, {}",
                         if chunk_number != number_of_chunks - 1 {
                             "rstick($···$),"
                         } else {
@@ -728,10 +1476,21 @@ pub fn circuit_into_typst_str(
             if let Some(ref add_bosonic_gates) = additional_bosonic_gates {
                 let current_chunk = &add_bosonic_gates[chunk_number];
                 for (qubit_index, gates) in current_chunk.iter().enumerate() {
+                    start_row(&mut typst_str, &mut first_row);
+                    typst_str.push_str(&format!(
+                        "lstick($···{}$), ",
+                        wire_labels.wire_name(Wire::Boson(qubit_index)),
+                    ));
+                    cells_to_typst(
+                        &mut typst_str,
+                        gates,
+                        Wire::Boson(qubit_index),
+                        n_qubits,
+                        n_bosons,
+                        &mut ranges,
+                    );
                     typst_str.push_str(&format!(
-                        "lstick($···b[{}]$), {}, 1, {}[\\ ],\n",
-                        qubit_index,
-                        gates.join(", "),
+                        ", 1, {}",
                         if chunk_number != number_of_chunks - 1 {
                             "rstick($···$),"
                         } else {
@@ -744,17 +1503,22 @@ pub fn circuit_into_typst_str(
                 let current_chunk = &add_classical_gates[chunk_number];
                 for (index, gates) in current_chunk.clone().iter_mut().enumerate() {
                     gates.insert(0, classical_gates[index][1].clone());
-                    typst_str.push_str(&format!("{}, 1, [\\ ],\n", gates.join(", "),));
+                    start_row(&mut typst_str, &mut first_row);
+                    cells_to_typst(
+                        &mut typst_str,
+                        gates,
+                        Wire::Classical(index),
+                        n_qubits,
+                        n_bosons,
+                        &mut ranges,
+                    );
+                    typst_str.push_str(", 1, ");
                 }
             }
         }
     }
-    typst_str = typst_str
-        .strip_suffix(" [\\ ],\n")
-        .map(str::to_owned)
-        .unwrap_or(typst_str);
-    typst_str.push_str(")\n}\n");
-    Ok(typst_str)
+    typst_str.push_str(")\n");
+    Ok((typst_str, ranges))
 }
 
 /// Converts a qoqo circuit to an image.
@@ -763,11 +1527,8 @@ pub fn circuit_into_typst_str(
 ///
 /// * `circuit` - The circuit to convert.
 /// * `pixels_per_point` - The pixel per point ratio.
-/// * `render_pragmas` - Whether to render Pragma Operations or not.
-/// * `initialization_mode` - The initialization mode of the circuit representation.
-/// * `max_length` - The maximum length of a circuit line. If the circuit line
-///   is longer than this value, it will be split into multiple lines.
-/// * `rounding_accuracy` - The number of digits to round to when displaying floats.
+/// * `options` - The options of the circuit representation.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
 ///
 /// ## Returns
 ///
@@ -775,17 +1536,156 @@ pub fn circuit_into_typst_str(
 pub fn circuit_to_image(
     circuit: &Circuit,
     pixels_per_point: Option<f32>,
-    render_pragmas: RenderPragmas,
-    initialization_mode: Option<InitializationMode>,
-    max_length: Option<usize>,
-    rounding_accuracy: Option<usize>,
-) -> Result<DynamicImage, RoqoqoBackendError> {
-    let typst_str = circuit_into_typst_str(
-        circuit,
-        render_pragmas,
-        initialization_mode,
-        max_length,
-        rounding_accuracy,
-    )?;
-    render_typst_str(typst_str, pixels_per_point)
+    options: &RenderOptions,
+    backend_config: Option<&BackendConfig>,
+) -> Result<DynamicImage, RoqollageError> {
+    backend_from_config(backend_config)?.circuit_to_image(circuit, pixels_per_point, options)
+}
+
+/// Converts a qoqo circuit to an image, drawing the operations not supported as placeholders.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `pixels_per_point` - The pixel per point ratio.
+/// * `options` - The options of the circuit representation.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * `(DynamicImage, Vec<String>)` - The image reprensenting the circuit and the warnings about
+///   the operations drawn as placeholders.
+pub fn circuit_to_lenient_image(
+    circuit: &Circuit,
+    pixels_per_point: Option<f32>,
+    options: &RenderOptions,
+    backend_config: Option<&BackendConfig>,
+) -> Result<(DynamicImage, Vec<String>), RoqollageError> {
+    let mut warnings = Vec::new();
+    let (typst_str, ranges) = circuit_into_typst_document(circuit, options, Some(&mut warnings))?;
+    let image = backend_from_config(backend_config)?
+        .render_typst_str(typst_str, pixels_per_point)
+        .map_err(|err| locate_diagnostics(err, circuit, &ranges))?;
+    Ok((image, warnings))
+}
+
+/// Converts several qoqo circuits to images concurrently.
+///
+/// The circuits are rendered on a thread pool, by backends sharing the same fonts and package files.
+///
+///  ## Arguments
+///
+/// * `circuits` - The circuits to convert.
+/// * `pixels_per_point` - The pixel per point ratio.
+/// * `options` - The options of the circuit representation.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * `Ok(Vec<Result<DynamicImage, RoqollageError>>)` - The result of the conversion of each circuit,
+///   in the same order as the circuits.
+/// * `Err(RoqollageError)` - The Typst backend couldn't be created.
+pub fn circuits_to_images(
+    circuits: &[Circuit],
+    pixels_per_point: Option<f32>,
+    options: &RenderOptions,
+    backend_config: Option<&BackendConfig>,
+) -> Result<Vec<Result<DynamicImage, RoqollageError>>, RoqollageError> {
+    let backend = backend_from_config(backend_config)?;
+    let images = circuits
+        .par_iter()
+        .map_init(
+            || backend.fork(String::new()),
+            |thread_backend, circuit| {
+                thread_backend.circuit_to_image(circuit, pixels_per_point, options)
+            },
+        )
+        .collect();
+    // The compilation cache is global: it is only evicted once all the threads are done.
+    comemo::evict(10);
+    Ok(images)
+}
+
+/// Converts several qoqo circuits to images concurrently, drawing the operations not supported as
+/// placeholders.
+///
+/// The circuits are rendered on a thread pool, by backends sharing the same fonts and package files.
+///
+///  ## Arguments
+///
+/// * `circuits` - The circuits to convert.
+/// * `pixels_per_point` - The pixel per point ratio.
+/// * `options` - The options of the circuit representation.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * `Ok(Vec<Result<(DynamicImage, Vec<String>), RoqollageError>>)` - The result of the conversion
+///   of each circuit, with the warnings about the operations drawn as placeholders, in the same
+///   order as the circuits.
+/// * `Err(RoqollageError)` - The Typst backend couldn't be created.
+#[allow(clippy::type_complexity)]
+pub fn circuits_to_lenient_images(
+    circuits: &[Circuit],
+    pixels_per_point: Option<f32>,
+    options: &RenderOptions,
+    backend_config: Option<&BackendConfig>,
+) -> Result<Vec<Result<(DynamicImage, Vec<String>), RoqollageError>>, RoqollageError> {
+    let backend = backend_from_config(backend_config)?;
+    let images = circuits
+        .par_iter()
+        .map_init(
+            || backend.fork(String::new()),
+            |thread_backend, circuit| {
+                let mut warnings = Vec::new();
+                let (typst_str, ranges) =
+                    circuit_into_typst_document(circuit, options, Some(&mut warnings))?;
+                let image = thread_backend
+                    .render_typst_str(typst_str, pixels_per_point)
+                    .map_err(|err| locate_diagnostics(err, circuit, &ranges))?;
+                Ok((image, warnings))
+            },
+        )
+        .collect();
+    // The compilation cache is global: it is only evicted once all the threads are done.
+    comemo::evict(10);
+    Ok(images)
+}
+
+/// Converts a qoqo circuit to a SVG image.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `options` - The options of the circuit representation.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * String: The SVG image reprensenting the circuit.
+pub fn circuit_to_svg(
+    circuit: &Circuit,
+    options: &RenderOptions,
+    backend_config: Option<&BackendConfig>,
+) -> Result<String, RoqollageError> {
+    backend_from_config(backend_config)?.circuit_to_svg(circuit, options)
+}
+
+/// Converts a qoqo circuit to a PDF document.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `options` - The options of the circuit representation.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * Vec<u8>: The bytes of the PDF document reprensenting the circuit.
+pub fn circuit_to_pdf(
+    circuit: &Circuit,
+    options: &RenderOptions,
+    backend_config: Option<&BackendConfig>,
+) -> Result<Vec<u8>, RoqollageError> {
+    backend_from_config(backend_config)?.circuit_to_pdf(circuit, options)
 }
@@ -0,0 +1,445 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use image::DynamicImage;
+use qoqo_calculator::CalculatorFloat;
+use roqoqo::{
+    devices::Device,
+    operations::{InvolveQubits, InvolvedQubits, Operate},
+    Circuit,
+};
+
+use crate::{
+    backend_from_config, format_calculator, BackendConfig, NumberFormat, RoqollageError,
+    TYPST_PREAMBLE,
+};
+
+/// Distance between two neighbouring qubits of the diagram, in points.
+const NODE_SPACING: f64 = 110.0;
+/// Radius of the qubit nodes of the diagram, in points.
+const NODE_RADIUS: f64 = 11.0;
+/// Margin around the nodes of the diagram, leaving room for their annotations, in points.
+const DIAGRAM_MARGIN: f64 = 60.0;
+
+/// Fill color of the gates a device doesn't support.
+const UNSUPPORTED_GATE_FILL: &str = "orange.lighten(40%)";
+/// Fill color of the two-qubit gates acting on qubits a device doesn't connect.
+const NON_ADJACENT_QUBITS_FILL: &str = "red.lighten(40%)";
+
+/// Kind of constraint of a device violated by an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceViolationKind {
+    /// The device doesn't support the gate on its qubits: the gate time is None.
+    UnsupportedGate,
+    /// The two qubits of the gate are not an edge of the device.
+    NonAdjacentQubits,
+}
+
+/// Operation of a circuit violating a constraint of a device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceViolation {
+    /// Index of the operation in the circuit.
+    pub index: usize,
+    /// Name of the operation.
+    pub hqslang: String,
+    /// Qubits the operation acts on, in ascending order.
+    pub qubits: Vec<usize>,
+    /// Kind of constraint violated by the operation.
+    pub kind: DeviceViolationKind,
+}
+
+impl DeviceViolation {
+    /// Returns the Typst fill color highlighting the operation.
+    pub fn fill(&self) -> &'static str {
+        match self.kind {
+            DeviceViolationKind::UnsupportedGate => UNSUPPORTED_GATE_FILL,
+            DeviceViolationKind::NonAdjacentQubits => NON_ADJACENT_QUBITS_FILL,
+        }
+    }
+
+    /// Returns a short description of the violation.
+    pub fn description(&self) -> String {
+        let qubits = self
+            .qubits
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<String>>()
+            .join(", ");
+        format!(
+            "operation #{} ({} on qubit{} {qubits}): {}",
+            self.index,
+            self.hqslang,
+            if self.qubits.len() > 1 { "s" } else { "" },
+            match self.kind {
+                DeviceViolationKind::UnsupportedGate => "gate not supported by the device",
+                DeviceViolationKind::NonAdjacentQubits => "qubits not connected in the device",
+            }
+        )
+    }
+}
+
+/// Lists the gates of a circuit violating the constraints of a device.
+///
+/// Single-qubit, two-qubit and multi-qubit gates are unsupported when the device has no gate
+/// time for them on their qubits. Two-qubit gates on a pair of qubits that is not one of the
+/// `two_qubit_edges` of the device are reported as non-adjacent. The other operations (pragmas,
+/// measurements, definitions...) are not checked.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to check.
+/// * `device` - The device the circuit should run on.
+///
+/// ## Returns
+///
+/// * `Vec<DeviceViolation>` - The violations, in the order of the operations.
+pub fn device_violations(circuit: &Circuit, device: &dyn Device) -> Vec<DeviceViolation> {
+    let edges = device.two_qubit_edges();
+    circuit
+        .iter()
+        .enumerate()
+        .filter_map(|(index, operation)| {
+            let mut qubits: Vec<usize> = match operation.involved_qubits() {
+                InvolvedQubits::Set(qubits) => qubits.into_iter().collect(),
+                _ => return None,
+            };
+            qubits.sort();
+            let hqslang = operation.hqslang();
+            let tags = operation.tags();
+            let kind = if tags.contains(&"SingleQubitGateOperation") && qubits.len() == 1 {
+                device
+                    .single_qubit_gate_time(hqslang, &qubits[0])
+                    .is_none()
+                    .then_some(DeviceViolationKind::UnsupportedGate)
+            } else if tags.contains(&"TwoQubitGateOperation") && qubits.len() == 2 {
+                let (first, second) = (qubits[0], qubits[1]);
+                if !edges.contains(&(first, second)) && !edges.contains(&(second, first)) {
+                    Some(DeviceViolationKind::NonAdjacentQubits)
+                } else {
+                    (device
+                        .two_qubit_gate_time(hqslang, &first, &second)
+                        .is_none()
+                        && device
+                            .two_qubit_gate_time(hqslang, &second, &first)
+                            .is_none())
+                    .then_some(DeviceViolationKind::UnsupportedGate)
+                }
+            } else if tags.contains(&"MultiQubitGateOperation") {
+                device
+                    .multi_qubit_gate_time(hqslang, &qubits)
+                    .is_none()
+                    .then_some(DeviceViolationKind::UnsupportedGate)
+            } else {
+                None
+            }?;
+            Some(DeviceViolation {
+                index,
+                hqslang: hqslang.to_owned(),
+                qubits,
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Returns the fill colors highlighting the operations violating the constraints of a device.
+pub(crate) fn violation_highlights(violations: &[DeviceViolation]) -> HashMap<usize, String> {
+    violations
+        .iter()
+        .map(|violation| (violation.index, violation.fill().to_owned()))
+        .collect()
+}
+
+/// Formats a small square filled with a highlight color, for the legends.
+pub(crate) fn color_swatch(fill: &str) -> String {
+    format!("#box(width: 8pt, height: 8pt, fill: {fill}, stroke: 0.5pt)")
+}
+
+/// Formats the legend listing the operations violating the constraints of a device.
+pub(crate) fn violations_legend(violations: &[DeviceViolation]) -> String {
+    let mut legend = format!(
+        "#block(stroke: 0.5pt + gray, inset: 6pt, radius: 3pt, breakable: false)[\n  *Device violations: {}* \\\n  {} unsupported gate {} qubits not connected\n",
+        violations.len(),
+        color_swatch(UNSUPPORTED_GATE_FILL),
+        color_swatch(NON_ADJACENT_QUBITS_FILL),
+    );
+    if violations.is_empty() {
+        legend.push_str("  - none\n");
+    }
+    for violation in violations {
+        legend.push_str(&format!(
+            "  - {} {}\n",
+            color_swatch(violation.fill()),
+            violation.description().replace('#', "\\#")
+        ));
+    }
+    legend.push_str("]\n");
+    legend
+}
+
+/// Returns the number of columns of the square lattice the device edges fit in, if any.
+///
+/// The qubits are numbered row by row in a square lattice: the edges either link two neighbours
+/// of the same row or two qubits of the same column in neighbouring rows.
+///
+/// # Arguments
+///
+/// * `number_qubits` - The number of qubits of the device.
+/// * `edges` - The two-qubit edges of the device.
+fn lattice_columns(number_qubits: usize, edges: &[(usize, usize)]) -> Option<usize> {
+    (1..=number_qubits)
+        .filter(|columns| number_qubits % columns == 0)
+        .filter(|columns| {
+            edges.iter().all(|(first, second)| {
+                let (low, high) = ((*first).min(*second), (*first).max(*second));
+                (high == low + 1 && high % columns != 0) || high == low + columns
+            })
+        })
+        .min_by_key(|columns| {
+            let rows = number_qubits / columns;
+            (rows.abs_diff(*columns), usize::MAX - columns)
+        })
+}
+
+/// Places the qubits of the device on a square lattice if the edges allow it, on a circle
+/// otherwise.
+///
+/// # Arguments
+///
+/// * `number_qubits` - The number of qubits of the device.
+/// * `edges` - The two-qubit edges of the device.
+///
+/// # Returns
+///
+/// * `Vec<(f64, f64)>` - The position of each qubit, in points.
+fn node_positions(number_qubits: usize, edges: &[(usize, usize)]) -> Vec<(f64, f64)> {
+    match lattice_columns(number_qubits, edges) {
+        Some(columns) => (0..number_qubits)
+            .map(|qubit| {
+                (
+                    (qubit % columns) as f64 * NODE_SPACING,
+                    (qubit / columns) as f64 * NODE_SPACING,
+                )
+            })
+            .collect(),
+        None => {
+            let radius = (NODE_SPACING * number_qubits as f64 / std::f64::consts::TAU)
+                .max(NODE_SPACING * 0.6);
+            (0..number_qubits)
+                .map(|qubit| {
+                    let angle = std::f64::consts::TAU * qubit as f64 / number_qubits as f64
+                        - std::f64::consts::FRAC_PI_2;
+                    (radius * (1.0 + angle.cos()), radius * (1.0 + angle.sin()))
+                })
+                .collect()
+        }
+    }
+}
+
+/// Formats a gate time, or a decoherence rate, as a Typst math expression.
+fn format_value(name: &str, value: f64, number_format: NumberFormat) -> String {
+    format!(
+        "$\"{name}\": {}$",
+        format_calculator(&CalculatorFloat::Float(value), number_format)
+    )
+}
+
+/// Returns the annotations of a qubit: its single-qubit gate times and its decoherence rates.
+///
+/// # Arguments
+///
+/// * `device` - The device.
+/// * `qubit` - The qubit to annotate.
+/// * `number_format` - How the numbers are displayed.
+fn qubit_annotations(
+    device: &dyn Device,
+    qubit: usize,
+    number_format: NumberFormat,
+) -> Vec<String> {
+    let mut gate_names = device.single_qubit_gate_names();
+    gate_names.sort();
+    let mut annotations: Vec<String> = gate_names
+        .iter()
+        .filter_map(|name| {
+            device
+                .single_qubit_gate_time(name, &qubit)
+                .map(|time| format_value(name, time, number_format))
+        })
+        .collect();
+    if let Some(rates) = device.qubit_decoherence_rates(&qubit) {
+        if rates.iter().any(|rate| *rate != 0.0) && rates.nrows() >= 3 && rates.ncols() >= 3 {
+            annotations.push(format!(
+                "$Gamma_(+,-,z): ({}, {}, {})$",
+                format_calculator(&CalculatorFloat::Float(rates[[0, 0]]), number_format),
+                format_calculator(&CalculatorFloat::Float(rates[[1, 1]]), number_format),
+                format_calculator(&CalculatorFloat::Float(rates[[2, 2]]), number_format),
+            ));
+        }
+    }
+    annotations
+}
+
+/// Returns the annotations of an edge: its two-qubit gate times.
+///
+/// # Arguments
+///
+/// * `device` - The device.
+/// * `edge` - The two qubits of the edge.
+/// * `number_format` - How the numbers are displayed.
+fn edge_annotations(
+    device: &dyn Device,
+    edge: (usize, usize),
+    number_format: NumberFormat,
+) -> Vec<String> {
+    let mut gate_names = device.two_qubit_gate_names();
+    gate_names.sort();
+    gate_names
+        .iter()
+        .filter_map(|name| {
+            device
+                .two_qubit_gate_time(name, &edge.0, &edge.1)
+                .or_else(|| device.two_qubit_gate_time(name, &edge.1, &edge.0))
+                .map(|time| format_value(name, time, number_format))
+        })
+        .collect()
+}
+
+/// Places a small text centered on a point of the diagram.
+fn centered_label(x: f64, y: f64, annotations: &[String]) -> String {
+    format!(
+        "  #place(top + left, dx: {:.1}pt, dy: {:.1}pt, box(width: {:.1}pt, align(center, box(fill: white, inset: 1pt, text(size: 6pt)[{}]))))\n",
+        x - NODE_SPACING / 2.0,
+        y,
+        NODE_SPACING,
+        annotations.join(" \\ "),
+    )
+}
+
+/// Converts a device to a typst string drawing its connectivity graph.
+///
+/// The qubits are placed on a square lattice if the edges of the device fit in one, on a circle
+/// otherwise. The qubits are annotated with their single-qubit gate times and decoherence rates
+/// (the diagonal of the rate matrix in the basis sigma^+, sigma^-, sigma^z) and the edges with
+/// their two-qubit gate times. The annotations shared by all the qubits, or by all the edges,
+/// are displayed once above the graph.
+///
+///  ## Arguments
+///
+/// * `device` - The device to draw.
+/// * `number_format` - How the numbers are displayed, the default format if None.
+///
+/// ## Returns
+///
+/// * `String` - The string representation of the device in Typst.
+pub fn device_into_typst_str(device: &dyn Device, number_format: Option<NumberFormat>) -> String {
+    let number_format = number_format.unwrap_or_default();
+    let number_qubits = device.number_qubits();
+    let mut edges = device.two_qubit_edges();
+    edges.sort();
+    let positions = node_positions(number_qubits, &edges);
+    let qubit_labels: Vec<Vec<String>> = (0..number_qubits)
+        .map(|qubit| qubit_annotations(device, qubit, number_format))
+        .collect();
+    let edge_labels: Vec<Vec<String>> = edges
+        .iter()
+        .map(|edge| edge_annotations(device, *edge, number_format))
+        .collect();
+    let shared_qubit_labels =
+        number_qubits > 1 && qubit_labels.iter().all(|labels| labels == &qubit_labels[0]);
+    let shared_edge_labels =
+        edges.len() > 1 && edge_labels.iter().all(|labels| labels == &edge_labels[0]);
+
+    let mut typst_str = format!(
+        "{TYPST_PREAMBLE}#set text(size: 8pt)\n*Device connectivity* \\\nQubits: {number_qubits}, two-qubit edges: {} \\\n",
+        edges.len()
+    );
+    if shared_qubit_labels && !qubit_labels[0].is_empty() {
+        typst_str.push_str(&format!("Every qubit: {} \\\n", qubit_labels[0].join(", ")));
+    }
+    if shared_edge_labels && !edge_labels[0].is_empty() {
+        typst_str.push_str(&format!("Every edge: {} \\\n", edge_labels[0].join(", ")));
+    }
+
+    let max_x = positions.iter().map(|(x, _)| *x).fold(0.0, f64::max);
+    let max_y = positions.iter().map(|(_, y)| *y).fold(0.0, f64::max);
+    typst_str.push_str(&format!(
+        "#box(width: {:.1}pt, height: {:.1}pt)[\n",
+        max_x + 2.0 * DIAGRAM_MARGIN,
+        max_y + 2.0 * DIAGRAM_MARGIN,
+    ));
+    let point = |qubit: usize| {
+        let (x, y) = positions[qubit];
+        (x + DIAGRAM_MARGIN, y + DIAGRAM_MARGIN)
+    };
+    for (first, second) in edges.iter().filter(|(first, second)| {
+        *first < number_qubits && *second < number_qubits && first != second
+    }) {
+        let (x_start, y_start) = point(*first);
+        let (x_end, y_end) = point(*second);
+        typst_str.push_str(&format!(
+            "  #place(top + left, line(start: ({x_start:.1}pt, {y_start:.1}pt), end: ({x_end:.1}pt, {y_end:.1}pt), stroke: 1pt + gray))\n",
+        ));
+    }
+    if !shared_edge_labels {
+        for ((first, second), labels) in edges.iter().zip(edge_labels.iter()) {
+            if labels.is_empty() || *first >= number_qubits || *second >= number_qubits {
+                continue;
+            }
+            let (x_start, y_start) = point(*first);
+            let (x_end, y_end) = point(*second);
+            typst_str.push_str(&centered_label(
+                (x_start + x_end) / 2.0,
+                (y_start + y_end) / 2.0 - 4.0,
+                labels,
+            ));
+        }
+    }
+    for (qubit, labels) in qubit_labels.iter().enumerate() {
+        let (x, y) = point(qubit);
+        typst_str.push_str(&format!(
+            "  #place(top + left, dx: {:.1}pt, dy: {:.1}pt, circle(radius: {NODE_RADIUS:.1}pt, fill: white, stroke: 1pt, inset: 0pt, align(center + horizon)[{qubit}]))\n",
+            x - NODE_RADIUS,
+            y - NODE_RADIUS,
+        ));
+        if !shared_qubit_labels && !labels.is_empty() {
+            typst_str.push_str(&centered_label(x, y + NODE_RADIUS + 2.0, labels));
+        }
+    }
+    typst_str.push_str("]\n");
+    typst_str
+}
+
+/// Converts a device to an image of its connectivity graph.
+///
+///  ## Arguments
+///
+/// * `device` - The device to draw.
+/// * `pixels_per_point` - The pixel per point ratio.
+/// * `number_format` - How the numbers are displayed, the default format if None.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * DynamicImage: The image reprensenting the device.
+pub fn device_to_image(
+    device: &dyn Device,
+    pixels_per_point: Option<f32>,
+    number_format: Option<NumberFormat>,
+    backend_config: Option<&BackendConfig>,
+) -> Result<DynamicImage, RoqollageError> {
+    backend_from_config(backend_config)?.render_typst_str(
+        device_into_typst_str(device, number_format),
+        pixels_per_point,
+    )
+}
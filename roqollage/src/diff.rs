@@ -0,0 +1,220 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use image::DynamicImage;
+use roqoqo::{
+    operations::{InvolveQubits, Operate, Operation},
+    Circuit,
+};
+
+use crate::{
+    backend_from_config, circuit_into_quill_code, color_swatch, BackendConfig, RenderOptions,
+    RoqollageError, QUILL_PACKAGE, TYPST_PREAMBLE,
+};
+
+/// Fill color of the operations only present in the second circuit.
+const ADDED_FILL: &str = "green.lighten(40%)";
+/// Fill color of the operations only present in the first circuit.
+const REMOVED_FILL: &str = "red.lighten(40%)";
+/// Fill color of the operations whose parameters changed between the circuits.
+const CHANGED_FILL: &str = "yellow.lighten(20%)";
+
+/// Difference between two circuits for one operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OperationDiff {
+    /// The operation is in both circuits.
+    Unchanged(Operation),
+    /// The operation is only in the second circuit.
+    Added(Operation),
+    /// The operation is only in the first circuit.
+    Removed(Operation),
+    /// The operation acts on the same qubits in both circuits but its parameters changed.
+    Changed {
+        /// The operation in the first circuit.
+        before: Operation,
+        /// The operation in the second circuit.
+        after: Operation,
+    },
+}
+
+/// Aligns the operations of two circuits.
+///
+/// The operations are aligned with the longest common subsequence of equal operations. Between
+/// two aligned operations, a removed and an added operation with the same name acting on the
+/// same qubits are reported as a single changed operation, placed where the added one is.
+///
+///  ## Arguments
+///
+/// * `before` - The first circuit.
+/// * `after` - The second circuit.
+///
+/// ## Returns
+///
+/// * `Vec<OperationDiff>` - The differences, in the order of the operations.
+pub fn diff_circuits(before: &Circuit, after: &Circuit) -> Vec<OperationDiff> {
+    let before: Vec<&Operation> = before.iter().collect();
+    let after: Vec<&Operation> = after.iter().collect();
+
+    // Length of the longest common subsequence of the suffixes of the circuits.
+    let mut lengths = vec![vec![0_usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lengths[i][j] = if before[i] == after[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut diffs = Vec::new();
+    let mut pending_removed: Vec<Operation> = Vec::new();
+    let mut pending_added: Vec<Operation> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            flush_changes(&mut diffs, &mut pending_removed, &mut pending_added);
+            diffs.push(OperationDiff::Unchanged(before[i].clone()));
+            i += 1;
+            j += 1;
+        } else if j < after.len() && (i == before.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            pending_added.push(after[j].clone());
+            j += 1;
+        } else {
+            pending_removed.push(before[i].clone());
+            i += 1;
+        }
+    }
+    flush_changes(&mut diffs, &mut pending_removed, &mut pending_added);
+    diffs
+}
+
+/// Adds the removed and added operations found between two aligned operations to the diff,
+/// pairing the ones with the same name and qubits as changed operations.
+fn flush_changes(
+    diffs: &mut Vec<OperationDiff>,
+    removed: &mut Vec<Operation>,
+    added: &mut Vec<Operation>,
+) {
+    let mut remaining_removed: Vec<Option<Operation>> = removed.drain(..).map(Some).collect();
+    let mut changes = Vec::new();
+    for operation in added.drain(..) {
+        let paired = remaining_removed.iter_mut().find(|candidate| {
+            candidate.as_ref().is_some_and(|candidate| {
+                candidate.hqslang() == operation.hqslang()
+                    && candidate.involved_qubits() == operation.involved_qubits()
+            })
+        });
+        match paired.and_then(Option::take) {
+            Some(before) => changes.push(OperationDiff::Changed {
+                before,
+                after: operation,
+            }),
+            None => changes.push(OperationDiff::Added(operation)),
+        }
+    }
+    diffs.extend(
+        remaining_removed
+            .into_iter()
+            .flatten()
+            .map(OperationDiff::Removed),
+    );
+    diffs.extend(changes);
+}
+
+/// Converts the differences between two qoqo circuits to a typst string.
+///
+/// The operations of both circuits are drawn in a single circuit: the added operations are
+/// filled in green, the removed ones in red and the ones whose parameters changed in yellow, with
+/// their new parameters. A legend counts the differences below the circuit.
+///
+///  ## Arguments
+///
+/// * `before` - The first circuit.
+/// * `after` - The second circuit.
+/// * `options` - The options of the circuit representation. The device and statistics options
+///   are not used.
+///
+/// ## Returns
+///
+/// * `String` - The string representation of the differences in Typst.
+pub fn circuits_diff_into_typst_str(
+    before: &Circuit,
+    after: &Circuit,
+    options: &RenderOptions,
+) -> Result<String, RoqollageError> {
+    let (mut n_added, mut n_removed, mut n_changed) = (0, 0, 0);
+    let operations: Vec<(Operation, Option<&str>)> = diff_circuits(before, after)
+        .into_iter()
+        .map(|diff| match diff {
+            OperationDiff::Unchanged(operation) => (operation, None),
+            OperationDiff::Added(operation) => {
+                n_added += 1;
+                (operation, Some(ADDED_FILL))
+            }
+            OperationDiff::Removed(operation) => {
+                n_removed += 1;
+                (operation, Some(REMOVED_FILL))
+            }
+            OperationDiff::Changed { after, .. } => {
+                n_changed += 1;
+                (after, Some(CHANGED_FILL))
+            }
+        })
+        .collect();
+    // The circuit lists its definitions before its other operations.
+    let (definitions, others): (Vec<_>, Vec<_>) = operations
+        .into_iter()
+        .partition(|(operation, _)| operation.tags().contains(&"Definition"));
+    let mut merged = Circuit::new();
+    let mut highlights: HashMap<usize, String> = HashMap::new();
+    for (index, (operation, fill)) in definitions.into_iter().chain(others).enumerate() {
+        merged.add_operation(operation);
+        if let Some(fill) = fill {
+            highlights.insert(index, fill.to_owned());
+        }
+    }
+    Ok(format!(
+        "{TYPST_PREAMBLE}#{{\n    import \"{QUILL_PACKAGE}\": *\n{}}}\n{} added: {n_added} {} removed: {n_removed} {} changed: {n_changed}\n",
+        circuit_into_quill_code(&merged, options, &highlights, None)?.0,
+        color_swatch(ADDED_FILL),
+        color_swatch(REMOVED_FILL),
+        color_swatch(CHANGED_FILL),
+    ))
+}
+
+/// Converts the differences between two qoqo circuits to an image.
+///
+///  ## Arguments
+///
+/// * `before` - The first circuit.
+/// * `after` - The second circuit.
+/// * `pixels_per_point` - The pixel per point ratio.
+/// * `options` - The options of the circuit representation. The device and statistics options
+///   are not used.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * DynamicImage: The image reprensenting the differences.
+pub fn circuits_diff_to_image(
+    before: &Circuit,
+    after: &Circuit,
+    pixels_per_point: Option<f32>,
+    options: &RenderOptions,
+    backend_config: Option<&BackendConfig>,
+) -> Result<DynamicImage, RoqollageError> {
+    let typst_str = circuits_diff_into_typst_str(before, after, options)?;
+    backend_from_config(backend_config)?.render_typst_str(typst_str, pixels_per_point)
+}
@@ -0,0 +1,179 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt, ops::Range, path::PathBuf};
+
+use roqoqo::RoqoqoBackendError;
+
+/// Diagnostic returned by the Typst compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypstDiagnostic {
+    /// Index in the circuit of the operation whose Typst code caused the error, if known.
+    pub index: Option<usize>,
+    /// Description of the operation whose Typst code caused the error, e.g. `RotateX on qubit 3`.
+    pub operation: Option<String>,
+    /// Message of the diagnostic.
+    pub message: String,
+    /// Hints given by the compiler to fix the error.
+    pub hints: Vec<String>,
+    /// Byte range of the error in the generated Typst source, None if it is in another file.
+    pub range: Option<Range<usize>>,
+    /// Text of the generated Typst source in the range of the error.
+    pub source: Option<String>,
+}
+
+impl fmt::Display for TypstDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.index, &self.operation) {
+            (Some(index), Some(operation)) => write!(f, "operation #{index} ({operation}): ")?,
+            (Some(index), None) => write!(f, "operation #{index}: ")?,
+            _ => {}
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(range) = &self.range {
+            write!(f, " at {}..{}", range.start, range.end)?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, " in `{source}`")?;
+        }
+        if !self.hints.is_empty() {
+            write!(f, " (hints: {})", self.hints.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors of the roqollage drawing functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoqollageError {
+    /// A font or a package couldn't be downloaded.
+    NetworkError {
+        /// Description of the error.
+        msg: String,
+    },
+    /// A file of the cache directory couldn't be created, read or written.
+    CacheError {
+        /// Path of the file.
+        path: PathBuf,
+        /// Description of the error.
+        msg: String,
+    },
+    /// The Typst compiler rejected the generated source.
+    TypstCompileError {
+        /// The errors reported by the compiler.
+        diagnostics: Vec<TypstDiagnostic>,
+    },
+    /// An operation of the circuit is not supported by the Typst backend.
+    UnsupportedOperation {
+        /// Index of the operation in the circuit, if known.
+        index: Option<usize>,
+        /// Name of the operation.
+        hqslang: &'static str,
+    },
+    /// An operation of the circuit can't be drawn, e.g. because it acts on no qubit.
+    InvalidOperation {
+        /// Index of the operation in the circuit, if known.
+        index: Option<usize>,
+        /// Description of the error.
+        msg: String,
+    },
+    /// An option given to the drawing functions is not valid.
+    InvalidOption {
+        /// Description of the error.
+        msg: String,
+    },
+    /// The compiled document couldn't be exported to an image or a document.
+    RenderError {
+        /// Description of the error.
+        msg: String,
+    },
+}
+
+impl RoqollageError {
+    /// Returns the index of the operation of the circuit causing the error, if any.
+    ///
+    /// For a Typst compilation error, this is the operation of the first located diagnostic.
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            RoqollageError::UnsupportedOperation { index, .. }
+            | RoqollageError::InvalidOperation { index, .. } => *index,
+            RoqollageError::TypstCompileError { diagnostics } => {
+                diagnostics.iter().find_map(|diagnostic| diagnostic.index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Sets the index of the operation causing the error if it is not known yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation_index` - The index of the operation in the circuit.
+    pub(crate) fn at_operation(mut self, operation_index: usize) -> Self {
+        if let RoqollageError::UnsupportedOperation { index, .. }
+        | RoqollageError::InvalidOperation { index, .. } = &mut self
+        {
+            index.get_or_insert(operation_index);
+        }
+        self
+    }
+}
+
+impl fmt::Display for RoqollageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoqollageError::NetworkError { msg } => write!(f, "Network error: {msg}"),
+            RoqollageError::CacheError { path, msg } => {
+                write!(f, "Cache error for {path:?}: {msg}")
+            }
+            RoqollageError::TypstCompileError { diagnostics } => {
+                write!(f, "Error during the Typst compilation:")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{diagnostic}")?;
+                }
+                Ok(())
+            }
+            RoqollageError::UnsupportedOperation { index, hqslang } => match index {
+                Some(index) => write!(
+                    f,
+                    "Operation #{index} ({hqslang}) not supported by the Typst backend."
+                ),
+                None => write!(f, "Operation {hqslang} not supported by the Typst backend."),
+            },
+            RoqollageError::InvalidOperation { index, msg } => match index {
+                Some(index) => write!(f, "Invalid operation #{index}: {msg}"),
+                None => write!(f, "Invalid operation: {msg}"),
+            },
+            RoqollageError::InvalidOption { msg } => write!(f, "Invalid option: {msg}"),
+            RoqollageError::RenderError { msg } => write!(f, "Error during the rendering: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for RoqollageError {}
+
+impl From<RoqollageError> for RoqoqoBackendError {
+    fn from(err: RoqollageError) -> Self {
+        match err {
+            RoqollageError::NetworkError { msg } => RoqoqoBackendError::NetworkError { msg },
+            RoqollageError::UnsupportedOperation { hqslang, .. } => {
+                RoqoqoBackendError::OperationNotInBackend {
+                    backend: "TypstBackend",
+                    hqslang,
+                }
+            }
+            err => RoqoqoBackendError::GenericError {
+                msg: err.to_string(),
+            },
+        }
+    }
+}
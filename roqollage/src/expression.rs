@@ -0,0 +1,819 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use qoqo_calculator::CalculatorFloat;
+
+use crate::{
+    format_calculator, format_symbol_str, latex_math, latex_math_enabled, typst_math_to_latex,
+    NumberFormat,
+};
+
+/// Token of a symbolic qoqo_calculator expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A number literal.
+    Number(f64),
+    /// The name of a variable or a function.
+    Identifier(String),
+    /// One of the `+`, `-`, `*`, `/` and `^` operators.
+    Operator(char),
+    /// An opening parenthesis.
+    LeftParenthesis,
+    /// A closing parenthesis.
+    RightParenthesis,
+    /// The separator of the arguments of a function.
+    Comma,
+}
+
+/// Binary operator of a symbolic expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+/// Node of a parsed symbolic expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    /// A number literal.
+    Number(f64),
+    /// A variable.
+    Variable(String),
+    /// The negation of an expression.
+    Negation(Box<Expression>),
+    /// A binary operation.
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    /// A function call, with its arguments.
+    Function(String, Vec<Expression>),
+}
+
+/// How tightly a Typst math representation binds, used to decide where parentheses are needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Sum,
+    Negation,
+    Product,
+    Power,
+    Atom,
+}
+
+/// Splits a symbolic expression into tokens.
+///
+/// # Arguments
+///
+/// * `expression` - The symbolic expression.
+///
+/// # Returns
+///
+/// * `Option<Vec<Token>>` - The tokens, None if the expression contains unexpected characters.
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    while let Some(&character) = chars.get(position) {
+        match character {
+            character if character.is_whitespace() => position += 1,
+            character if character.is_ascii_digit() || character == '.' => {
+                let start = position;
+                while chars
+                    .get(position)
+                    .is_some_and(|next| next.is_ascii_digit() || *next == '.')
+                {
+                    position += 1;
+                }
+                if matches!(chars.get(position), Some('e' | 'E')) {
+                    let exponent_start = match chars.get(position + 1) {
+                        Some('+' | '-') => position + 2,
+                        _ => position + 1,
+                    };
+                    if chars
+                        .get(exponent_start)
+                        .is_some_and(|next| next.is_ascii_digit())
+                    {
+                        position = exponent_start;
+                        while chars
+                            .get(position)
+                            .is_some_and(|next| next.is_ascii_digit())
+                        {
+                            position += 1;
+                        }
+                    }
+                }
+                let literal: String = chars[start..position].iter().collect();
+                tokens.push(Token::Number(literal.parse().ok()?));
+            }
+            character if character.is_ascii_alphabetic() || character == '_' => {
+                let start = position;
+                while chars.get(position).is_some_and(|next| {
+                    next.is_ascii_alphanumeric() || *next == '_' || *next == '.'
+                }) {
+                    position += 1;
+                }
+                tokens.push(Token::Identifier(chars[start..position].iter().collect()));
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                tokens.push(Token::Operator(character));
+                position += 1;
+            }
+            '(' => {
+                tokens.push(Token::LeftParenthesis);
+                position += 1;
+            }
+            ')' => {
+                tokens.push(Token::RightParenthesis);
+                position += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                position += 1;
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+/// Parses a sum or difference of products, starting at the current position.
+///
+/// # Arguments
+///
+/// * `tokens` - The tokens of the expression.
+/// * `position` - The position of the first token of the sum, moved after it.
+fn parse_sum(tokens: &[Token], position: &mut usize) -> Option<Expression> {
+    let mut expression = parse_product(tokens, position)?;
+    while let Some(Token::Operator(character @ ('+' | '-'))) = tokens.get(*position) {
+        *position += 1;
+        let operator = if *character == '+' {
+            Operator::Add
+        } else {
+            Operator::Subtract
+        };
+        let right = parse_product(tokens, position)?;
+        expression = Expression::Binary(operator, Box::new(expression), Box::new(right));
+    }
+    Some(expression)
+}
+
+/// Parses a product or quotient of factors, starting at the current position.
+///
+/// # Arguments
+///
+/// * `tokens` - The tokens of the expression.
+/// * `position` - The position of the first token of the product, moved after it.
+fn parse_product(tokens: &[Token], position: &mut usize) -> Option<Expression> {
+    let mut expression = parse_unary(tokens, position)?;
+    while let Some(Token::Operator(character @ ('*' | '/'))) = tokens.get(*position) {
+        *position += 1;
+        let operator = if *character == '*' {
+            Operator::Multiply
+        } else {
+            Operator::Divide
+        };
+        let right = parse_unary(tokens, position)?;
+        expression = Expression::Binary(operator, Box::new(expression), Box::new(right));
+    }
+    Some(expression)
+}
+
+/// Parses a factor with its leading signs, starting at the current position.
+///
+/// # Arguments
+///
+/// * `tokens` - The tokens of the expression.
+/// * `position` - The position of the first token of the factor, moved after it.
+fn parse_unary(tokens: &[Token], position: &mut usize) -> Option<Expression> {
+    match tokens.get(*position) {
+        Some(Token::Operator('-')) => {
+            *position += 1;
+            Some(Expression::Negation(Box::new(parse_unary(
+                tokens, position,
+            )?)))
+        }
+        Some(Token::Operator('+')) => {
+            *position += 1;
+            parse_unary(tokens, position)
+        }
+        _ => parse_power(tokens, position),
+    }
+}
+
+/// Parses a power, right associative, starting at the current position.
+///
+/// # Arguments
+///
+/// * `tokens` - The tokens of the expression.
+/// * `position` - The position of the first token of the power, moved after it.
+fn parse_power(tokens: &[Token], position: &mut usize) -> Option<Expression> {
+    let base = parse_primary(tokens, position)?;
+    if tokens.get(*position) == Some(&Token::Operator('^')) {
+        *position += 1;
+        let exponent = parse_unary(tokens, position)?;
+        return Some(Expression::Binary(
+            Operator::Power,
+            Box::new(base),
+            Box::new(exponent),
+        ));
+    }
+    Some(base)
+}
+
+/// Parses a number, a variable, a function call or a parenthesized expression, starting at the
+/// current position.
+///
+/// # Arguments
+///
+/// * `tokens` - The tokens of the expression.
+/// * `position` - The position of the first token, moved after the parsed expression.
+fn parse_primary(tokens: &[Token], position: &mut usize) -> Option<Expression> {
+    let token = tokens.get(*position)?.clone();
+    *position += 1;
+    match token {
+        Token::Number(value) => Some(Expression::Number(value)),
+        Token::Identifier(name) if tokens.get(*position) == Some(&Token::LeftParenthesis) => {
+            *position += 1;
+            let mut arguments = vec![parse_sum(tokens, position)?];
+            while tokens.get(*position) == Some(&Token::Comma) {
+                *position += 1;
+                arguments.push(parse_sum(tokens, position)?);
+            }
+            (tokens.get(*position) == Some(&Token::RightParenthesis)).then(|| {
+                *position += 1;
+                Expression::Function(name, arguments)
+            })
+        }
+        Token::Identifier(name) => Some(Expression::Variable(name)),
+        Token::LeftParenthesis => {
+            let expression = parse_sum(tokens, position)?;
+            (tokens.get(*position) == Some(&Token::RightParenthesis)).then(|| {
+                *position += 1;
+                expression
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Formats the name of a variable as Typst math.
+///
+/// The part after the first underscore, or the trailing digits, is put in subscript and the names
+/// of Typst symbols, like `theta`, are kept to be displayed as symbols.
+///
+/// # Arguments
+///
+/// * `name` - The name of the variable.
+fn format_variable(name: &str) -> String {
+    let (base, subscript) = match name.split_once('_') {
+        Some((base, subscript)) if !base.is_empty() && !subscript.is_empty() => (base, subscript),
+        _ => {
+            let base = name.trim_end_matches(|character: char| character.is_ascii_digit());
+            (base, &name[base.len()..])
+        }
+    };
+    let base = if base.len() == 1
+        && base
+            .chars()
+            .all(|character| character.is_ascii_alphabetic())
+    {
+        base.to_owned()
+    } else {
+        format_symbol_str(base)
+    };
+    match subscript {
+        "" => base,
+        subscript
+            if subscript
+                .chars()
+                .all(|character| character.is_ascii_digit()) =>
+        {
+            format!("{base}_{subscript}")
+        }
+        subscript => {
+            let subscript = format_variable(subscript);
+            if subscript.contains('_') {
+                format!("{base}_({subscript})")
+            } else {
+                format!("{base}_{subscript}")
+            }
+        }
+    }
+}
+
+/// Formats an expression as Typst math, in parentheses if it binds less tightly than required.
+///
+/// # Arguments
+///
+/// * `expression` - The expression to format.
+/// * `minimum` - The precedence the expression must have to be written without parentheses.
+/// * `number_format` - How the numbers are displayed.
+fn grouped(expression: &Expression, minimum: Precedence, number_format: NumberFormat) -> String {
+    let (typst_str, precedence) = expression_to_typst(expression, number_format);
+    if precedence < minimum {
+        format!("({typst_str})")
+    } else {
+        typst_str
+    }
+}
+
+/// Formats the exponent of a power as a Typst attachment.
+///
+/// # Arguments
+///
+/// * `exponent` - The exponent.
+/// * `number_format` - How the numbers are displayed.
+fn superscript(exponent: &Expression, number_format: NumberFormat) -> String {
+    let (typst_str, _) = expression_to_typst(exponent, number_format);
+    if typst_str
+        .chars()
+        .all(|character| character.is_alphanumeric() || character == '.')
+    {
+        format!("^{typst_str}")
+    } else {
+        format!("^({typst_str})")
+    }
+}
+
+/// Formats a function call as Typst math.
+///
+/// The functions known by Typst math are written with their operator, the exponentials as
+/// powers and the other functions with their name as upright text.
+///
+/// # Arguments
+///
+/// * `name` - The name of the function.
+/// * `arguments` - The arguments of the function.
+/// * `number_format` - How the numbers are displayed.
+fn function_to_typst(
+    name: &str,
+    arguments: &[Expression],
+    number_format: NumberFormat,
+) -> (String, Precedence) {
+    let joined = arguments
+        .iter()
+        .map(|argument| expression_to_typst(argument, number_format).0)
+        .collect::<Vec<String>>()
+        .join(", ");
+    let power = |base: Expression, exponent: &Expression| {
+        expression_to_typst(
+            &Expression::Binary(Operator::Power, Box::new(base), Box::new(exponent.clone())),
+            number_format,
+        )
+    };
+    match (name, arguments) {
+        ("exp", [argument]) => (
+            format!("e{}", superscript(argument, number_format)),
+            Precedence::Power,
+        ),
+        ("exp2", [argument]) => power(Expression::Number(2.0), argument),
+        ("expm1", [argument]) => (
+            format!("e{} - 1", superscript(argument, number_format)),
+            Precedence::Sum,
+        ),
+        ("pow" | "powf", [base, exponent]) => power(base.clone(), exponent),
+        ("hypot", [first, second]) => (
+            format!(
+                "sqrt({}^2 + {}^2)",
+                grouped(first, Precedence::Atom, number_format),
+                grouped(second, Precedence::Atom, number_format)
+            ),
+            Precedence::Atom,
+        ),
+        ("cbrt", [_]) => (format!("root(3, {joined})"), Precedence::Atom),
+        ("asin" | "acos" | "atan", [_]) => {
+            (format!("arc{}({joined})", &name[1..]), Precedence::Atom)
+        }
+        ("log10", [_]) => (format!("log_10({joined})"), Precedence::Atom),
+        ("log2", [_]) => (format!("log_2({joined})"), Precedence::Atom),
+        ("ln_1p" | "log1p", [_]) => (format!("ln(1 + {joined})"), Precedence::Atom),
+        (
+            "sqrt" | "abs" | "floor" | "ceil" | "sin" | "cos" | "tan" | "sinh" | "cosh" | "tanh"
+            | "arcsin" | "arccos" | "arctan" | "ln" | "log" | "max" | "min",
+            _,
+        ) => (format!("{name}({joined})"), Precedence::Atom),
+        _ => (format!("\"{name}\"({joined})"), Precedence::Atom),
+    }
+}
+
+/// Formats the difference of two expressions as Typst math.
+///
+/// # Arguments
+///
+/// * `left` - The expression subtracted from.
+/// * `right` - The subtracted expression.
+/// * `number_format` - How the numbers are displayed.
+fn difference_to_typst(
+    left: &Expression,
+    right: &Expression,
+    number_format: NumberFormat,
+) -> (String, Precedence) {
+    (
+        format!(
+            "{} - {}",
+            grouped(left, Precedence::Sum, number_format),
+            grouped(right, Precedence::Product, number_format)
+        ),
+        Precedence::Sum,
+    )
+}
+
+/// Formats an expression as Typst math.
+///
+/// # Arguments
+///
+/// * `expression` - The expression to format.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `(String, Precedence)` - The Typst math representation and how tightly it binds.
+fn expression_to_typst(
+    expression: &Expression,
+    number_format: NumberFormat,
+) -> (String, Precedence) {
+    match expression {
+        Expression::Number(value) => {
+            // The integer coefficients are written without decimals.
+            let typst_str = if value.fract() == 0.0 && *value < 1000.0 {
+                format!("{value}")
+            } else {
+                format_calculator(&CalculatorFloat::Float(*value), number_format)
+            };
+            // Fractions and products with a coefficient, like `(3pi)/4` or `2sqrt(3)`.
+            let compound = typst_str.contains(['/', ' '])
+                || (typst_str.starts_with(|character: char| character.is_ascii_digit())
+                    && typst_str.contains(|character: char| character.is_alphabetic()));
+            let precedence = if compound {
+                Precedence::Product
+            } else {
+                Precedence::Atom
+            };
+            (typst_str, precedence)
+        }
+        Expression::Variable(name) => (format_variable(name), Precedence::Atom),
+        Expression::Negation(operand) => (
+            format!("-{}", grouped(operand, Precedence::Product, number_format)),
+            Precedence::Negation,
+        ),
+        Expression::Binary(operator, left, right) => {
+            match (operator, left.as_ref(), right.as_ref()) {
+                (Operator::Add, left, Expression::Negation(right)) => {
+                    difference_to_typst(left, right, number_format)
+                }
+                (Operator::Subtract, left, right) => {
+                    difference_to_typst(left, right, number_format)
+                }
+                (Operator::Add, left, right) => (
+                    format!(
+                        "{} + {}",
+                        grouped(left, Precedence::Sum, number_format),
+                        grouped(right, Precedence::Product, number_format)
+                    ),
+                    Precedence::Sum,
+                ),
+                (Operator::Multiply, Expression::Number(one), other)
+                | (Operator::Multiply, other, Expression::Number(one))
+                    if *one == 1.0 =>
+                {
+                    expression_to_typst(other, number_format)
+                }
+                (Operator::Multiply, Expression::Negation(negated), other)
+                    if **negated == Expression::Number(1.0) =>
+                {
+                    expression_to_typst(
+                        &Expression::Negation(Box::new(other.clone())),
+                        number_format,
+                    )
+                }
+                (Operator::Multiply, left, right) => {
+                    // The numbers are written first, as coefficients.
+                    let (left, right) = match (left, right) {
+                        (
+                            Expression::Variable(_)
+                            | Expression::Function(..)
+                            | Expression::Binary(Operator::Power, ..),
+                            Expression::Number(_),
+                        ) => (right, left),
+                        _ => (left, right),
+                    };
+                    let (left_str, left_precedence) = expression_to_typst(left, number_format);
+                    let left_str = if left_precedence < Precedence::Negation {
+                        format!("({left_str})")
+                    } else {
+                        left_str
+                    };
+                    let right_str = grouped(right, Precedence::Product, number_format);
+                    let separator =
+                        if right_str.starts_with(|character: char| character.is_ascii_digit()) {
+                            " dot "
+                        } else {
+                            " "
+                        };
+                    let precedence = if left_precedence == Precedence::Negation {
+                        Precedence::Negation
+                    } else {
+                        Precedence::Product
+                    };
+                    (format!("{left_str}{separator}{right_str}"), precedence)
+                }
+                (Operator::Divide, Expression::Negation(numerator), denominator) => (
+                    format!(
+                        "-{}/{}",
+                        grouped(numerator, Precedence::Power, number_format),
+                        grouped(denominator, Precedence::Power, number_format)
+                    ),
+                    Precedence::Negation,
+                ),
+                (Operator::Divide, left, right) => (
+                    format!(
+                        "{}/{}",
+                        grouped(left, Precedence::Power, number_format),
+                        grouped(right, Precedence::Power, number_format)
+                    ),
+                    Precedence::Product,
+                ),
+                (Operator::Power, base, exponent) => (
+                    format!(
+                        "{}{}",
+                        grouped(base, Precedence::Atom, number_format),
+                        superscript(exponent, number_format)
+                    ),
+                    Precedence::Power,
+                ),
+            }
+        }
+        Expression::Function(name, arguments) => function_to_typst(name, arguments, number_format),
+    }
+}
+
+/// Formats an expression as LaTeX math, in parentheses if it binds less tightly than required.
+///
+/// # Arguments
+///
+/// * `expression` - The expression to format.
+/// * `minimum` - The precedence the expression must have to be written without parentheses.
+/// * `number_format` - How the numbers are displayed.
+fn grouped_latex(
+    expression: &Expression,
+    minimum: Precedence,
+    number_format: NumberFormat,
+) -> String {
+    let (latex_str, precedence) = expression_to_latex(expression, number_format);
+    if precedence < minimum {
+        format!("({latex_str})")
+    } else {
+        latex_str
+    }
+}
+
+/// Formats a function call as LaTeX math, in the same way as `function_to_typst`.
+///
+/// # Arguments
+///
+/// * `name` - The name of the function.
+/// * `arguments` - The arguments of the function.
+/// * `number_format` - How the numbers are displayed.
+fn function_to_latex(
+    name: &str,
+    arguments: &[Expression],
+    number_format: NumberFormat,
+) -> (String, Precedence) {
+    let joined = arguments
+        .iter()
+        .map(|argument| expression_to_latex(argument, number_format).0)
+        .collect::<Vec<String>>()
+        .join(", ");
+    let power = |base: Expression, exponent: &Expression| {
+        expression_to_latex(
+            &Expression::Binary(Operator::Power, Box::new(base), Box::new(exponent.clone())),
+            number_format,
+        )
+    };
+    match (name, arguments) {
+        ("exp", [argument]) => (
+            format!("e^{{{}}}", expression_to_latex(argument, number_format).0),
+            Precedence::Power,
+        ),
+        ("exp2", [argument]) => power(Expression::Number(2.0), argument),
+        ("expm1", [argument]) => (
+            format!(
+                "e^{{{}}} - 1",
+                expression_to_latex(argument, number_format).0
+            ),
+            Precedence::Sum,
+        ),
+        ("pow" | "powf", [base, exponent]) => power(base.clone(), exponent),
+        ("hypot", [first, second]) => (
+            format!(
+                "\\sqrt{{{}^{{2}} + {}^{{2}}}}",
+                grouped_latex(first, Precedence::Atom, number_format),
+                grouped_latex(second, Precedence::Atom, number_format)
+            ),
+            Precedence::Atom,
+        ),
+        ("sqrt", [_]) => (format!("\\sqrt{{{joined}}}"), Precedence::Atom),
+        ("cbrt", [_]) => (format!("\\sqrt[3]{{{joined}}}"), Precedence::Atom),
+        ("abs", [_]) => (format!("|{joined}|"), Precedence::Atom),
+        ("floor", [_]) => (format!("\\lfloor {joined} \\rfloor"), Precedence::Atom),
+        ("ceil", [_]) => (format!("\\lceil {joined} \\rceil"), Precedence::Atom),
+        ("asin" | "acos" | "atan", [_]) => {
+            (format!("\\arc{}({joined})", &name[1..]), Precedence::Atom)
+        }
+        ("log10", [_]) => (format!("\\log_{{10}}({joined})"), Precedence::Atom),
+        ("log2", [_]) => (format!("\\log_{{2}}({joined})"), Precedence::Atom),
+        ("ln_1p" | "log1p", [_]) => (format!("\\ln(1 + {joined})"), Precedence::Atom),
+        (
+            "sin" | "cos" | "tan" | "sinh" | "cosh" | "tanh" | "arcsin" | "arccos" | "arctan"
+            | "ln" | "log" | "max" | "min",
+            _,
+        ) => (format!("\\{name}({joined})"), Precedence::Atom),
+        _ => (format!("\\mathrm{{{name}}}({joined})"), Precedence::Atom),
+    }
+}
+
+/// Formats the difference of two expressions as LaTeX math.
+///
+/// # Arguments
+///
+/// * `left` - The expression subtracted from.
+/// * `right` - The subtracted expression.
+/// * `number_format` - How the numbers are displayed.
+fn difference_to_latex(
+    left: &Expression,
+    right: &Expression,
+    number_format: NumberFormat,
+) -> (String, Precedence) {
+    (
+        format!(
+            "{} - {}",
+            grouped_latex(left, Precedence::Sum, number_format),
+            grouped_latex(right, Precedence::Product, number_format)
+        ),
+        Precedence::Sum,
+    )
+}
+
+/// Formats an expression as LaTeX math, in the same way as `expression_to_typst`.
+///
+/// The divisions are written as `\frac`, so their operands are never put in parentheses.
+///
+/// # Arguments
+///
+/// * `expression` - The expression to format.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `(String, Precedence)` - The LaTeX math representation and how tightly it binds.
+fn expression_to_latex(
+    expression: &Expression,
+    number_format: NumberFormat,
+) -> (String, Precedence) {
+    match expression {
+        Expression::Number(_) | Expression::Variable(_) => {
+            // The Typst math of the numbers and the variables only needs its symbols to be
+            // replaced, their fractions being already written in LaTeX.
+            let (typst_str, precedence) = expression_to_typst(expression, number_format);
+            let latex_str = typst_math_to_latex(&typst_str);
+            let precedence = if latex_str.contains("\\frac") {
+                precedence.min(Precedence::Product)
+            } else {
+                precedence
+            };
+            (latex_str, precedence)
+        }
+        Expression::Negation(operand) => (
+            format!(
+                "-{}",
+                grouped_latex(operand, Precedence::Product, number_format)
+            ),
+            Precedence::Negation,
+        ),
+        Expression::Binary(operator, left, right) => {
+            match (operator, left.as_ref(), right.as_ref()) {
+                (Operator::Add, left, Expression::Negation(right)) => {
+                    difference_to_latex(left, right, number_format)
+                }
+                (Operator::Subtract, left, right) => {
+                    difference_to_latex(left, right, number_format)
+                }
+                (Operator::Add, left, right) => (
+                    format!(
+                        "{} + {}",
+                        grouped_latex(left, Precedence::Sum, number_format),
+                        grouped_latex(right, Precedence::Product, number_format)
+                    ),
+                    Precedence::Sum,
+                ),
+                (Operator::Multiply, Expression::Number(one), other)
+                | (Operator::Multiply, other, Expression::Number(one))
+                    if *one == 1.0 =>
+                {
+                    expression_to_latex(other, number_format)
+                }
+                (Operator::Multiply, Expression::Negation(negated), other)
+                    if **negated == Expression::Number(1.0) =>
+                {
+                    expression_to_latex(
+                        &Expression::Negation(Box::new(other.clone())),
+                        number_format,
+                    )
+                }
+                (Operator::Multiply, left, right) => {
+                    // The numbers are written first, as coefficients.
+                    let (left, right) = match (left, right) {
+                        (
+                            Expression::Variable(_)
+                            | Expression::Function(..)
+                            | Expression::Binary(Operator::Power, ..),
+                            Expression::Number(_),
+                        ) => (right, left),
+                        _ => (left, right),
+                    };
+                    let (left_str, left_precedence) = expression_to_latex(left, number_format);
+                    let left_str = if left_precedence < Precedence::Negation {
+                        format!("({left_str})")
+                    } else {
+                        left_str
+                    };
+                    let right_str = grouped_latex(right, Precedence::Product, number_format);
+                    let separator =
+                        if right_str.starts_with(|character: char| character.is_ascii_digit()) {
+                            " \\cdot "
+                        } else {
+                            " "
+                        };
+                    let precedence = if left_precedence == Precedence::Negation {
+                        Precedence::Negation
+                    } else {
+                        Precedence::Product
+                    };
+                    (format!("{left_str}{separator}{right_str}"), precedence)
+                }
+                (Operator::Divide, Expression::Negation(numerator), denominator) => (
+                    format!(
+                        "-\\frac{{{}}}{{{}}}",
+                        expression_to_latex(numerator, number_format).0,
+                        expression_to_latex(denominator, number_format).0
+                    ),
+                    Precedence::Negation,
+                ),
+                (Operator::Divide, left, right) => (
+                    format!(
+                        "\\frac{{{}}}{{{}}}",
+                        expression_to_latex(left, number_format).0,
+                        expression_to_latex(right, number_format).0
+                    ),
+                    Precedence::Product,
+                ),
+                (Operator::Power, base, exponent) => (
+                    format!(
+                        "{}^{{{}}}",
+                        grouped_latex(base, Precedence::Atom, number_format),
+                        expression_to_latex(exponent, number_format).0
+                    ),
+                    Precedence::Power,
+                ),
+            }
+        }
+        Expression::Function(name, arguments) => function_to_latex(name, arguments, number_format),
+    }
+}
+
+/// Formats a symbolic qoqo_calculator expression as Typst math.
+///
+/// The expression is parsed and written as it would be in a paper: divisions as fractions,
+/// powers and exponentials as superscripts, products by juxtaposition and known functions with
+/// their operator.
+///
+/// # Arguments
+///
+/// * `expression` - The symbolic expression.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `Option<String>` - The Typst math representation, None if the expression can't be parsed.
+///   In the cells converted to LaTeX, the LaTeX math of the expression wrapped by `latex_math`.
+pub(crate) fn format_expression(expression: &str, number_format: NumberFormat) -> Option<String> {
+    let tokens = tokenize(expression)?;
+    let mut position = 0;
+    let parsed = parse_sum(&tokens, &mut position)?;
+    (position == tokens.len()).then(|| {
+        if latex_math_enabled() {
+            latex_math(&expression_to_latex(&parsed, number_format).0)
+        } else {
+            expression_to_typst(&parsed, number_format).0
+        }
+    })
+}
@@ -10,14 +10,16 @@
 // express or implied. See the License for the specific language governing permissions and
 // limitations under the License.
 
+use ndarray::Array2;
 use num_complex::Complex64;
 use qoqo_calculator::CalculatorFloat;
-use roqoqo::{operations::*, RoqoqoBackendError, RoqoqoError};
+use roqoqo::operations::*;
 use typst::foundations::Value::Symbol;
 
-use crate::RenderPragmas;
-
-const EPSILON: f64 = 1e-6;
+use crate::{
+    expression::format_expression, format_angle_float, format_complex, format_float, Cell,
+    ComplexStyle, NumberFormat, RenderPragmas, RoqollageError, Wire, EPSILON,
+};
 
 // Operations that are ignored by backend and do not throw an error.
 const ALLOWED_OPERATIONS: &[&str; 3] = &["DefinitionFloat", "DefinitionComplex", "DefinitionUsize"];
@@ -28,34 +30,24 @@ const ALLOWED_OPERATIONS: &[&str; 3] = &["DefinitionFloat", "DefinitionComplex",
 ///
 /// * `circuit_gates` - A vector of all the gates vectors of the circuit.
 /// * `qubits` - A vector of the qubits to represent.
-fn add_qubits_vec(circuit_gates: &mut Vec<Vec<String>>, qubits: &[usize]) {
+fn add_qubits_vec(circuit_gates: &mut Vec<Vec<Cell>>, qubits: &[usize]) {
     while &circuit_gates.len() <= qubits.iter().max().unwrap_or(&0) {
         circuit_gates.push(Vec::new());
     }
 }
 
-/// Calculates the length on the image since some gate are not represented in typst
+/// Calculates the length on the image since some cells are drawn on top of the others
 /// and therefore are not taking any space.
 ///
 /// # Arguments
 ///
-/// * `gates` - A vector of gates in typst representation.
+/// * `gates` - A vector of cells of a wire.
 ///
 /// # Returns
 ///
 /// * `usize` - The total length the gates will take on the image.
-pub(crate) fn effective_len(gates: &[String]) -> usize {
-    gates.len()
-        - gates
-            .iter()
-            .filter(|gate| {
-                gate.contains("slice")
-                    || gate.contains("gategroup")
-                    || gate.contains("lstick")
-                    || gate.contains("setwire")
-            })
-            .collect::<Vec<&String>>()
-            .len()
+pub(crate) fn effective_len(gates: &[Cell]) -> usize {
+    gates.iter().filter(|gate| gate.takes_column()).count()
 }
 
 /// Flattens the length of the gates vector for certain qubits in the circuit.
@@ -65,7 +57,7 @@ pub(crate) fn effective_len(gates: &[String]) -> usize {
 ///
 /// * `circuit_gates` - A vector of all the gates vectors of the circuit.
 /// * `qubits` - A vector of the qubits to flatten.
-fn flatten_qubits(circuit_gates: &mut [Vec<String>], qubits: &[usize]) {
+fn flatten_qubits(circuit_gates: &mut [Vec<Cell>], qubits: &[usize]) {
     let max_len = qubits
         .iter()
         .map(|&qubit| effective_len(&circuit_gates[qubit]))
@@ -78,7 +70,7 @@ fn flatten_qubits(circuit_gates: &mut [Vec<String>], qubits: &[usize]) {
     {
         for &qubit in qubits.iter() {
             if effective_len(&circuit_gates[qubit]) < max_len {
-                circuit_gates[qubit].push("1".to_owned());
+                circuit_gates[qubit].push(Cell::Idle);
             }
         }
     }
@@ -91,9 +83,9 @@ fn flatten_qubits(circuit_gates: &mut [Vec<String>], qubits: &[usize]) {
 /// * `circuit_gates` - A vector of all the gates vectors of the circuit.
 /// * `min` - The minimum index of the circuit
 /// * `max` - The maximum index of the circuit
-fn push_ones(circuit_gates: &mut [Vec<String>], min: usize, max: usize) {
+fn push_ones(circuit_gates: &mut [Vec<Cell>], min: usize, max: usize) {
     for gates in circuit_gates.iter_mut().take(max + 1).skip(min + 1) {
-        gates.push("1".to_owned());
+        gates.push(Cell::Idle);
     }
 }
 
@@ -107,8 +99,8 @@ fn push_ones(circuit_gates: &mut [Vec<String>], min: usize, max: usize) {
 /// * `vec_1_ind` - A vector of the indexes to flatten.
 /// * `vec_2_ind` - A vector of the indexes to flatten.
 pub(crate) fn flatten_multiple_vec(
-    gate_vec_1: &mut [Vec<String>],
-    gate_vec_2: &mut [Vec<String>],
+    gate_vec_1: &mut [Vec<Cell>],
+    gate_vec_2: &mut [Vec<Cell>],
     vec_1_ind: &[usize],
     vec_2_ind: &[usize],
 ) {
@@ -134,12 +126,12 @@ pub(crate) fn flatten_multiple_vec(
     {
         for &index in vec_1_ind.iter() {
             if effective_len(&gate_vec_1[index]) < max_len {
-                gate_vec_1[index].push("1".to_owned());
+                gate_vec_1[index].push(Cell::Idle);
             }
         }
         for &boson in vec_2_ind.iter() {
             if effective_len(&gate_vec_2[boson]) < max_len {
-                gate_vec_2[boson].push("1".to_owned());
+                gate_vec_2[boson].push(Cell::Idle);
             }
         }
     }
@@ -155,7 +147,7 @@ pub(crate) fn flatten_multiple_vec(
 /// # Returns
 ///
 /// * `String` The formatted string.
-fn format_symbol_str(str_value: &str) -> String {
+pub(crate) fn format_symbol_str(str_value: &str) -> String {
     let (main_variant, sup) = str_value.split_once('.').unwrap_or((str_value, ""));
     let library = typst::Library::default();
     let math = library.math.scope();
@@ -172,18 +164,11 @@ fn format_symbol_str(str_value: &str) -> String {
     }
 }
 
-fn count_digits(num: f64) -> usize {
-    let num_str: String = num
-        .to_string()
-        .chars()
-        .skip_while(|&c| c == '.' || c == '0' || c == '-')
-        .collect();
-    let count = num_str.chars().filter(|&c| c != '.').count();
-    count
-}
-
 /// Formats a calculatorFloat to be displayed in a typst representation.
 ///
+/// The symbolic expressions are parsed and written as Typst math, the ones that can't be parsed
+/// only have their unknown identifiers quoted.
+///
 /// # Arguments
 ///
 /// * `calculator` - The CalculatorFloat to be formatted.
@@ -191,40 +176,16 @@ fn count_digits(num: f64) -> usize {
 /// # Returns
 ///
 /// * `String` The calculator's typst representation.
-fn format_calculator(calculator: &CalculatorFloat, rounding_accuracy: usize) -> String {
+pub(crate) fn format_calculator(
+    calculator: &CalculatorFloat,
+    number_format: NumberFormat,
+) -> String {
     match calculator {
-        CalculatorFloat::Float(float_value) => match float_value {
-            v if (v - std::f64::consts::PI).abs() < EPSILON => "pi".to_owned(),
-            v if (v + std::f64::consts::PI).abs() < EPSILON => "-pi".to_owned(),
-            v if (v - std::f64::consts::FRAC_PI_2).abs() < EPSILON => "pi/2".to_owned(),
-            v if (v + std::f64::consts::FRAC_PI_2).abs() < EPSILON => "-pi/2".to_owned(),
-            v if (v - 3.0 * std::f64::consts::FRAC_PI_2).abs() < EPSILON => "(3pi)/2".to_owned(),
-            v if (v + 3.0 * std::f64::consts::FRAC_PI_2).abs() < EPSILON => "-(3pi)/2".to_owned(),
-            v if (v - std::f64::consts::FRAC_PI_3).abs() < EPSILON => "pi/3".to_owned(),
-            v if (v + std::f64::consts::FRAC_PI_3).abs() < EPSILON => "-pi/3".to_owned(),
-            v if (v - 2.0 * std::f64::consts::FRAC_PI_3).abs() < EPSILON => "(2pi)/3".to_owned(),
-            v if (v + 2.0 * std::f64::consts::FRAC_PI_3).abs() < EPSILON => "-(2pi)/3".to_owned(),
-            v if (v - std::f64::consts::FRAC_PI_4).abs() < EPSILON => "pi/4".to_owned(),
-            v if (v + std::f64::consts::FRAC_PI_4).abs() < EPSILON => "-pi/4".to_owned(),
-            v if (v - 3.0 * std::f64::consts::FRAC_PI_4).abs() < EPSILON => "(3pi)/4".to_owned(),
-            v if (v + 3.0 * std::f64::consts::FRAC_PI_4).abs() < EPSILON => "-(3pi)/4".to_owned(),
-            v if (v - std::f64::consts::SQRT_2).abs() < EPSILON => "sqrt(2)".to_owned(),
-            v if (v + std::f64::consts::SQRT_2).abs() < EPSILON => "-sqrt(2)".to_owned(),
-            v if (v - std::f64::consts::FRAC_1_SQRT_2).abs() < EPSILON => "1/sqrt(2)".to_owned(),
-            v if (v + std::f64::consts::FRAC_1_SQRT_2).abs() < EPSILON => "-1/sqrt(2)".to_owned(),
-            v if v.abs() <= 0.005 || v.abs() >= 1000. => match count_digits(*v) {
-                0 => "0".to_owned(),
-                1 => format!("\"{v:e}\""),
-                2 => format!("\"{v:.1e}\""),
-                _ => format!("\"{v:.2e}\""),
-            },
-            _ => {
-                let n_digits = count_digits(*float_value);
-                let acc = rounding_accuracy.min(n_digits);
-                format!("{float_value:.acc$}")
-            }
-        },
+        CalculatorFloat::Float(float_value) => format_float(*float_value, number_format),
         CalculatorFloat::Str(str_value) => {
+            if let Some(typst_str) = format_expression(str_value, number_format) {
+                return typst_str;
+            }
             let mut value = str_value.as_str();
             if str_value.ends_with(')') && str_value.starts_with('(') {
                 let mut remove_bracket = 1;
@@ -250,35 +211,192 @@ fn format_calculator(calculator: &CalculatorFloat, rounding_accuracy: usize) ->
     }
 }
 
+/// Formats a rotation angle to be displayed in a typst representation.
+///
+/// The float angles are converted to the angle unit of the number format, the symbolic ones are
+/// kept as they are.
+///
+/// # Arguments
+///
+/// * `calculator` - The angle, in radians.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `String` - The angle's typst representation.
+pub(crate) fn format_angle(calculator: &CalculatorFloat, number_format: NumberFormat) -> String {
+    match calculator {
+        CalculatorFloat::Float(float_value) => format_angle_float(*float_value, number_format),
+        CalculatorFloat::Str(_) => format_calculator(calculator, number_format),
+    }
+}
+
 /// Formats a complex value to be displayed in a typst representation.
 ///
 /// # Arguments
 ///
 /// * `value` - The complex value to be formatted
+/// * `number_format` - How the numbers are displayed, with the style of the complex numbers.
 ///
 /// # Returns
 ///
 /// * `String` - The complex's typst representation.
-fn format_complex_value(value: Complex64, rounding_accuracy: usize) -> String {
-    format!(
-        "{}+{}i",
-        format_calculator(&CalculatorFloat::Float(value.re), rounding_accuracy),
-        format_calculator(&CalculatorFloat::Float(value.im), rounding_accuracy)
-    )
+pub(crate) fn format_complex_value(value: Complex64, number_format: NumberFormat) -> String {
+    format_complex(value.re, value.im, number_format)
 }
 
-/// Formats a qubit value to be displayed as an input in a quill multi qubit gate.
+/// Formats a complex value given by its real and imaginary CalculatorFloat parts.
+///
+/// The symbolic parts are written in the cartesian form.
 ///
 /// # Arguments
 ///
-/// * `qubit` - The qubit acting on the gate.
-/// * `label` - The label to be displayed.
+/// * `re` - The real part of the complex value.
+/// * `im` - The imaginary part of the complex value.
+/// * `number_format` - How the numbers are displayed, with the style of the complex numbers.
 ///
 /// # Returns
 ///
-/// * `String` - The formatted input string.
-fn format_qubit_input(qubit: usize, label: &str) -> String {
-    format!(r#"{qubit}, label: "{label}""#)
+/// * `String` - The complex's typst representation.
+pub(crate) fn format_complex_calculator(
+    re: &CalculatorFloat,
+    im: &CalculatorFloat,
+    number_format: NumberFormat,
+) -> String {
+    match (re, im) {
+        (CalculatorFloat::Float(re), CalculatorFloat::Float(im)) => {
+            format_complex(*re, *im, number_format)
+        }
+        (re, CalculatorFloat::Float(im)) if im.abs() < EPSILON => {
+            format_calculator(re, number_format)
+        }
+        (re, im) => {
+            let imaginary = match im {
+                CalculatorFloat::Str(_) => format!("({}) i", format_calculator(im, number_format)),
+                CalculatorFloat::Float(im) => format_complex(
+                    0.0,
+                    *im,
+                    NumberFormat {
+                        complex_style: ComplexStyle::Cartesian,
+                        ..number_format
+                    },
+                ),
+            };
+            match re {
+                CalculatorFloat::Float(re) if re.abs() < EPSILON => imaginary,
+                re => match imaginary.strip_prefix('-') {
+                    Some(negated) => {
+                        format!("{} - {negated}", format_calculator(re, number_format))
+                    }
+                    None => format!("{} + {imaginary}", format_calculator(re, number_format)),
+                },
+            }
+        }
+    }
+}
+
+/// Formats an entry of a state vector or of a density matrix, in parentheses if it is a sum.
+///
+/// # Arguments
+///
+/// * `value` - The complex entry.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `String` - The entry's typst representation.
+fn format_state_entry(value: Complex64, number_format: NumberFormat) -> String {
+    let entry = format_complex_value(value, number_format);
+    if entry.contains(" + ") || entry.contains(" - ") {
+        format!("({entry})")
+    } else {
+        entry
+    }
+}
+
+/// Formats a state vector as a sum of basis kets, without the zero amplitudes.
+///
+/// The basis kets are written with the highest qubit first, e.g. `|01>` for qubit 0 in state 1.
+///
+/// # Arguments
+///
+/// * `statevector` - The amplitudes of the basis states.
+/// * `number_format` - How the numbers are displayed, with the number of kets displayed before an
+///   ellipsis.
+///
+/// # Returns
+///
+/// * `String` - The state vector's typst representation.
+fn format_statevector(statevector: &[Complex64], number_format: NumberFormat) -> String {
+    let n_qubits = (statevector.len().max(2) - 1).ilog2() as usize + 1;
+    let mut terms = statevector
+        .iter()
+        .enumerate()
+        .filter(|(_, amplitude)| amplitude.norm() >= EPSILON)
+        .map(|(index, &amplitude)| {
+            let ket = format!("|{index:0n_qubits$b}>");
+            if (amplitude.re - 1.0).abs() < EPSILON && amplitude.im.abs() < EPSILON {
+                ket
+            } else if (amplitude.re + 1.0).abs() < EPSILON && amplitude.im.abs() < EPSILON {
+                format!("-{ket}")
+            } else {
+                format!("{} {ket}", format_state_entry(amplitude, number_format))
+            }
+        });
+    let mut sum = terms.next().unwrap_or_else(|| "0".to_owned());
+    for term in terms.by_ref().take(number_format.max_state_size.max(1) - 1) {
+        match term.strip_prefix('-') {
+            Some(negated) => sum.push_str(&format!(" - {negated}")),
+            None => sum.push_str(&format!(" + {term}")),
+        }
+    }
+    if terms.next().is_some() {
+        sum.push_str(" + dots");
+    }
+    sum
+}
+
+/// Formats a density matrix as a Typst matrix.
+///
+/// The rows and columns after the maximum state size are replaced by an ellipsis.
+///
+/// # Arguments
+///
+/// * `density_matrix` - The density matrix.
+/// * `number_format` - How the numbers are displayed, with the number of rows and columns displayed
+///   before an ellipsis.
+///
+/// # Returns
+///
+/// * `String` - The density matrix's typst representation.
+fn format_density_matrix(
+    density_matrix: &Array2<Complex64>,
+    number_format: NumberFormat,
+) -> String {
+    let max_size = number_format.max_state_size.max(1);
+    let truncated = density_matrix.nrows() > max_size || density_matrix.ncols() > max_size;
+    let mut rows: Vec<String> = density_matrix
+        .rows()
+        .into_iter()
+        .take(max_size)
+        .map(|row| {
+            let mut entries: Vec<String> = row
+                .iter()
+                .take(max_size)
+                .map(|&entry| format_state_entry(entry, number_format))
+                .collect();
+            if truncated {
+                entries.push("dots.h".to_owned());
+            }
+            entries.join(", ")
+        })
+        .collect();
+    if truncated {
+        let mut last_row = vec!["dots.v"; density_matrix.ncols().min(max_size)];
+        last_row.push("dots.down");
+        rows.push(last_row.join(", "));
+    }
+    format!("mat({})", rows.join("; "))
 }
 
 /// Prepares the circuit for a slice gate.
@@ -286,32 +404,31 @@ fn format_qubit_input(qubit: usize, label: &str) -> String {
 /// # Arguments
 ///
 /// * `circuit_gates` - A vector of all the gates vectors of the circuit.
-fn prepare_for_slice(circuit_gates: &mut Vec<Vec<String>>, circuit_lock: &mut Vec<(usize, usize)>) {
+fn prepare_for_slice(circuit_gates: &mut Vec<Vec<Cell>>, circuit_lock: &mut Vec<(usize, usize)>) {
     add_qubits_vec(circuit_gates, &[0]);
     if circuit_gates[0].is_empty() {
-        circuit_gates[0].push("1".to_owned());
+        circuit_gates[0].push(Cell::Idle);
         for qubit in 1..10 {
             circuit_lock.push((qubit, 0))
         }
     } else {
-        let last_slice = circuit_gates[0]
-            .iter()
-            .rfind(|gate| gate.contains("slice") || gate.contains("gategroup"));
+        let last_slice = circuit_gates[0].iter().rfind(|gate| {
+            matches!(
+                gate.unhighlighted(),
+                Cell::Slice { .. } | Cell::Group { .. }
+            )
+        });
         if let Some(last_slice) = last_slice {
             let dist_to_max = circuit_gates
                 .iter()
-                .map(|gates: &Vec<String>| effective_len(gates) + 1)
+                .map(|gates: &Vec<Cell>| effective_len(gates) + 1)
                 .max()
                 .unwrap_or(0)
                 - effective_len(&circuit_gates[0])
-                + if last_slice.contains("gategroup") {
-                    last_slice.split(",").nth(1).unwrap_or_default()
-                } else {
-                    Default::default()
-                }
-                .trim()
-                .parse::<usize>()
-                .unwrap_or_default();
+                + match last_slice.unhighlighted() {
+                    Cell::Group { length, .. } => *length,
+                    _ => 0,
+                };
             let len_to_add = match circuit_gates[0]
                 .iter()
                 .rev()
@@ -326,7 +443,7 @@ fn prepare_for_slice(circuit_gates: &mut Vec<Vec<String>>, circuit_lock: &mut Ve
             };
 
             for _ in 0..(len_to_add + dist_to_max) {
-                circuit_gates[0].push("1".to_owned());
+                circuit_gates[0].push(Cell::Idle);
             }
         }
     }
@@ -339,7 +456,7 @@ fn prepare_for_slice(circuit_gates: &mut Vec<Vec<String>>, circuit_lock: &mut Ve
 /// * `circuit_gates` - A vector of all the gates vectors of the circuit.
 /// * `circuit_lock` - The list of all the emplacements of the circuit that are reserved for a control wire between two gates.
 fn prepare_for_ctrl(
-    circuit_gates: &mut Vec<Vec<String>>,
+    circuit_gates: &mut Vec<Vec<Cell>>,
     circuit_lock: &mut Vec<(usize, usize)>,
     qubits: &[usize],
 ) {
@@ -350,7 +467,7 @@ fn prepare_for_ctrl(
     for qubit in min + 1..max {
         while circuit_lock.contains(&(qubit, effective_len(&circuit_gates[qubit]))) {
             circuit_lock.retain(|&val| val != (qubit, effective_len(&circuit_gates[qubit])));
-            circuit_gates[qubit].push("1".to_owned());
+            circuit_gates[qubit].push(Cell::Idle);
         }
 
         if circuit_gates.len() > qubit
@@ -367,12 +484,12 @@ fn prepare_for_ctrl(
 
 fn prepare_for_bosonic(
     mode: usize,
-    bosonic_gates: &mut [Vec<String>],
+    bosonic_gates: &mut [Vec<Cell>],
     bosonic_lock: &mut Vec<(usize, usize)>,
 ) {
     while bosonic_lock.contains(&(mode, effective_len(&bosonic_gates[mode]))) {
         bosonic_lock.retain(|&val| val != (mode, effective_len(&bosonic_gates[mode])));
-        bosonic_gates[mode].push("1".to_owned());
+        bosonic_gates[mode].push(Cell::Idle);
     }
 }
 
@@ -386,6 +503,52 @@ fn qubit_range(qubits: &[usize]) -> usize {
     qubits.iter().max().unwrap() - qubits.iter().min().unwrap() + 1
 }
 
+/// Draws an operation not supported by the backend as a box labeled with its name.
+///
+/// The box spans the qubits the operation acts on. An operation acting on all the qubits, or on
+/// none of them, is drawn as a slice across the circuit instead.
+///
+/// # Arguments
+///
+/// * `circuit_gates` - A vector of all the gates vectors of the circuit.
+/// * `circuit_lock` - The list of all the emplacements of the circuit that are reserved for a control wire between two gates.
+/// * `operation` - The operation to draw.
+fn add_placeholder(
+    circuit_gates: &mut Vec<Vec<Cell>>,
+    circuit_lock: &mut Vec<(usize, usize)>,
+    operation: &Operation,
+) {
+    let label = format!("\"{}\"", operation.hqslang());
+    match operation.involved_qubits() {
+        InvolvedQubits::Set(qubits) if !qubits.is_empty() => {
+            let min = qubits.iter().min().unwrap().to_owned();
+            let max = qubits.iter().max().unwrap().to_owned();
+            let range: Vec<usize> = (min..max + 1).collect();
+            let mut inputs: Vec<usize> = qubits.iter().map(|qubit| qubit - min).collect();
+            inputs.sort();
+            add_qubits_vec(circuit_gates, &range);
+            flatten_qubits(circuit_gates, &range);
+            circuit_gates[min].push(Cell::MultiGate {
+                content: label,
+                n_wires: range.len(),
+                width: None,
+                fill: None,
+                inputs,
+            });
+            push_ones(circuit_gates, min, max);
+        }
+        _ => {
+            prepare_for_slice(circuit_gates, circuit_lock);
+            let n_qubits = circuit_gates.len();
+            flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
+            circuit_gates[0].push(Cell::Slice {
+                label,
+                dash: Some("dotted".to_owned()),
+            });
+        }
+    }
+}
+
 /// Adds a gate to the circuit's typst representation.
 ///
 /// # Arguments
@@ -397,23 +560,28 @@ fn qubit_range(qubits: &[usize]) -> usize {
 /// * `bosonic_lock` - The list of all the emplacements of the bosonic part of the circuit that are reserved for a control wire between two gates.
 /// * `classical_lock` - The list of all the emplacements of the classical part of the circuit that are reserved for a control wire between two gates.
 /// * `operation` - The operation to add to the circuit.
+/// * `render_pragmas` - Whether to render Pragma Operations or not.
+/// * `number_format` - How the numbers are displayed.
+/// * `warnings` - If set, the operations not supported are drawn as placeholders and a warning is
+///   added to this list for each of them instead of returning an error.
 ///
 /// # Returns
 ///
 /// * `Ok(())` - If the operation was successfully added to the circuit.
-/// * Err(RoqoqoBackendError) - Operation not supported.
+/// * Err(RoqollageError) - Operation not supported or not valid.
 #[allow(clippy::too_many_arguments)]
 pub fn add_gate(
-    circuit_gates: &mut Vec<Vec<String>>,
-    bosonic_gates: &mut Vec<Vec<String>>,
-    classical_gates: &mut Vec<Vec<String>>,
+    circuit_gates: &mut Vec<Vec<Cell>>,
+    bosonic_gates: &mut Vec<Vec<Cell>>,
+    classical_gates: &mut Vec<Vec<Cell>>,
     circuit_lock: &mut Vec<(usize, usize)>,
     bosonic_lock: &mut Vec<(usize, usize)>,
     classical_lock: &mut Vec<(usize, usize)>,
     operation: &Operation,
     render_pragmas: &RenderPragmas,
-    rounding_accuracy: usize,
-) -> Result<(), RoqoqoBackendError> {
+    number_format: NumberFormat,
+    mut warnings: Option<&mut Vec<String>>,
+) -> Result<(), RoqollageError> {
     match render_pragmas {
         RenderPragmas::All => (),
         RenderPragmas::None => {
@@ -452,174 +620,185 @@ pub fn add_gate(
     for qubit in used_qubits.iter() {
         while circuit_lock.contains(&(*qubit, effective_len(&circuit_gates[*qubit]))) {
             circuit_lock.retain(|&val| val != (*qubit, effective_len(&circuit_gates[*qubit])));
-            circuit_gates[*qubit].push("1".to_owned());
+            circuit_gates[*qubit].push(Cell::Idle);
         }
     }
     match operation {
         Operation::Hadamard(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ H $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("H"));
             Ok(())
         }
         Operation::CNOT(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push("targ()".to_owned());
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::Target);
 
             Ok(())
         }
         Operation::SingleQubitGate(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ U({}+{}i,{}+{}i,{}) $, label: \"SingleQubitGate\")",
-                format_calculator(&op.alpha_r(), rounding_accuracy),
-                format_calculator(&op.alpha_i(), rounding_accuracy),
-                format_calculator(&op.beta_r(), rounding_accuracy),
-                format_calculator(&op.beta_i(), rounding_accuracy),
-                format_calculator(&op.global_phase(), rounding_accuracy)
+            circuit_gates[*op.qubit()].push(Cell::labelled_gate(
+                format!(
+                    "U({},{},{})",
+                    format_complex_calculator(&op.alpha_r(), &op.alpha_i(), number_format),
+                    format_complex_calculator(&op.beta_r(), &op.beta_i(), number_format),
+                    format_angle(&op.global_phase(), number_format)
+                ),
+                "SingleQubitGate",
             ));
             Ok(())
         }
         Operation::RotateX(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"Rx\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::gate(format!(
+                "\"Rx\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::RotateY(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"Ry\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::gate(format!(
+                "\"Ry\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::RotateZ(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"Rz\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::gate(format!(
+                "\"Rz\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::PauliX(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ X $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("X"));
             Ok(())
         }
         Operation::PauliY(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ Y $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("Y"));
             Ok(())
         }
         Operation::PauliZ(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ Z $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("Z"));
             Ok(())
         }
         Operation::SqrtPauliX(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ sqrt(X) $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("sqrt(X)"));
             Ok(())
         }
         Operation::InvSqrtPauliX(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ sqrt(X)^(dagger) $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("sqrt(X)^(dagger)"));
             Ok(())
         }
         Operation::SGate(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ S $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("S"));
             Ok(())
         }
         Operation::TGate(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ T $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("T"));
             Ok(())
         }
         Operation::PhaseShiftState1(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"p1\"({}) $, label: \"PhaseShiftState1\")",
-                format_calculator(op.theta(), rounding_accuracy)
+            circuit_gates[*op.qubit()].push(Cell::labelled_gate(
+                format!("\"p1\"({})", format_angle(op.theta(), number_format)),
+                "PhaseShiftState1",
             ));
             Ok(())
         }
         Operation::PhaseShiftState0(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"p0\"({}) $, label: \"PhaseShiftState0\")",
-                format_calculator(op.theta(), rounding_accuracy)
+            circuit_gates[*op.qubit()].push(Cell::labelled_gate(
+                format!("\"p0\"({})", format_angle(op.theta(), number_format)),
+                "PhaseShiftState0",
             ));
             Ok(())
         }
         Operation::RotateAroundSphericalAxis(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"Rsph\"({},{},{}) $, label: \"RotateAroundSphericalAxis\")",
-                format_calculator(op.theta(), rounding_accuracy),
-                format_calculator(op.spherical_theta(), rounding_accuracy),
-                format_calculator(op.spherical_phi(), rounding_accuracy),
+            circuit_gates[*op.qubit()].push(Cell::labelled_gate(
+                format!(
+                    "\"Rsph\"({},{},{})",
+                    format_angle(op.theta(), number_format),
+                    format_angle(op.spherical_theta(), number_format),
+                    format_angle(op.spherical_phi(), number_format)
+                ),
+                "RotateAroundSphericalAxis",
             ));
             Ok(())
         }
         Operation::RotateXY(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"Rxy\"({},{}) $)",
-                format_calculator(op.theta(), rounding_accuracy),
-                format_calculator(op.phi(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::gate(format!(
+                "\"Rxy\"({},{})",
+                format_angle(op.theta(), number_format),
+                format_angle(op.phi(), number_format)
+            )));
             Ok(())
         }
         Operation::PragmaSetNumberOfMeasurements(op) => {
             prepare_for_slice(circuit_gates, circuit_lock);
             let n_qubits = circuit_gates.len();
             flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
-            circuit_gates[0].push(format!(
-                "slice(label: $ \"Measurements\"\\ \"n={}\" $)",
-                op.number_measurements(),
-            ));
+            circuit_gates[0].push(Cell::Slice {
+                label: format!("\"Measurements\"\\ \"n={}\"", op.number_measurements()),
+                dash: None,
+            });
             Ok(())
         }
         Operation::PragmaSetStateVector(op) => {
             prepare_for_slice(circuit_gates, circuit_lock);
             let n_qubits = circuit_gates.len();
             flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
-            circuit_gates[0].push(format!(
-                r#"slice(label: $ "SetStatevector"\ [{}] $, stroke: (paint: black, thickness: 1pt, dash: "solid"))"#,
-                op.statevector().iter().map(|&complex| format_complex_value(complex, rounding_accuracy)).collect::<Vec<String>>().join(","),
-            ));
+            circuit_gates[0].push(Cell::Slice {
+                label: format!(
+                    r#""SetStatevector"\ {}"#,
+                    format_statevector(&op.statevector().to_vec(), number_format)
+                ),
+                dash: Some("solid".to_owned()),
+            });
             Ok(())
         }
         Operation::PragmaSetDensityMatrix(op) => {
             prepare_for_slice(circuit_gates, circuit_lock);
             let n_qubits = circuit_gates.len();
             flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
-            circuit_gates[0].push(format!(
-                r#"slice(label: $ "SetDensityMatrix"\ "{}" $, stroke: (paint: black, thickness: 1pt, dash: "solid"))"#,
-                op.density_matrix(),
-            ));
+            circuit_gates[0].push(Cell::Slice {
+                label: format!(
+                    r#""SetDensityMatrix"\ {}"#,
+                    format_density_matrix(op.density_matrix(), number_format)
+                ),
+                dash: Some("solid".to_owned()),
+            });
             Ok(())
         }
         Operation::PragmaRepeatGate(op) => {
             prepare_for_slice(circuit_gates, circuit_lock);
             let n_qubits = circuit_gates.len();
             flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
-            circuit_gates[0].push(format!(
-                r#"slice(label: $ "RepeatNextGate"\ {}" times" $, stroke: (paint: black, thickness: 1pt, dash: "densely-dash-dotted"))"#,
-                op.repetition_coefficient(),
-            ));
+            circuit_gates[0].push(Cell::Slice {
+                label: format!(
+                    r#""RepeatNextGate"\ {}" times""#,
+                    op.repetition_coefficient()
+                ),
+                dash: Some("densely-dash-dotted".to_owned()),
+            });
             Ok(())
         }
         Operation::PragmaOverrotation(op) => {
             if op.qubits().is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -628,14 +807,18 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                r#"mqgate($ "Overrotation"\ ({},{})\ "\"{}\"" $, n: {}, width: 10em, fill: gray, inputs: ({}))"#,
-                format_calculator(&CalculatorFloat::Float(*op.amplitude()), rounding_accuracy),
-                format_calculator(&CalculatorFloat::Float(*op.variance()), rounding_accuracy),
-                op.gate_hqslang(),
-                qubits.len(),
-                op.qubits().iter().map(|qubit| format!("(qubit: {})", format_qubit_input(qubit - min, "x"))).collect::<Vec<String>>().join(",")
-            ));
+            circuit_gates[min].push(Cell::MultiGate {
+                content: format!(
+                    r#""Overrotation"\ ({},{})\ "\"{}\"""#,
+                    format_calculator(&CalculatorFloat::Float(*op.amplitude()), number_format),
+                    format_calculator(&CalculatorFloat::Float(*op.variance()), number_format),
+                    op.gate_hqslang()
+                ),
+                n_wires: qubits.len(),
+                width: Some("10em".to_owned()),
+                fill: Some("gray".to_owned()),
+                inputs: op.qubits().iter().map(|qubit| qubit - min).collect(),
+            });
             push_ones(circuit_gates, min, max);
             Ok(())
         }
@@ -643,15 +826,19 @@ pub fn add_gate(
             prepare_for_slice(circuit_gates, circuit_lock);
             let n_qubits = circuit_gates.len();
             flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
-            circuit_gates[0].push(format!(
-                r#"slice(label: $ "BoostNoise"\ n={} $)"#,
-                format_calculator(op.noise_coefficient(), rounding_accuracy),
-            ));
+            circuit_gates[0].push(Cell::Slice {
+                label: format!(
+                    r#""BoostNoise"\ n={}"#,
+                    format_calculator(op.noise_coefficient(), number_format)
+                ),
+                dash: None,
+            });
             Ok(())
         }
         Operation::PragmaStopParallelBlock(op) => {
             if op.qubits().is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -660,18 +847,23 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                r#"mqgate($ "StopParallelBlock"\ ({}) $, n: {}, width: 13em, fill: gray, inputs: ({}))"#,
-                format_calculator(op.execution_time(), rounding_accuracy),
-                qubits.len(),
-                op.qubits().iter().map(|qubit| format!("(qubit: {})", format_qubit_input(qubit - min, "x"))).collect::<Vec<String>>().join(",")
-            ));
+            circuit_gates[min].push(Cell::MultiGate {
+                content: format!(
+                    r#""StopParallelBlock"\ ({})"#,
+                    format_calculator(op.execution_time(), number_format)
+                ),
+                n_wires: qubits.len(),
+                width: Some("13em".to_owned()),
+                fill: Some("gray".to_owned()),
+                inputs: op.qubits().iter().map(|qubit| qubit - min).collect(),
+            });
             push_ones(circuit_gates, min, max);
             Ok(())
         }
         Operation::PragmaStartDecompositionBlock(op) => {
             if op.qubits().is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -680,18 +872,27 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                r#"mqgate($ "StartDecompositionBlock"\ "{}" $, n: {}, width: 14em, fill: gray, inputs: ({}))"#,
-                op.reordering_dictionary().iter().map(|(key, val)| format!("{key}:{val}")).collect::<Vec<String>>().join("\n"),
-                qubits.len(),
-                op.qubits().iter().map(|qubit| format!("(qubit: {})", format_qubit_input(qubit - min, "x"))).collect::<Vec<String>>().join(",")
-            ));
+            circuit_gates[min].push(Cell::MultiGate {
+                content: format!(
+                    r#""StartDecompositionBlock"\ "{}""#,
+                    op.reordering_dictionary()
+                        .iter()
+                        .map(|(key, val)| format!("{key}:{val}"))
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                ),
+                n_wires: qubits.len(),
+                width: Some("14em".to_owned()),
+                fill: Some("gray".to_owned()),
+                inputs: op.qubits().iter().map(|qubit| qubit - min).collect(),
+            });
             push_ones(circuit_gates, min, max);
             Ok(())
         }
         Operation::PragmaStopDecompositionBlock(op) => {
             if op.qubits().is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -700,11 +901,13 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                r#"mqgate($ "StopDecompositionBlock" $, n: {}, width: 13em, fill: gray, inputs: ({}))"#,
-                qubits.len(),
-                op.qubits().iter().map(|qubit| format!("(qubit: {})", format_qubit_input(qubit - min, "x"))).collect::<Vec<String>>().join(",")
-            ));
+            circuit_gates[min].push(Cell::MultiGate {
+                content: r#""StopDecompositionBlock""#.to_owned(),
+                n_wires: qubits.len(),
+                width: Some("13em".to_owned()),
+                fill: Some("gray".to_owned()),
+                inputs: op.qubits().iter().map(|qubit| qubit - min).collect(),
+            });
             push_ones(circuit_gates, min, max);
             Ok(())
         }
@@ -712,15 +915,19 @@ pub fn add_gate(
             prepare_for_slice(circuit_gates, circuit_lock);
             let n_qubits = circuit_gates.len();
             flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
-            circuit_gates[0].push(format!(
-                r#"slice(label: $ "GlobalPhase"\ {} $)"#,
-                format_calculator(op.phase(), rounding_accuracy),
-            ));
+            circuit_gates[0].push(Cell::Slice {
+                label: format!(
+                    r#""GlobalPhase"\ {}"#,
+                    format_angle(op.phase(), number_format)
+                ),
+                dash: None,
+            });
             Ok(())
         }
         Operation::PragmaSleep(op) => {
             if op.qubits().is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -729,68 +936,68 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                r#"mqgate($ "Sleep"({}) $, n: {}, width: 7em, fill: gray, inputs: ({}))"#,
-                format_calculator(op.sleep_time(), rounding_accuracy),
-                qubits.len(),
-                op.qubits()
-                    .iter()
-                    .map(|qubit| format!("(qubit: {})", format_qubit_input(qubit - min, "x")))
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ));
+            circuit_gates[min].push(Cell::MultiGate {
+                content: format!(
+                    r#""Sleep"({})"#,
+                    format_calculator(op.sleep_time(), number_format)
+                ),
+                n_wires: qubits.len(),
+                width: Some("7em".to_owned()),
+                fill: Some("gray".to_owned()),
+                inputs: op.qubits().iter().map(|qubit| qubit - min).collect(),
+            });
             push_ones(circuit_gates, min, max);
             Ok(())
         }
         Operation::PragmaActiveReset(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("gate($ \"Reset\" $, fill: gray)".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::pragma_gate("\"Reset\""));
             Ok(())
         }
         Operation::PragmaDamping(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"Damping\"({},{}) $, fill: gray)",
-                format_calculator(op.gate_time(), rounding_accuracy),
-                format_calculator(op.rate(), rounding_accuracy),
-            ));
+            circuit_gates[*op.qubit()].push(Cell::pragma_gate(format!(
+                "\"Damping\"({},{})",
+                format_calculator(op.gate_time(), number_format),
+                format_calculator(op.rate(), number_format)
+            )));
             Ok(())
         }
         Operation::PragmaDepolarising(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"Depolarising\"({},{}) $, fill: gray)",
-                format_calculator(op.gate_time(), rounding_accuracy),
-                format_calculator(op.rate(), rounding_accuracy),
-            ));
+            circuit_gates[*op.qubit()].push(Cell::pragma_gate(format!(
+                "\"Depolarising\"({},{})",
+                format_calculator(op.gate_time(), number_format),
+                format_calculator(op.rate(), number_format)
+            )));
             Ok(())
         }
         Operation::PragmaDephasing(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"Dephasing\"({},{}) $, fill: gray)",
-                format_calculator(op.gate_time(), rounding_accuracy),
-                format_calculator(op.rate(), rounding_accuracy),
-            ));
+            circuit_gates[*op.qubit()].push(Cell::pragma_gate(format!(
+                "\"Dephasing\"({},{})",
+                format_calculator(op.gate_time(), number_format),
+                format_calculator(op.rate(), number_format)
+            )));
             Ok(())
         }
         Operation::PragmaRandomNoise(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"RandomNoise\"({},{},{}) $, fill: gray)",
-                format_calculator(op.gate_time(), rounding_accuracy),
-                format_calculator(op.depolarising_rate(), rounding_accuracy),
-                format_calculator(op.dephasing_rate(), rounding_accuracy),
-            ));
+            circuit_gates[*op.qubit()].push(Cell::pragma_gate(format!(
+                "\"RandomNoise\"({},{},{})",
+                format_calculator(op.gate_time(), number_format),
+                format_calculator(op.depolarising_rate(), number_format),
+                format_calculator(op.dephasing_rate(), number_format)
+            )));
             Ok(())
         }
         Operation::PragmaGeneralNoise(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"GeneralNoise\"({},{}) $, fill: gray)",
-                format_calculator(op.gate_time(), rounding_accuracy),
-                op.rates(),
-            ));
+            circuit_gates[*op.qubit()].push(Cell::pragma_gate(format!(
+                "\"GeneralNoise\"({},{})",
+                format_calculator(op.gate_time(), number_format),
+                op.rates()
+            )));
             Ok(())
         }
         Operation::PragmaConditional(op) => {
@@ -824,12 +1031,15 @@ pub fn add_gate(
             }
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, replace_by_len, label: \"Conditional: {}[{}]\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.condition_register(),
-                op.condition_index(),
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 0,
+                label: format!(
+                    "Conditional: {}[{}]",
+                    op.condition_register(),
+                    op.condition_index()
+                ),
+            });
             let group_index = circuit_gates[min].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -845,7 +1055,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -853,8 +1064,9 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[min][group_index] = circuit_gates[min][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[min][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
@@ -862,239 +1074,220 @@ pub fn add_gate(
             prepare_for_slice(circuit_gates, circuit_lock);
             let n_qubits = circuit_gates.len();
             flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
-            circuit_gates[0].push(format!(
-                r#"slice(label: $ "ChangeDevice" "{}" $)"#,
-                op.wrapped_hqslang,
-            ));
+            circuit_gates[0].push(Cell::Slice {
+                label: format!(r#""ChangeDevice" "{}""#, op.wrapped_hqslang),
+                dash: None,
+            });
             Ok(())
         }
         Operation::SWAP(op) => {
             let min = *op.control().min(op.target());
             let max = *op.control().max(op.target());
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[min].push(format!("swap({})", max - min));
-            circuit_gates[max].push("swap()".to_owned());
+            circuit_gates[min].push(Cell::Swap {
+                target: Some(Wire::Qubit(max)),
+                label: None,
+            });
+            circuit_gates[max].push(Cell::Swap {
+                target: None,
+                label: None,
+            });
             Ok(())
         }
         Operation::ISwap(op) => {
             let min = *op.control().min(op.target());
             let max = *op.control().max(op.target());
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[min].push(format!("swap({}, label: \"ISwap\")", max - min));
-            circuit_gates[max].push("swap()".to_owned());
+            circuit_gates[min].push(Cell::Swap {
+                target: Some(Wire::Qubit(max)),
+                label: Some("\"ISwap\"".to_owned()),
+            });
+            circuit_gates[max].push(Cell::Swap {
+                target: None,
+                label: None,
+            });
             Ok(())
         }
         Operation::FSwap(op) => {
             let min = *op.control().min(op.target());
             let max = *op.control().max(op.target());
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[min].push(format!("swap({}, label: \"FSwap\")", max - min));
-            circuit_gates[max].push("swap()".to_owned());
+            circuit_gates[min].push(Cell::Swap {
+                target: Some(Wire::Qubit(max)),
+                label: Some("\"FSwap\"".to_owned()),
+            });
+            circuit_gates[max].push(Cell::Swap {
+                target: None,
+                label: None,
+            });
             Ok(())
         }
         Operation::SqrtISwap(op) => {
             let min = *op.control().min(op.target());
             let max = *op.control().max(op.target());
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[min].push(format!("swap({}, label: $ sqrt(\"ISwap\") $)", max - min));
-            circuit_gates[max].push("swap()".to_owned());
+            circuit_gates[min].push(Cell::Swap {
+                target: Some(Wire::Qubit(max)),
+                label: Some("sqrt(\"ISwap\")".to_owned()),
+            });
+            circuit_gates[max].push(Cell::Swap {
+                target: None,
+                label: None,
+            });
             Ok(())
         }
         Operation::InvSqrtISwap(op) => {
             let min = *op.control().min(op.target());
             let max = *op.control().max(op.target());
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[min].push(format!(
-                "swap({}, label: $ sqrt(\"ISwap\")^(dagger) $)",
-                max - min
-            ));
-            circuit_gates[max].push("swap()".to_owned());
+            circuit_gates[min].push(Cell::Swap {
+                target: Some(Wire::Qubit(max)),
+                label: Some("sqrt(\"ISwap\")^(dagger)".to_owned()),
+            });
+            circuit_gates[max].push(Cell::Swap {
+                target: None,
+                label: None,
+            });
             Ok(())
         }
         Operation::XY(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"XY\"({})$)",
-                format_calculator(op.theta(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"XY\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::ControlledPhaseShift(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"PhaseShift\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"PhaseShift\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::ControlledPauliY(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push("gate($ \"Y\" $)".to_string());
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate("\"Y\""));
             Ok(())
         }
         Operation::ControlledPauliZ(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push("gate($ \"Z\" $)".to_string());
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate("\"Z\""));
             Ok(())
         }
         Operation::MolmerSorensenXX(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push("gate($ \"MolmerSorensenXX\" $)".to_owned());
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate("\"MolmerSorensenXX\""));
             Ok(())
         }
         Operation::VariableMSXX(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"VariableMSXX\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"VariableMSXX\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::GivensRotation(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"GivensRotation\"({},{}) $)",
-                format_calculator(op.theta(), rounding_accuracy),
-                format_calculator(op.phi(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"GivensRotation\"({},{})",
+                format_angle(op.theta(), number_format),
+                format_angle(op.phi(), number_format)
+            )));
             Ok(())
         }
         Operation::GivensRotationLittleEndian(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"GivensRotationLE\"({},{}) $)",
-                format_calculator(op.theta(), rounding_accuracy),
-                format_calculator(op.phi(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"GivensRotationLE\"({},{})",
+                format_angle(op.theta(), number_format),
+                format_angle(op.phi(), number_format)
+            )));
             Ok(())
         }
         Operation::Qsim(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"Qsim\"({},{},{}) $)",
-                format_calculator(op.x(), rounding_accuracy),
-                format_calculator(op.y(), rounding_accuracy),
-                format_calculator(op.z(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"Qsim\"({},{},{})",
+                format_calculator(op.x(), number_format),
+                format_calculator(op.y(), number_format),
+                format_calculator(op.z(), number_format)
+            )));
             Ok(())
         }
         Operation::Fsim(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"Fsim\"({},{},{}) $)",
-                format_calculator(op.t(), rounding_accuracy),
-                format_calculator(op.u(), rounding_accuracy),
-                format_calculator(op.delta(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"Fsim\"({},{},{})",
+                format_calculator(op.t(), number_format),
+                format_calculator(op.u(), number_format),
+                format_calculator(op.delta(), number_format)
+            )));
             Ok(())
         }
         Operation::SpinInteraction(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"SpinInteraction\"({},{},{}) $)",
-                format_calculator(op.x(), rounding_accuracy),
-                format_calculator(op.y(), rounding_accuracy),
-                format_calculator(op.z(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"SpinInteraction\"({},{},{})",
+                format_calculator(op.x(), number_format),
+                format_calculator(op.y(), number_format),
+                format_calculator(op.z(), number_format)
+            )));
             Ok(())
         }
         Operation::Bogoliubov(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"Bogoliubov\"({},{}) $)",
-                format_calculator(op.delta_real(), rounding_accuracy),
-                format_calculator(op.delta_imag(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"Bogoliubov\"({})",
+                format_complex_calculator(op.delta_real(), op.delta_imag(), number_format)
+            )));
             Ok(())
         }
         Operation::PMInteraction(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"PMInteraction\"({}) $)",
-                format_calculator(op.t(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"PMInteraction\"({})",
+                format_calculator(op.t(), number_format)
+            )));
             Ok(())
         }
         Operation::ComplexPMInteraction(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"ComplexPMInteraction\"({},{}) $)",
-                format_calculator(op.t_real(), rounding_accuracy),
-                format_calculator(op.t_imag(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"ComplexPMInteraction\"({})",
+                format_complex_calculator(op.t_real(), op.t_imag(), number_format)
+            )));
             Ok(())
         }
         Operation::PhaseShiftedControlledZ(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"PhaseShiftedControlledZ\"({}) $)",
-                format_calculator(op.phi(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"PhaseShiftedControlledZ\"({})",
+                format_angle(op.phi(), number_format)
+            )));
             Ok(())
         }
         Operation::MultiQubitMS(op) => {
             if op.qubits().is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1103,24 +1296,25 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                r#"mqgate($ "MultiQubitMS"({}) $, n: {}, width: 11em, inputs: ({}))"#,
-                format_calculator(op.theta(), rounding_accuracy),
-                qubits.len(),
-                op.qubits()
-                    .iter()
-                    .map(|qubit| format!("(qubit: {})", format_qubit_input(qubit - min, "x")))
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ));
+            circuit_gates[min].push(Cell::MultiGate {
+                content: format!(
+                    r#""MultiQubitMS"({})"#,
+                    format_angle(op.theta(), number_format)
+                ),
+                n_wires: qubits.len(),
+                width: Some("11em".to_owned()),
+                fill: None,
+                inputs: op.qubits().iter().map(|qubit| qubit - min).collect(),
+            });
             for gates in circuit_gates.iter_mut().take(max + 1).skip(min + 1) {
-                gates.push("1".to_owned());
+                gates.push(Cell::Idle);
             }
             Ok(())
         }
         Operation::MultiQubitZZ(op) => {
             if op.qubits().is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1129,18 +1323,18 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                r#"mqgate($ "MultiQubitZZ"({}) $, n: {}, width: 11em, inputs: ({}))"#,
-                format_calculator(op.theta(), rounding_accuracy),
-                qubits.len(),
-                op.qubits()
-                    .iter()
-                    .map(|qubit| format!("(qubit: {})", format_qubit_input(qubit - min, "x")))
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ));
+            circuit_gates[min].push(Cell::MultiGate {
+                content: format!(
+                    r#""MultiQubitZZ"({})"#,
+                    format_angle(op.theta(), number_format)
+                ),
+                n_wires: qubits.len(),
+                width: Some("11em".to_owned()),
+                fill: None,
+                inputs: op.qubits().iter().map(|qubit| qubit - min).collect(),
+            });
             for gates in circuit_gates.iter_mut().take(max + 1).skip(min + 1) {
-                gates.push("1".to_owned());
+                gates.push(Cell::Idle);
             }
             Ok(())
         }
@@ -1150,14 +1344,14 @@ pub fn add_gate(
                 .iter()
                 .cloned()
                 .enumerate()
-                .find(|(_i, gates)| gates[0].eq(&format!("lstick($ \"{} : \" $)", op.readout())))
+                .find(|(_i, gates)| gates[0] == Cell::RegisterName(op.readout().to_owned()))
             {
                 flatten_multiple_vec(circuit_gates, classical_gates, &[*op.qubit()], &[index]);
                 for qubit in *op.qubit()..circuit_gates.len() {
                     while circuit_lock.contains(&(qubit, effective_len(&circuit_gates[qubit]))) {
                         circuit_lock
                             .retain(|&val| val != (qubit, effective_len(&circuit_gates[qubit])));
-                        circuit_gates[qubit].push("1".to_owned());
+                        circuit_gates[qubit].push(Cell::Idle);
                     }
                     if circuit_gates.len() > qubit
                         && effective_len(&circuit_gates[qubit])
@@ -1191,7 +1385,7 @@ pub fn add_gate(
                                 effective_len(&classical_gates[classical_index]),
                             )
                         });
-                        classical_gates[classical_index].push("1".to_owned());
+                        classical_gates[classical_index].push(Cell::Idle);
                     }
                     if classical_gates.len() > classical_index
                         && effective_len(&classical_gates[classical_index])
@@ -1210,17 +1404,15 @@ pub fn add_gate(
                 for classical_index in 0..index {
                     classical_lock.push((classical_index, classical_gates[index].len()));
                 }
-                circuit_gates[*op.qubit()].push(format!(
-                    "meter(target:replace_by_classical_len_{}-{})",
-                    index,
-                    *op.qubit()
-                ));
-                classical_gates[index].push(format!(
-                    "ctrl(0, label: (content: $ {} $, pos: bottom))",
-                    op.readout_index()
-                ))
+                circuit_gates[*op.qubit()].push(Cell::Meter {
+                    target: Some(Wire::Classical(index)),
+                });
+                classical_gates[index].push(Cell::Control {
+                    target: None,
+                    label: Some(op.readout_index().to_string()),
+                })
             } else {
-                circuit_gates[*op.qubit()].push("meter()".to_owned());
+                circuit_gates[*op.qubit()].push(Cell::Meter { target: None });
             }
             Ok(())
         }
@@ -1250,7 +1442,8 @@ pub fn add_gate(
                 InvolvedQubits::None => {}
             }
             if used_qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1262,11 +1455,11 @@ pub fn add_gate(
             }
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, replace_by_len, label: \"GetStateVector: {}\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.readout()
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 0,
+                label: format!("GetStateVector: {}", op.readout()),
+            });
             let group_index = circuit_gates[min].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -1282,7 +1475,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -1290,8 +1484,9 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[min][group_index] = circuit_gates[min][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[min][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
@@ -1321,7 +1516,8 @@ pub fn add_gate(
                 InvolvedQubits::None => {}
             }
             if used_qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1333,11 +1529,11 @@ pub fn add_gate(
             }
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, replace_by_len, label: \"GetDensityMatrix: {}\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.readout(),
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 0,
+                label: format!("GetDensityMatrix: {}", op.readout()),
+            });
             let group_index = circuit_gates[min].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -1353,7 +1549,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -1361,8 +1558,9 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[min][group_index] = circuit_gates[min][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[min][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
@@ -1392,7 +1590,8 @@ pub fn add_gate(
                 InvolvedQubits::None => {}
             }
             if used_qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1404,11 +1603,11 @@ pub fn add_gate(
             }
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, replace_by_len, label: \"GetOccupationProbability: {}\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.readout(),
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 0,
+                label: format!("GetOccupationProbability: {}", op.readout()),
+            });
             let group_index = circuit_gates[min].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -1424,7 +1623,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -1432,8 +1632,9 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[min][group_index] = circuit_gates[min][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[min][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
@@ -1447,9 +1648,10 @@ pub fn add_gate(
                     2 => circuit.add_operation(PauliY::new(qubit)),
                     3 => circuit.add_operation(PauliZ::new(qubit)),
                     _ => {
-                        return Err(RoqoqoBackendError::RoqoqoError(
-                            RoqoqoError::QubitMappingError { qubit },
-                        ))
+                        return Err(RoqollageError::InvalidOperation {
+                            index: None,
+                            msg: format!("Invalid Pauli operator {op_val} for qubit {qubit}."),
+                        })
                     }
                 }
             }
@@ -1472,7 +1674,8 @@ pub fn add_gate(
                 InvolvedQubits::None => {}
             }
             if used_qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1484,11 +1687,11 @@ pub fn add_gate(
             }
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, replace_by_len, label: \"GetPauliProduct: {}\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.readout(),
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 0,
+                label: format!("GetPauliProduct: {}", op.readout()),
+            });
             let group_index = circuit_gates[min].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -1504,7 +1707,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -1512,8 +1716,9 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[min][group_index] = circuit_gates[min][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[min][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
@@ -1526,7 +1731,8 @@ pub fn add_gate(
                     map.keys().cloned().collect()
                 });
             if used_qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1535,11 +1741,11 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, 1, label: \"Repeat {} times\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.number_measurements(),
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 1,
+                label: format!("Repeat {} times", op.number_measurements()),
+            });
             for &qubit in used_qubits.iter() {
                 add_gate(
                     circuit_gates,
@@ -1550,7 +1756,8 @@ pub fn add_gate(
                     classical_lock,
                     &Operation::from(MeasureQubit::new(qubit, "ro".to_owned(), qubit)),
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
@@ -1560,11 +1767,14 @@ pub fn add_gate(
             prepare_for_slice(circuit_gates, circuit_lock);
             let n_qubits = circuit_gates.len();
             flatten_qubits(circuit_gates, &(0..n_qubits).collect::<Vec<usize>>());
-            circuit_gates[0].push(format!(
-                "slice(label: $ \"Replace Symbole:\"\\ \"{}\"=>{} $)",
-                op.name(),
-                format_calculator(&CalculatorFloat::from(op.input()), rounding_accuracy),
-            ));
+            circuit_gates[0].push(Cell::Slice {
+                label: format!(
+                    "\"Replace Symbole:\"\\ \"{}\"=>{}",
+                    op.name(),
+                    format_calculator(&CalculatorFloat::from(op.input()), number_format)
+                ),
+                dash: None,
+            });
             Ok(())
         }
         Operation::PragmaLoop(op) => {
@@ -1598,14 +1808,18 @@ pub fn add_gate(
             }
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, replace_by_len, label: \"Loop: {} times\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                match op.repetitions() {
-                    CalculatorFloat::Float(float_value) => (float_value.floor() as usize).to_string(),
-                    _ => format_calculator(op.repetitions(), rounding_accuracy).replace('"', "")
-                }
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 0,
+                label: format!(
+                    "Loop: {} times",
+                    match op.repetitions() {
+                        CalculatorFloat::Float(float_value) =>
+                            (float_value.floor() as usize).to_string(),
+                        _ => format_calculator(op.repetitions(), number_format).replace('"', ""),
+                    }
+                ),
+            });
             let group_index = circuit_gates[min].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -1621,7 +1835,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -1629,47 +1844,39 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[min][group_index] = circuit_gates[min][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[min][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
         Operation::PhaseShiftedControlledPhase(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"PhaseShiftControlledPhase\"({},{})$)",
-                format_calculator(op.theta(), rounding_accuracy),
-                format_calculator(op.phi(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"PhaseShiftControlledPhase\"({},{})",
+                format_angle(op.theta(), number_format),
+                format_angle(op.phi(), number_format)
+            )));
             Ok(())
         }
         Operation::ControlledRotateX(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"Rx\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"Rx\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::ControlledRotateXY(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"Rxy\"({},{}) $)",
-                format_calculator(op.theta(), rounding_accuracy),
-                format_calculator(op.phi(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"Rxy\"({},{})",
+                format_angle(op.theta(), number_format),
+                format_angle(op.phi(), number_format)
+            )));
             Ok(())
         }
         Operation::ControlledControlledPauliZ(op) => {
@@ -1678,15 +1885,9 @@ pub fn add_gate(
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
             flatten_qubits(circuit_gates, qubits);
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[*op.control_1()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_1() as i32
-            ));
-            circuit_gates[*op.target()].push("gate($ Z $)".to_owned());
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_1()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate("Z"));
             Ok(())
         }
         Operation::ControlledControlledPhaseShift(op) => {
@@ -1695,18 +1896,12 @@ pub fn add_gate(
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
             flatten_qubits(circuit_gates, qubits);
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[*op.control_1()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_1() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"PhaseShift\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_1()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"PhaseShift\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::Toffoli(op) => {
@@ -1714,31 +1909,25 @@ pub fn add_gate(
             add_qubits_vec(circuit_gates, qubits);
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[*op.control_1()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_1() as i32
-            ));
-            circuit_gates[*op.target()].push("targ()".to_owned());
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_1()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::Target);
             Ok(())
         }
         Operation::GPi(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"GPi\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::gate(format!(
+                "\"GPi\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::GPi2(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push(format!(
-                "gate($ \"GPi2\"({}) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::gate(format!(
+                "\"GPi2\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::PragmaControlledCircuit(op) => {
@@ -1765,7 +1954,8 @@ pub fn add_gate(
                 InvolvedQubits::None => {}
             }
             if used_qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1774,11 +1964,11 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, replace_by_len, label: \"ControlledCircuit by qubit: {}\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.controlling_qubit(),
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 0,
+                label: format!("ControlledCircuit by qubit: {}", op.controlling_qubit()),
+            });
             let group_index = circuit_gates[min].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -1794,7 +1984,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -1802,28 +1993,29 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[min][group_index] = circuit_gates[min][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[min][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
         Operation::Squeezing(op) => {
             add_qubits_vec(bosonic_gates, &[*op.mode()]);
             prepare_for_bosonic(*op.mode(), bosonic_gates, bosonic_lock);
-            bosonic_gates[*op.mode()].push(format!(
-                "gate($ \"Squeezing\"({},{}) $)",
-                format_calculator(op.squeezing(), rounding_accuracy),
-                format_calculator(op.phase(), rounding_accuracy),
-            ));
+            bosonic_gates[*op.mode()].push(Cell::gate(format!(
+                "\"Squeezing\"({},{})",
+                format_calculator(op.squeezing(), number_format),
+                format_angle(op.phase(), number_format)
+            )));
             Ok(())
         }
         Operation::PhaseShift(op) => {
             add_qubits_vec(bosonic_gates, &[*op.mode()]);
             prepare_for_bosonic(*op.mode(), bosonic_gates, bosonic_lock);
-            bosonic_gates[*op.mode()].push(format!(
-                "gate($ \"PhaseShift\"({}) $)",
-                format_calculator(op.phase(), rounding_accuracy),
-            ));
+            bosonic_gates[*op.mode()].push(Cell::gate(format!(
+                "\"PhaseShift\"({})",
+                format_angle(op.phase(), number_format)
+            )));
             Ok(())
         }
         Operation::BeamSplitter(op) => {
@@ -1835,28 +2027,31 @@ pub fn add_gate(
                 prepare_for_bosonic(mode, bosonic_gates, bosonic_lock);
             }
             flatten_qubits(bosonic_gates, &modes);
-            bosonic_gates[min].push(format!(
-                "mqgate($ \"BeamSplitter\"\\ ({},{}) $, n: {}, width: 9em, inputs: ((qubit: {}), (qubit: {})))",
-                format_calculator(op.theta(), rounding_accuracy),
-                format_calculator(op.phi(), rounding_accuracy),
-                modes.len(),
-                format_qubit_input(*op.mode_0() - min, "x"),
-                format_qubit_input(*op.mode_1() - min, "x")
-            ));
+            bosonic_gates[min].push(Cell::MultiGate {
+                content: format!(
+                    "\"BeamSplitter\"\\ ({},{})",
+                    format_angle(op.theta(), number_format),
+                    format_angle(op.phi(), number_format),
+                ),
+                n_wires: modes.len(),
+                width: Some("9em".to_owned()),
+                fill: None,
+                inputs: vec![*op.mode_0() - min, *op.mode_1() - min],
+            });
             for gates in circuit_gates.iter_mut().take(max + 1).skip(min + 1) {
-                gates.push("1".to_owned());
+                gates.push(Cell::Idle);
             }
             Ok(())
         }
         Operation::PhotonDetection(op) => {
             add_qubits_vec(bosonic_gates, &[*op.mode()]);
             prepare_for_bosonic(*op.mode(), bosonic_gates, bosonic_lock);
-            bosonic_gates[*op.mode()].push("meter()".to_owned());
+            bosonic_gates[*op.mode()].push(Cell::Meter { target: None });
             Ok(())
         }
         Operation::Identity(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ I $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("I"));
             Ok(())
         }
         Operation::PragmaAnnotatedOp(op) => {
@@ -1880,7 +2075,8 @@ pub fn add_gate(
                 InvolvedQubits::None => {}
             }
             if used_qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1889,11 +2085,11 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
-            circuit_gates[min].push(format!(
-                "gategroup({}, 1, label: \"{}\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.annotation,
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 1,
+                label: op.annotation.clone(),
+            });
             add_gate(
                 circuit_gates,
                 bosonic_gates,
@@ -1903,34 +2099,33 @@ pub fn add_gate(
                 classical_lock,
                 &op.operation,
                 render_pragmas,
-                rounding_accuracy,
+                number_format,
+                warnings.as_deref_mut(),
             )?;
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
         Operation::EchoCrossResonance(op) => {
             prepare_for_ctrl(circuit_gates, circuit_lock, &[*op.control(), *op.target()]);
-            circuit_gates[*op.control()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control() as i32
-            ));
-            circuit_gates[*op.target()].push("gate($ \"EchoCrossResonance\" $)".to_owned());
+            circuit_gates[*op.control()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate("\"EchoCrossResonance\""));
             Ok(())
         }
         Operation::PhaseDisplacement(op) => {
             add_qubits_vec(bosonic_gates, &[*op.mode()]);
             prepare_for_bosonic(*op.mode(), bosonic_gates, bosonic_lock);
-            bosonic_gates[*op.mode()].push(format!(
-                "gate($ \"PhaseDisplacement\"({},{}) $)",
-                format_calculator(op.displacement(), rounding_accuracy),
-                format_calculator(op.phase(), rounding_accuracy),
-            ));
+            bosonic_gates[*op.mode()].push(Cell::gate(format!(
+                "\"PhaseDisplacement\"({},{})",
+                format_calculator(op.displacement(), number_format),
+                format_angle(op.phase(), number_format)
+            )));
             Ok(())
         }
         #[cfg(feature = "unstable_operation_definition")]
         Operation::CallDefinedGate(op) => {
             if op.qubits().is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1939,16 +2134,13 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                r#"mqgate($ "CallDefinedGate\n\"{}\"" $, n: {}, width: 11em, inputs: ({}))"#,
-                op.gate_name(),
-                qubits.len(),
-                op.qubits()
-                    .iter()
-                    .map(|qubit| format!("(qubit: {})", format_qubit_input(qubit - min, "x")))
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ));
+            circuit_gates[min].push(Cell::MultiGate {
+                content: format!(r#""CallDefinedGate\n\"{}\"""#, op.gate_name()),
+                n_wires: qubits.len(),
+                width: Some("11em".to_owned()),
+                fill: None,
+                inputs: op.qubits().iter().map(|qubit| qubit - min).collect(),
+            });
             push_ones(circuit_gates, min, max);
             Ok(())
         }
@@ -1977,7 +2169,8 @@ pub fn add_gate(
                 InvolvedQubits::None => {}
             }
             if used_qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {op:?}"),
                 });
             }
@@ -1986,11 +2179,11 @@ pub fn add_gate(
             let qubits: Vec<usize> = (min..max + 1).collect();
             add_qubits_vec(circuit_gates, &qubits);
             flatten_qubits(circuit_gates, &qubits);
-            circuit_gates[min].push(format!(
-                "gategroup({}, replace_by_len, label: \"GateDefinition: {}\",  stroke: (dash: \"dotted\"))",
-                qubit_range(&qubits),
-                op.name(),
-            ));
+            circuit_gates[min].push(Cell::Group {
+                n_wires: qubit_range(&qubits),
+                length: 0,
+                label: format!("GateDefinition: {}", op.name()),
+            });
             let group_index = circuit_gates[min].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -2006,7 +2199,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -2014,8 +2208,9 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[min][group_index] = circuit_gates[min][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[min][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(&qubits));
             Ok(())
         }
@@ -2031,16 +2226,15 @@ pub fn add_gate(
             for mode in 0..*op.mode() {
                 bosonic_lock.push((mode, effective_len(&bosonic_gates[*op.mode()])));
             }
-            circuit_gates[*op.qubit()].push(format!(
-                "mqgate($ {} * X $, extent: 1.4em, target: replace_by_n_qubits_plus_{}-{})",
-                format_calculator(op.theta(), rounding_accuracy),
-                *op.mode(),
-                *op.qubit(),
-            ));
-            bosonic_gates[*op.mode()].push(format!(
-                "gate($ {}*(b^(dagger)+b) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::LinkedGate {
+                content: format!("{} * X", format_calculator(op.theta(), number_format)),
+                target: Wire::Boson(*op.mode()),
+                extent: Some("1.4em".to_owned()),
+            });
+            bosonic_gates[*op.mode()].push(Cell::gate(format!(
+                "{}*(b^(dagger)+b)",
+                format_calculator(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::LongitudinalCoupling(op) => {
@@ -2055,16 +2249,15 @@ pub fn add_gate(
             for mode in 0..*op.mode() {
                 bosonic_lock.push((mode, effective_len(&bosonic_gates[*op.mode()])));
             }
-            circuit_gates[*op.qubit()].push(format!(
-                "mqgate($ {} * Z $, extent: 1.4em, target: replace_by_n_qubits_plus_{}-{})",
-                format_calculator(op.theta(), rounding_accuracy),
-                *op.mode(),
-                *op.qubit(),
-            ));
-            bosonic_gates[*op.mode()].push(format!(
-                "gate($ {}*(b^(dagger)+b) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::LinkedGate {
+                content: format!("{} * Z", format_calculator(op.theta(), number_format)),
+                target: Wire::Boson(*op.mode()),
+                extent: Some("1.4em".to_owned()),
+            });
+            bosonic_gates[*op.mode()].push(Cell::gate(format!(
+                "{}*(b^(dagger)+b)",
+                format_calculator(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::JaynesCummings(op) => {
@@ -2079,16 +2272,18 @@ pub fn add_gate(
             for mode in 0..*op.mode() {
                 bosonic_lock.push((mode, effective_len(&bosonic_gates[*op.mode()])));
             }
-            circuit_gates[*op.qubit()].push(format!(
-                "mqgate($ {} * (sigma^-+sigma^+) $, extent: 1.4em, target: replace_by_n_qubits_plus_{}-{})",
-                format_calculator(op.theta(), rounding_accuracy),
-                *op.mode(),
-                *op.qubit(),
-            ));
-            bosonic_gates[*op.mode()].push(format!(
-                "gate($ {}*(b^(dagger)+b) $)",
-                format_calculator(op.theta(), rounding_accuracy)
-            ));
+            circuit_gates[*op.qubit()].push(Cell::LinkedGate {
+                content: format!(
+                    "{} * (sigma^-+sigma^+)",
+                    format_calculator(op.theta(), number_format)
+                ),
+                target: Wire::Boson(*op.mode()),
+                extent: Some("1.4em".to_owned()),
+            });
+            bosonic_gates[*op.mode()].push(Cell::gate(format!(
+                "{}*(b^(dagger)+b)",
+                format_calculator(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::SingleExcitationStore(op) => {
@@ -2103,13 +2298,12 @@ pub fn add_gate(
             for mode in 0..*op.mode() {
                 bosonic_lock.push((mode, effective_len(&bosonic_gates[*op.mode()])));
             }
-            circuit_gates[*op.qubit()].push(format!(
-                r#"mqgate($ alpha"|0>" + beta"|1>" -> "|0>" $, target: replace_by_n_qubits_plus_{}-{})"#,
-                *op.mode(),
-                *op.qubit(),
-            ));
-            bosonic_gates[*op.mode()]
-                .push(r#"gate($ "|0>" -> alpha"|0>" + beta"|1>" $)"#.to_string());
+            circuit_gates[*op.qubit()].push(Cell::LinkedGate {
+                content: r#"alpha"|0>" + beta"|1>" -> "|0>""#.to_owned(),
+                target: Wire::Boson(*op.mode()),
+                extent: None,
+            });
+            bosonic_gates[*op.mode()].push(Cell::gate(r#""|0>" -> alpha"|0>" + beta"|1>""#));
             Ok(())
         }
         Operation::SingleExcitationLoad(op) => {
@@ -2124,13 +2318,12 @@ pub fn add_gate(
             for mode in 0..*op.mode() {
                 bosonic_lock.push((mode, effective_len(&bosonic_gates[*op.mode()])));
             }
-            circuit_gates[*op.qubit()].push(format!(
-                r#"mqgate($ "|0>" -> alpha"|0>" + beta"|1>" $, target: replace_by_n_qubits_plus_{}-{})"#,
-                *op.mode(),
-                *op.qubit(),
-            ));
-            bosonic_gates[*op.mode()]
-                .push(r#"gate($ alpha"|0>" + beta"|1>" -> "|0>" $)"#.to_string());
+            circuit_gates[*op.qubit()].push(Cell::LinkedGate {
+                content: r#""|0>" -> alpha"|0>" + beta"|1>""#.to_owned(),
+                target: Wire::Boson(*op.mode()),
+                extent: None,
+            });
+            bosonic_gates[*op.mode()].push(Cell::gate(r#"alpha"|0>" + beta"|1>" -> "|0>""#));
             Ok(())
         }
         Operation::CZQubitResonator(op) => {
@@ -2161,63 +2354,59 @@ pub fn add_gate(
             for mode in 0..*op.mode() {
                 bosonic_lock.push((mode, effective_len(&bosonic_gates[*op.mode()])));
             }
-            circuit_gates[*op.qubit()].push(format!(
-                r#"ctrl(replace_by_n_qubits_plus_{}-{})"#,
-                *op.mode(),
-                *op.qubit(),
-            ));
-            bosonic_gates[*op.mode()].push(r#"gate($ Z $)"#.to_string());
+            circuit_gates[*op.qubit()].push(Cell::control(Wire::Boson(*op.mode())));
+            bosonic_gates[*op.mode()].push(Cell::gate("Z"));
             Ok(())
         }
         Operation::DefinitionBit(op) => {
             classical_gates.push(Vec::new());
             let index = classical_gates.len() - 1;
-            classical_gates[index].push(format!("lstick($ \"{} : \" $)", op.name()));
-            classical_gates[index].push(format!("setwire({})", 2));
+            classical_gates[index].push(Cell::RegisterName(op.name().to_owned()));
+            classical_gates[index].push(Cell::WireLines(2));
             Ok(())
         }
         Operation::InputBit(op) => {
             if let Some((index, _)) = classical_gates
                 .iter()
                 .enumerate()
-                .find(|(_ind, register)| register[0].contains(op.name()))
+                .find(|(_ind, register)| register[0] == Cell::RegisterName(op.name().to_owned()))
             {
-                classical_gates[index].push(format!(
-                    "gate($ \"InputBit:\"\\ {}=>#{} $)",
+                classical_gates[index].push(Cell::gate(format!(
+                    "\"InputBit:\"\\ {}=>#{}",
                     op.index(),
                     op.value()
-                ));
+                )));
             }
             Ok(())
         }
         Operation::SqrtPauliY(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ sqrt(Y) $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("sqrt(Y)"));
             Ok(())
         }
         Operation::InvSqrtPauliY(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ sqrt(Y)^(dagger) $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("sqrt(Y)^(dagger)"));
             Ok(())
         }
         Operation::InvSGate(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ S^(dagger) $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("S^(dagger)"));
             Ok(())
         }
         Operation::InvTGate(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ T^(dagger) $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("T^(dagger)"));
             Ok(())
         }
         Operation::SXGate(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ \"SX\" $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("\"SX\""));
             Ok(())
         }
         Operation::InvSXGate(op) => {
             add_qubits_vec(circuit_gates, &[*op.qubit()]);
-            circuit_gates[*op.qubit()].push("$ \"SX\"^(dagger) $".to_owned());
+            circuit_gates[*op.qubit()].push(Cell::gate("\"SX\"^(dagger)"));
             Ok(())
         }
         Operation::ControlledSWAP(op) => {
@@ -2227,12 +2416,15 @@ pub fn add_gate(
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
             let min = op.target_0().min(op.target_1()).to_owned();
             let max = op.target_0().max(op.target_1()).to_owned();
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[min].push(format!("swap({})", max - min));
-            circuit_gates[max].push("swap()".to_owned());
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[min].push(Cell::Swap {
+                target: Some(Wire::Qubit(max)),
+                label: None,
+            });
+            circuit_gates[max].push(Cell::Swap {
+                target: None,
+                label: None,
+            });
             Ok(())
         }
         Operation::PhaseShiftedControlledControlledZ(op) => {
@@ -2240,18 +2432,12 @@ pub fn add_gate(
             add_qubits_vec(circuit_gates, qubits);
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[*op.control_1()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_1() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"PhaseShiftedControlledControlledZ\"({})$)",
-                format_calculator(op.phi(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_1()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"PhaseShiftedControlledControlledZ\"({})",
+                format_angle(op.phi(), number_format)
+            )));
             Ok(())
         }
         Operation::PhaseShiftedControlledControlledPhase(op) => {
@@ -2259,19 +2445,13 @@ pub fn add_gate(
             add_qubits_vec(circuit_gates, qubits);
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[*op.control_1()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_1() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"PhaseShiftedControlledControlledPhase\"({},{})$)",
-                format_calculator(op.theta(), rounding_accuracy),
-                format_calculator(op.phi(), rounding_accuracy)
-            ));
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_1()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"PhaseShiftedControlledControlledPhase\"({},{})",
+                format_angle(op.theta(), number_format),
+                format_angle(op.phi(), number_format)
+            )));
             Ok(())
         }
         Operation::TripleControlledPauliX(op) => {
@@ -2284,19 +2464,10 @@ pub fn add_gate(
             add_qubits_vec(circuit_gates, qubits);
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[*op.control_1()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_1() as i32
-            ));
-            circuit_gates[*op.control_2()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_2() as i32
-            ));
-            circuit_gates[*op.target()].push("targ()".to_owned());
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_1()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_2()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::Target);
             Ok(())
         }
         Operation::TripleControlledPauliZ(op) => {
@@ -2309,19 +2480,10 @@ pub fn add_gate(
             add_qubits_vec(circuit_gates, qubits);
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[*op.control_1()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_1() as i32
-            ));
-            circuit_gates[*op.control_2()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_2() as i32
-            ));
-            circuit_gates[*op.target()].push("$ Z $".to_owned());
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_1()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_2()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate("Z"));
             Ok(())
         }
         Operation::TripleControlledPhaseShift(op) => {
@@ -2334,28 +2496,20 @@ pub fn add_gate(
             add_qubits_vec(circuit_gates, qubits);
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
-            circuit_gates[*op.control_0()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_0() as i32
-            ));
-            circuit_gates[*op.control_1()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_1() as i32
-            ));
-            circuit_gates[*op.control_2()].push(format!(
-                "ctrl({})",
-                *op.target() as i32 - *op.control_2() as i32
-            ));
-            circuit_gates[*op.target()].push(format!(
-                "gate($ \"TripleControlledPhaseShift\"({})$)",
-                format_calculator(op.theta(), rounding_accuracy),
-            ));
+            circuit_gates[*op.control_0()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_1()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.control_2()].push(Cell::control(Wire::Qubit(*op.target())));
+            circuit_gates[*op.target()].push(Cell::gate(format!(
+                "\"TripleControlledPhaseShift\"({})",
+                format_angle(op.theta(), number_format)
+            )));
             Ok(())
         }
         Operation::MultiQubitCNOT(multi_qubit_cnot) => {
             let qubits = multi_qubit_cnot.qubits();
             if qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {multi_qubit_cnot:?}"),
                 });
             }
@@ -2363,26 +2517,27 @@ pub fn add_gate(
             flatten_qubits(circuit_gates, qubits);
             prepare_for_ctrl(circuit_gates, circuit_lock, qubits);
             for i in 0..qubits.len() - 1 {
-                circuit_gates[qubits[i]]
-                    .push(format!("ctrl({})", qubits[i + 1] as i32 - qubits[i] as i32));
+                circuit_gates[qubits[i]].push(Cell::control(Wire::Qubit(qubits[i + 1])));
             }
-            circuit_gates[*qubits.last().unwrap()].push("targ()".to_owned());
+            circuit_gates[*qubits.last().unwrap()].push(Cell::Target);
             Ok(())
         }
         Operation::QFT(qft) => {
             let qubits = qft.qubits();
             if qubits.is_empty() {
-                return Err(RoqoqoBackendError::GenericError {
+                return Err(RoqollageError::InvalidOperation {
+                    index: None,
                     msg: format!("Operations with no qubit in the input: {qft:?}"),
                 });
             }
             add_qubits_vec(circuit_gates, qubits);
             flatten_qubits(circuit_gates, &qubit_range_vec(qubits));
             prepare_for_slice(circuit_gates, circuit_lock);
-            circuit_gates[qubits[0]].push(format!(
-                "gategroup({}, replace_by_len, label: \"QFT\",  stroke: (dash: \"dotted\"))",
-                qubit_range(qubits),
-            ));
+            circuit_gates[qubits[0]].push(Cell::Group {
+                n_wires: qubit_range(qubits),
+                length: 0,
+                label: "QFT".to_owned(),
+            });
             let group_index = circuit_gates[qubits[0]].len() - 1;
             let old_len = circuit_gates
                 .iter()
@@ -2398,7 +2553,8 @@ pub fn add_gate(
                     classical_lock,
                     operation,
                     render_pragmas,
-                    rounding_accuracy,
+                    number_format,
+                    warnings.as_deref_mut(),
                 )?;
             }
             let max_gates_len_diff = qubits
@@ -2406,17 +2562,25 @@ pub fn add_gate(
                 .map(|&qubit| effective_len(&circuit_gates[qubit]) - old_len[qubit])
                 .max()
                 .unwrap_or(0);
-            circuit_gates[qubits[0]][group_index] = circuit_gates[qubits[0]][group_index]
-                .replace("replace_by_len", &max_gates_len_diff.to_string());
+            if let Cell::Group { length, .. } = &mut circuit_gates[qubits[0]][group_index] {
+                *length = max_gates_len_diff;
+            }
             flatten_qubits(circuit_gates, &qubit_range_vec(qubits));
             Ok(())
         }
         _ => {
             if ALLOWED_OPERATIONS.contains(&operation.hqslang()) {
                 Ok(())
+            } else if let Some(warnings) = warnings {
+                add_placeholder(circuit_gates, circuit_lock, operation);
+                warnings.push(format!(
+                    "Operation {} not supported by the Typst backend, drawn as a placeholder.",
+                    operation.hqslang()
+                ));
+                Ok(())
             } else {
-                Err(RoqoqoBackendError::OperationNotInBackend {
-                    backend: "TypstBackend",
+                Err(RoqollageError::UnsupportedOperation {
+                    index: None,
                     hqslang: operation.hqslang(),
                 })
             }
@@ -0,0 +1,153 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use qoqo_calculator::CalculatorFloat;
+
+use crate::{format_calculator, Cell, InitializationMode, NumberFormat, Wire};
+
+/// Custom labels displayed at the left of the wires of a circuit.
+///
+/// The qubit and mode labels and the initial states are Typst math, in which the words that aren't
+/// Typst symbols are written as text, e.g. `anc`, `data_0`, `|+>` or `|psi>`. The register labels
+/// replace the names of the classical registers and are written as text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WireLabels {
+    /// Labels of the qubit wires, by qubit index.
+    pub qubits: HashMap<usize, String>,
+    /// Labels of the bosonic mode wires, by mode index.
+    pub modes: HashMap<usize, String>,
+    /// Labels of the classical register wires, by register name.
+    pub registers: HashMap<String, String>,
+    /// Initial states of the qubit wires, by qubit index.
+    pub initial_states: HashMap<usize, String>,
+}
+
+impl WireLabels {
+    /// Returns the name of a qubit or bosonic mode wire.
+    ///
+    /// # Arguments
+    ///
+    /// * `wire` - The wire to name.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The Typst math of the custom label of the wire, `q[n]` or `b[n]` by default.
+    pub(crate) fn wire_name(&self, wire: Wire) -> String {
+        match wire {
+            Wire::Qubit(index) => self
+                .qubits
+                .get(&index)
+                .map(|label| format_label(label))
+                .unwrap_or_else(|| format!("q[{index}]")),
+            Wire::Boson(index) => self
+                .modes
+                .get(&index)
+                .map(|label| format_label(label))
+                .unwrap_or_else(|| format!("b[{index}]")),
+            Wire::Classical(index) => format!("c[{index}]"),
+        }
+    }
+
+    /// Returns the name and the initial state displayed at the start of a qubit or bosonic mode wire.
+    ///
+    /// The name is displayed if the wire has a custom label or in the qubit initialization mode, the
+    /// state if the wire has a custom initial state or in the state initialization mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `wire` - The wire to label.
+    /// * `initialization_mode` - The initialization mode of the circuit representation.
+    ///
+    /// # Returns
+    ///
+    /// * `(Option<String>, Option<String>)` - The Typst math of the name and of the initial state.
+    pub(crate) fn wire_start(
+        &self,
+        wire: Wire,
+        initialization_mode: Option<InitializationMode>,
+    ) -> (Option<String>, Option<String>) {
+        let has_label = match wire {
+            Wire::Qubit(index) => self.qubits.contains_key(&index),
+            Wire::Boson(index) => self.modes.contains_key(&index),
+            Wire::Classical(_) => false,
+        };
+        let name = (has_label || initialization_mode == Some(InitializationMode::Qubit))
+            .then(|| self.wire_name(wire));
+        let state = match wire {
+            Wire::Qubit(index) => self
+                .initial_states
+                .get(&index)
+                .map(|state| format_label(state)),
+            _ => None,
+        }
+        .or_else(|| {
+            (initialization_mode != Some(InitializationMode::Qubit)).then(|| "|0>".to_owned())
+        });
+        (name, state)
+    }
+
+    /// Returns the label displayed at the start of a qubit or bosonic mode wire.
+    ///
+    /// # Arguments
+    ///
+    /// * `wire` - The wire to label.
+    /// * `initialization_mode` - The initialization mode of the circuit representation.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The Typst math of the name and the initial state of the wire, separated by a
+    ///   colon.
+    pub(crate) fn wire_label(
+        &self,
+        wire: Wire,
+        initialization_mode: Option<InitializationMode>,
+    ) -> String {
+        match self.wire_start(wire, initialization_mode) {
+            (Some(name), Some(state)) => format!("{name}: {state}"),
+            (Some(label), None) | (None, Some(label)) => label,
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Replaces the names of the classical registers by their custom labels.
+    ///
+    /// # Arguments
+    ///
+    /// * `classical_gates` - The cells of the classical register wires.
+    pub(crate) fn relabel_registers(&self, classical_gates: &mut [Vec<Cell>]) {
+        for cell in classical_gates.iter_mut().flatten() {
+            if let Cell::RegisterName(name) = cell {
+                if let Some(label) = self.registers.get(name) {
+                    *name = label.to_owned();
+                }
+            }
+        }
+    }
+}
+
+/// Formats a custom label as Typst math, writing the words that aren't Typst symbols as text.
+///
+/// # Arguments
+///
+/// * `label` - The label to format.
+///
+/// # Returns
+///
+/// * `String` - The Typst math of the label.
+fn format_label(label: &str) -> String {
+    format_calculator(
+        &CalculatorFloat::Str(label.to_owned()),
+        NumberFormat::default(),
+    )
+}
@@ -0,0 +1,832 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Cell as StateCell;
+
+use roqoqo::Circuit;
+
+use crate::{
+    cells_into_columns, circuit_into_cells, typst_string, Cell, RenderOptions, RoqollageError, Wire,
+};
+
+thread_local! {
+    /// Whether the cells being created write their fractions and symbolic expressions in LaTeX.
+    static LATEX_MATH: StateCell<bool> = const { StateCell::new(false) };
+}
+
+/// Character opening the LaTeX math placed in the Typst content of a cell.
+const LATEX_MATH_START: char = '\u{E000}';
+
+/// Character closing the LaTeX math placed in the Typst content of a cell.
+const LATEX_MATH_END: char = '\u{E001}';
+
+/// Returns whether the cells being created are converted to LaTeX.
+///
+/// The fractions and the symbolic expressions are then written in LaTeX from their structure,
+/// with `latex_math`, instead of being converted from their Typst representation.
+pub(crate) fn latex_math_enabled() -> bool {
+    LATEX_MATH.with(StateCell::get)
+}
+
+/// Wraps LaTeX math to be placed in the Typst content of a cell and copied as is to LaTeX.
+///
+/// # Arguments
+///
+/// * `latex` - The LaTeX math.
+pub(crate) fn latex_math(latex: &str) -> String {
+    format!("{LATEX_MATH_START}{latex}{LATEX_MATH_END}")
+}
+
+/// Runs the creation of cells that are converted to LaTeX afterwards.
+///
+/// # Arguments
+///
+/// * `create` - The creation of the cells.
+fn with_latex_math<T>(create: impl FnOnce() -> T) -> T {
+    /// Restores the previous state when the creation ends, even by unwinding.
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            LATEX_MATH.with(|latex_math| latex_math.set(self.0));
+        }
+    }
+    let _restore = Restore(LATEX_MATH.with(|latex_math| latex_math.replace(true)));
+    create()
+}
+
+/// Returns the LaTeX command of the Typst symbols displayed in the gates.
+///
+/// # Arguments
+///
+/// * `name` - The name of the Typst symbol.
+/// * `variant` - The variant of the Typst symbol, empty for the default one.
+fn latex_symbol(name: &str, variant: &str) -> Option<&'static str> {
+    Some(match (name, variant) {
+        ("epsilon", "alt") => "\\varepsilon",
+        ("theta", "alt") => "\\vartheta",
+        ("kappa", "alt") => "\\varkappa",
+        ("pi", "alt") => "\\varpi",
+        ("rho", "alt") => "\\varrho",
+        ("sigma", "alt") => "\\varsigma",
+        ("phi", "alt") => "\\varphi",
+        ("alpha", _) => "\\alpha",
+        ("beta", _) => "\\beta",
+        ("gamma", _) => "\\gamma",
+        ("delta", _) => "\\delta",
+        ("epsilon", _) => "\\epsilon",
+        ("zeta", _) => "\\zeta",
+        ("eta", _) => "\\eta",
+        ("theta", _) => "\\theta",
+        ("iota", _) => "\\iota",
+        ("kappa", _) => "\\kappa",
+        ("lambda", _) => "\\lambda",
+        ("mu", _) => "\\mu",
+        ("nu", _) => "\\nu",
+        ("xi", _) => "\\xi",
+        ("omicron", _) => "o",
+        ("pi", _) => "\\pi",
+        ("rho", _) => "\\rho",
+        ("sigma", _) => "\\sigma",
+        ("tau", _) => "\\tau",
+        ("upsilon", _) => "\\upsilon",
+        ("phi", _) => "\\phi",
+        ("chi", _) => "\\chi",
+        ("psi", _) => "\\psi",
+        ("omega", _) => "\\omega",
+        ("Gamma", _) => "\\Gamma",
+        ("Delta", _) => "\\Delta",
+        ("Theta", _) => "\\Theta",
+        ("Lambda", _) => "\\Lambda",
+        ("Xi", _) => "\\Xi",
+        ("Pi", _) => "\\Pi",
+        ("Sigma", _) => "\\Sigma",
+        ("Phi", _) => "\\Phi",
+        ("Psi", _) => "\\Psi",
+        ("Omega", _) => "\\Omega",
+        ("dagger", _) => "\\dagger",
+        ("dot", _) => "\\cdot",
+        ("sin", _) => "\\sin",
+        ("cos", _) => "\\cos",
+        ("tan", _) => "\\tan",
+        ("sinh", _) => "\\sinh",
+        ("cosh", _) => "\\cosh",
+        ("tanh", _) => "\\tanh",
+        ("arcsin", _) => "\\arcsin",
+        ("arccos", _) => "\\arccos",
+        ("arctan", _) => "\\arctan",
+        ("ln", _) => "\\ln",
+        ("log", _) => "\\log",
+        ("max", _) => "\\max",
+        ("min", _) => "\\min",
+        ("infinity", _) => "\\infty",
+        ("dots", "v") => "\\vdots",
+        ("dots", "down") => "\\ddots",
+        ("dots", _) => "\\dots",
+        _ => return None,
+    })
+}
+
+/// Escapes the characters having a special meaning in LaTeX text.
+///
+/// # Arguments
+///
+/// * `text` - The text to escape.
+///
+/// # Returns
+///
+/// * `String` - The text, usable in LaTeX text mode.
+pub(crate) fn escape_latex_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\n' => escaped.push(' '),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Returns a LaTeX command, followed by a space if a letter follows it in the Typst content.
+///
+/// # Arguments
+///
+/// * `name` - The LaTeX command.
+/// * `chars` - The characters of the Typst math content.
+/// * `position` - The position following the command in the Typst math content.
+fn command(name: &str, chars: &[char], position: usize) -> String {
+    if chars
+        .get(position)
+        .is_some_and(|next| next.is_ascii_alphabetic())
+    {
+        format!("{name} ")
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Converts a Typst identifier, starting at the current position, to LaTeX.
+///
+/// # Arguments
+///
+/// * `chars` - The characters of the Typst math content.
+/// * `position` - The position of the first letter of the identifier, moved after it.
+fn convert_identifier(chars: &[char], position: &mut usize) -> String {
+    let mut word = String::new();
+    while let Some(&character) = chars.get(*position) {
+        let continues_variant = character == '.'
+            && chars
+                .get(*position + 1)
+                .is_some_and(|next| next.is_ascii_alphabetic());
+        if character.is_ascii_alphabetic() || continues_variant {
+            word.push(character);
+            *position += 1;
+        } else {
+            break;
+        }
+    }
+    let (name, variant) = word.split_once('.').unwrap_or((word.as_str(), ""));
+    if name == "sqrt" && chars.get(*position) == Some(&'(') {
+        *position += 1;
+        return format!("\\sqrt{{{}}}", convert_math(chars, position, Some(')')));
+    }
+    if name == "mat" && chars.get(*position) == Some(&'(') {
+        *position += 1;
+        return convert_matrix(chars, position);
+    }
+    match latex_symbol(name, variant) {
+        Some(symbol) => command(symbol, chars, *position),
+        None if name.chars().count() == 1 => name.to_owned(),
+        None => format!("\\mathrm{{{name}}}"),
+    }
+}
+
+/// Converts the rows of a Typst matrix, separated by `;`, to a LaTeX `pmatrix`.
+///
+/// # Arguments
+///
+/// * `chars` - The characters of the Typst math content.
+/// * `position` - The position following the opening parenthesis, moved after the closing one.
+fn convert_matrix(chars: &[char], position: &mut usize) -> String {
+    let mut rows: Vec<Vec<String>> = vec![Vec::new()];
+    let mut entry_start = *position;
+    let mut depth = 0;
+    let mut in_string = false;
+    while let Some(&character) = chars.get(*position) {
+        *position += 1;
+        match character {
+            '\\' => *position += 1,
+            '"' => in_string = !in_string,
+            _ if in_string => (),
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            ',' | ';' | ')' if depth == 0 => {
+                let mut entry_position = 0;
+                let entry = convert_math(
+                    &chars[entry_start..*position - 1],
+                    &mut entry_position,
+                    None,
+                );
+                if let Some(row) = rows.last_mut() {
+                    row.push(entry.trim().to_owned());
+                }
+                entry_start = *position;
+                match character {
+                    ';' => rows.push(Vec::new()),
+                    ')' => break,
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+    }
+    format!(
+        "\\begin{{pmatrix}} {} \\end{{pmatrix}}",
+        rows.iter()
+            .map(|row| row.join(" & "))
+            .collect::<Vec<String>>()
+            .join(" \\\\ ")
+    )
+}
+
+/// Copies the LaTeX math placed in the Typst content by `latex_math`.
+///
+/// # Arguments
+///
+/// * `chars` - The characters of the Typst math content.
+/// * `position` - The position following the opening character, moved after the closing one.
+fn copy_latex_math(chars: &[char], position: &mut usize) -> String {
+    let mut latex = String::new();
+    while let Some(&character) = chars.get(*position) {
+        *position += 1;
+        if character == LATEX_MATH_END {
+            break;
+        }
+        latex.push(character);
+    }
+    latex
+}
+
+/// Converts the superscript or subscript starting at the current position to LaTeX.
+///
+/// # Arguments
+///
+/// * `chars` - The characters of the Typst math content.
+/// * `position` - The position following the `^` or `_`, moved after the attachment.
+fn convert_attachment(chars: &[char], position: &mut usize) -> String {
+    match chars.get(*position) {
+        Some('(') => {
+            *position += 1;
+            convert_math(chars, position, Some(')'))
+        }
+        Some(&LATEX_MATH_START) => {
+            *position += 1;
+            copy_latex_math(chars, position)
+        }
+        Some(character) if character.is_ascii_alphabetic() => convert_identifier(chars, position),
+        Some(character) if character.is_ascii_digit() => {
+            let mut number = String::new();
+            while let Some(&digit) = chars.get(*position).filter(|digit| digit.is_ascii_digit()) {
+                number.push(digit);
+                *position += 1;
+            }
+            number
+        }
+        Some(&character) => {
+            *position += 1;
+            escape_latex_text(&character.to_string())
+        }
+        None => String::new(),
+    }
+}
+
+/// Converts Typst math content to LaTeX until the closing character, or the end of the content.
+///
+/// # Arguments
+///
+/// * `chars` - The characters of the Typst math content.
+/// * `position` - The position to start at, moved after the closing character.
+/// * `closing` - The character closing the current group, if any.
+fn convert_math(chars: &[char], position: &mut usize, closing: Option<char>) -> String {
+    let mut latex = String::new();
+    while let Some(&character) = chars.get(*position) {
+        *position += 1;
+        match character {
+            character if Some(character) == closing => break,
+            '"' => {
+                let mut text = String::new();
+                while let Some(&character) = chars.get(*position) {
+                    *position += 1;
+                    match character {
+                        '"' => break,
+                        '\\' => {
+                            match chars.get(*position) {
+                                Some('n') => text.push(' '),
+                                Some(&escaped) => text.push(escaped),
+                                None => (),
+                            }
+                            *position += 1;
+                        }
+                        character => text.push(character),
+                    }
+                }
+                latex.push_str(&format!("\\text{{{}}}", escape_latex_text(&text)));
+            }
+            '\\' => {
+                match chars.get(*position) {
+                    Some(' ') => latex.push_str("\\ "),
+                    Some(&escaped) => latex.push_str(&escape_latex_text(&escaped.to_string())),
+                    None => (),
+                }
+                *position += 1;
+            }
+            '^' | '_' => {
+                let attachment = convert_attachment(chars, position);
+                latex.push_str(&format!("{character}{{{}}}", attachment.trim()));
+            }
+            '(' => {
+                latex.push('(');
+                latex.push_str(&convert_math(chars, position, Some(')')));
+                latex.push(')');
+            }
+            '*' => latex.push_str(&command("\\cdot", chars, *position)),
+            '=' if chars.get(*position) == Some(&'>') => {
+                *position += 1;
+                latex.push_str(&command("\\Rightarrow", chars, *position));
+            }
+            '-' if chars.get(*position) == Some(&'>') => {
+                *position += 1;
+                latex.push_str(&command("\\rightarrow", chars, *position));
+            }
+            LATEX_MATH_START => latex.push_str(&copy_latex_math(chars, position)),
+            '#' => (),
+            '°' => latex.push_str("^{\\circ}"),
+            character if character.is_ascii_alphabetic() => {
+                *position -= 1;
+                latex.push_str(&convert_identifier(chars, position));
+            }
+            '&' | '%' | '$' | '{' | '}' | '~' => {
+                latex.push_str(&escape_latex_text(&character.to_string()))
+            }
+            '\n' => latex.push(' '),
+            character => latex.push(character),
+        }
+    }
+    latex
+}
+
+/// Converts the Typst math content of a cell to LaTeX math.
+///
+/// The Typst strings are placed in `\text`, the symbols are replaced by their LaTeX commands and
+/// the attachments and square roots are given their LaTeX groups.
+///
+/// # Arguments
+///
+/// * `content` - The Typst math content.
+///
+/// # Returns
+///
+/// * `String` - The LaTeX math representation of the content.
+pub(crate) fn typst_math_to_latex(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut position = 0;
+    convert_math(&chars, &mut position, None).trim().to_owned()
+}
+
+/// Converts the Typst math of the initial state of a wire to LaTeX math, writing kets with `\ket`.
+///
+/// # Arguments
+///
+/// * `state` - The Typst math of the initial state.
+///
+/// # Returns
+///
+/// * `String` - The LaTeX math representation of the initial state.
+fn state_to_latex(state: &str) -> String {
+    match state
+        .strip_prefix('|')
+        .and_then(|state| state.strip_suffix('>'))
+    {
+        Some(content) => format!("\\ket{{{}}}", typst_math_to_latex(content)),
+        None => typst_math_to_latex(state),
+    }
+}
+
+/// Creates an invisible group of a single cell displaying a label above or below the cell.
+///
+/// # Arguments
+///
+/// * `label` - The LaTeX text of the label.
+/// * `below` - Whether to display the label below the cell.
+fn quantikz_label(label: &str, below: bool) -> String {
+    format!(
+        "\\gategroup[1,steps=1,style={{draw=none}}{}]{{{label}}}",
+        if below {
+            ",label style={label position=below,anchor=north,yshift=-0.2cm}"
+        } else {
+            Default::default()
+        },
+    )
+}
+
+impl Cell {
+    /// Translates the cell to its LaTeX (quantikz) representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `wire` - The wire the cell is placed on.
+    /// * `n_qubits` - The number of qubit wires.
+    /// * `n_bosons` - The number of bosonic mode wires.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The quantikz representation of the cell.
+    pub fn to_quantikz(&self, wire: Wire, n_qubits: usize, n_bosons: usize) -> String {
+        let offset = |target: &Wire| {
+            target.row(n_qubits, n_bosons) as i64 - wire.row(n_qubits, n_bosons) as i64
+        };
+        let link = |target: &Wire, wire_type: &str| {
+            let offset = offset(target);
+            format!(
+                "\\wire[{}][{}]{{{wire_type}}}",
+                if offset < 0 { "u" } else { "d" },
+                offset.abs(),
+            )
+        };
+        match self {
+            Cell::Idle => String::new(),
+            Cell::Gate {
+                content,
+                label,
+                fill,
+            } => format!(
+                "\\gate{}{{{}}}{}",
+                fill.as_ref()
+                    .map(|fill| format!("[style={{fill={fill}!20}}]"))
+                    .unwrap_or_default(),
+                typst_math_to_latex(content),
+                label
+                    .as_ref()
+                    .map(|label| format!(" {}", quantikz_label(&escape_latex_text(label), false)))
+                    .unwrap_or_default(),
+            ),
+            Cell::MultiGate {
+                content,
+                n_wires,
+                fill,
+                ..
+            } => format!(
+                "\\gate[{n_wires}{}]{{{}}}",
+                fill.as_ref()
+                    .map(|fill| format!(",style={{fill={fill}!20}}"))
+                    .unwrap_or_default(),
+                typst_math_to_latex(content),
+            ),
+            Cell::LinkedGate {
+                content, target, ..
+            } => format!(
+                "\\gate{{{}}} {}",
+                typst_math_to_latex(content),
+                link(target, "q"),
+            ),
+            Cell::Control { target, label } => format!(
+                "{}{}",
+                target
+                    .as_ref()
+                    .map(|target| format!("\\ctrl{{{}}}", offset(target)))
+                    .unwrap_or_else(|| "\\control{}".to_owned()),
+                label
+                    .as_ref()
+                    .map(|label| format!(
+                        " {}",
+                        quantikz_label(&format!("${}$", typst_math_to_latex(label)), true)
+                    ))
+                    .unwrap_or_default(),
+            ),
+            Cell::Target => "\\targ{}".to_owned(),
+            Cell::Swap { target, label } => format!(
+                "{}{}",
+                target
+                    .as_ref()
+                    .map(|target| format!("\\swap{{{}}}", offset(target)))
+                    .unwrap_or_else(|| "\\targX{}".to_owned()),
+                label
+                    .as_ref()
+                    .map(|label| format!(
+                        " {}",
+                        quantikz_label(&format!("${}$", typst_math_to_latex(label)), false)
+                    ))
+                    .unwrap_or_default(),
+            ),
+            Cell::Meter { target } => format!(
+                "\\meter{{}}{}",
+                target
+                    .as_ref()
+                    .map(|target| format!(" {}", link(target, "c")))
+                    .unwrap_or_default(),
+            ),
+            Cell::Group {
+                n_wires,
+                length,
+                label,
+            } => format!(
+                "\\gategroup[{n_wires},steps={length},style={{dotted,rounded corners,inner xsep=2pt}}]{{{}}}",
+                escape_latex_text(label),
+            ),
+            Cell::Slice { label, dash } => format!(
+                "\\slice{}{{${}$}}",
+                dash.as_ref()
+                    .map(|dash| format!(
+                        "[style={{{}}}]",
+                        match dash.as_str() {
+                            "solid" => "solid",
+                            "densely-dash-dotted" => "densely dashdotted",
+                            "dotted" => "dotted",
+                            _ => "dashed",
+                        }
+                    ))
+                    .unwrap_or_default(),
+                typst_math_to_latex(label),
+            ),
+            Cell::RegisterName(name) => {
+                format!("\\lstick{{{}}}", escape_latex_text(&format!("{name} : ")))
+            }
+            Cell::WireLines(n_lines) => format!(
+                "\\setwiretype{{{}}}",
+                if *n_lines > 1 { "c" } else { "q" }
+            ),
+            Cell::Highlighted { cell, .. } | Cell::Traced { cell, .. } => {
+                cell.to_quantikz(wire, n_qubits, n_bosons)
+            }
+        }
+    }
+}
+
+/// Replaces the groups around a single column of identity gates by slices.
+///
+/// The readout pragmas without a circuit, like `PragmaGetStateVector`, are drawn as a group around
+/// an identity gate on each qubit. In quantikz they are drawn as a `\slice` instead.
+///
+/// # Arguments
+///
+/// * `columns` - The cell of each row in each column.
+/// * `boundaries` - The cells drawn on each boundary between the columns.
+fn readout_groups_into_slices(columns: &mut [Vec<Cell>], boundaries: &mut [Vec<(usize, Cell)>]) {
+    for (column, boundary) in columns.iter_mut().zip(boundaries.iter_mut()) {
+        for (row, cell) in boundary.iter_mut() {
+            let Cell::Group {
+                n_wires,
+                length: 1,
+                label,
+            } = cell.unhighlighted()
+            else {
+                continue;
+            };
+            let label = typst_string(label);
+            let Some(identities) = column.get_mut(*row..*row + n_wires) else {
+                continue;
+            };
+            if identities.iter().all(|identity| {
+                matches!(identity.unhighlighted(), Cell::Gate { content, .. } if content == "I")
+            }) {
+                identities.fill(Cell::Idle);
+                *cell = Cell::Slice { label, dash: None };
+            }
+        }
+    }
+}
+
+/// Splits the columns of a circuit in chunks of at least `max_length` columns.
+///
+/// A chunk is only ended on a boundary which isn't crossed by a group.
+///
+/// # Arguments
+///
+/// * `boundaries` - The cells drawn on each boundary between the columns.
+/// * `max_length` - The maximum number of columns of a chunk, the circuit isn't split if None.
+///
+/// # Returns
+///
+/// * `Vec<(usize, usize)>` - The first column and the column following the last one of each chunk.
+fn split_columns(
+    boundaries: &[Vec<(usize, Cell)>],
+    max_length: Option<usize>,
+) -> Vec<(usize, usize)> {
+    let n_columns = boundaries.len() - 1;
+    let groups: Vec<(usize, usize)> = boundaries
+        .iter()
+        .enumerate()
+        .flat_map(|(index, boundary)| {
+            boundary.iter().filter_map(move |(_, cell)| match cell {
+                Cell::Group { length, .. } => Some((index, index + length)),
+                _ => None,
+            })
+        })
+        .collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    if let Some(max_length) = max_length {
+        for boundary in 1..n_columns {
+            if boundary - start >= max_length.max(1)
+                && !groups
+                    .iter()
+                    .any(|(first, end)| *first < boundary && boundary < *end)
+            {
+                chunks.push((start, boundary));
+                start = boundary;
+            }
+        }
+    }
+    chunks.push((start, n_columns));
+    chunks
+}
+
+/// Converts a qoqo circuit to a LaTeX string, drawn with the quantikz package.
+///
+/// The circuit is laid out in the same way as its Typst representation. The returned string
+/// contains one `quantikz` environment per chunk of the circuit and needs the `quantikz2` TikZ
+/// library (`\usepackage{tikz}` and `\usetikzlibrary{quantikz2}`) to be compiled.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `options` - The options of the circuit representation. A circuit line longer than the maximum
+///   length is split into multiple quantikz environments. The page, device and statistics options
+///   are not used.
+///
+/// ## Returns
+///
+/// * `String` - The LaTeX representation of the circuit.
+pub fn circuit_into_quantikz_str(
+    circuit: &Circuit,
+    options: &RenderOptions,
+) -> Result<String, RoqollageError> {
+    let (circuit_gates, bosonic_gates, mut classical_gates) = with_latex_math(|| {
+        circuit_into_cells(
+            circuit,
+            &options.render_pragmas,
+            options.number_format,
+            options.layout_mode,
+        )
+    })?;
+    let wire_labels = &options.wire_labels;
+    wire_labels.relabel_registers(&mut classical_gates);
+    let n_qubits = circuit_gates.len();
+    let n_bosons = bosonic_gates.len();
+    let wires: Vec<Wire> = (0..n_qubits)
+        .map(Wire::Qubit)
+        .chain((0..n_bosons).map(Wire::Boson))
+        .chain((0..classical_gates.len()).map(Wire::Classical))
+        .collect();
+    let rows: Vec<Vec<Cell>> = circuit_gates
+        .into_iter()
+        .chain(bosonic_gates)
+        .chain(classical_gates)
+        .collect();
+    let n_rows = rows.len();
+    let (mut columns, mut boundaries) = cells_into_columns(rows);
+    readout_groups_into_slices(&mut columns, &mut boundaries);
+    let n_columns = columns.len();
+    let chunks = split_columns(&boundaries, options.max_length);
+    let n_chunks = chunks.len();
+
+    let mut latex_str = String::new();
+    let mut register_names: Vec<Option<String>> = vec![None; n_rows];
+    let mut wire_lines = vec![1_usize; n_rows];
+    for (chunk_number, (start, end)) in chunks.into_iter().enumerate() {
+        let is_first = chunk_number == 0;
+        let is_last = chunk_number == n_chunks - 1;
+        // The first cell of each row holds the label of the wire, the last one ends the wire.
+        let mut grid: Vec<Vec<String>> = vec![vec![String::new(); end - start + 2]; n_rows];
+        let mut lsticks: Vec<String> = wires
+            .iter()
+            .enumerate()
+            .map(|(row, wire)| match (wire, is_first) {
+                (Wire::Qubit(_) | Wire::Boson(_), true) => {
+                    let (name, state) = wire_labels.wire_start(*wire, options.initialization_mode);
+                    format!(
+                        "\\lstick{{${}$}}",
+                        name.map(|name| typst_math_to_latex(&name))
+                            .into_iter()
+                            .chain(state.map(|state| state_to_latex(&state)))
+                            .collect::<Vec<String>>()
+                            .join(": ")
+                    )
+                }
+                (Wire::Qubit(_) | Wire::Boson(_), false) => format!(
+                    "\\lstick{{$\\cdots {}$}}",
+                    typst_math_to_latex(&wire_labels.wire_name(*wire))
+                ),
+                (Wire::Classical(_), _) => register_names[row]
+                    .as_ref()
+                    .map(|name| {
+                        Cell::RegisterName(name.to_owned()).to_quantikz(*wire, n_qubits, n_bosons)
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect();
+        if !is_first {
+            for (row, &n_lines) in wire_lines.iter().enumerate() {
+                if n_lines > 1 {
+                    grid[row][0] =
+                        Cell::WireLines(n_lines).to_quantikz(wires[row], n_qubits, n_bosons);
+                }
+            }
+        }
+
+        let last_boundary = if is_last { n_columns } else { end - 1 };
+        for (index, boundary) in boundaries
+            .iter()
+            .enumerate()
+            .take(last_boundary + 1)
+            .skip(start)
+        {
+            for (row, cell) in boundary {
+                let wire = wires[*row];
+                let cell_index = match cell {
+                    Cell::RegisterName(name) => {
+                        register_names[*row] = Some(name.to_owned());
+                        lsticks[*row] = cell.to_quantikz(wire, n_qubits, n_bosons);
+                        continue;
+                    }
+                    Cell::WireLines(n_lines) => {
+                        wire_lines[*row] = *n_lines;
+                        index - start
+                    }
+                    Cell::Group { length: 0, .. } => continue,
+                    Cell::Group { .. } => index - start + 1,
+                    _ => index - start,
+                };
+                let command = cell.to_quantikz(wire, n_qubits, n_bosons);
+                let slot = &mut grid[*row][cell_index];
+                if !slot.is_empty() {
+                    slot.push(' ');
+                }
+                slot.push_str(&command);
+            }
+        }
+        for (column_index, column) in columns.iter().enumerate().take(end).skip(start) {
+            for (row, cell) in column.iter().enumerate() {
+                let command = cell.to_quantikz(wires[row], n_qubits, n_bosons);
+                if !command.is_empty() {
+                    let slot = &mut grid[row][column_index - start + 1];
+                    // Commands placed on the boundary following the column are kept after it.
+                    *slot = if slot.is_empty() {
+                        command
+                    } else {
+                        format!("{command} {slot}")
+                    };
+                }
+            }
+        }
+        if !is_last {
+            for (row, wire) in wires.iter().enumerate() {
+                if !matches!(wire, Wire::Classical(_)) {
+                    grid[row][end - start + 1] = "\\rstick{$\\cdots$}".to_owned();
+                }
+            }
+        }
+
+        latex_str.push_str("\\begin{quantikz}\n");
+        let lines: Vec<String> = grid
+            .into_iter()
+            .zip(lsticks)
+            .map(|(cells, lstick)| {
+                let mut line = match (lstick.is_empty(), cells[0].is_empty()) {
+                    (true, _) => cells[0].clone(),
+                    (false, true) => lstick,
+                    (false, false) => format!("{lstick} {}", cells[0]),
+                };
+                for cell in cells.iter().skip(1) {
+                    line.push_str(" &");
+                    if !cell.is_empty() {
+                        line.push(' ');
+                        line.push_str(cell);
+                    }
+                }
+                line
+            })
+            .collect();
+        if !lines.is_empty() {
+            latex_str.push_str(&lines.join(" \\\\\n"));
+            latex_str.push('\n');
+        }
+        latex_str.push_str("\\end{quantikz}\n");
+    }
+    Ok(latex_str)
+}
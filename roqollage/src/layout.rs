@@ -0,0 +1,969 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, str::FromStr};
+
+use roqoqo::Circuit;
+
+use crate::{add_gate, flatten_multiple_vec, NumberFormat, RenderPragmas, RoqollageError};
+
+/// Minimum number of columns between two labelled slices or groups in the compact layouts.
+const LABEL_SPACING: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How to place the operations in the columns of the circuit representation.
+pub enum LayoutMode {
+    /// Operations are added one after the other, in the order of the circuit.
+    #[default]
+    Sequential,
+    /// Operations are placed in the earliest column free on all the wires they cross.
+    Asap,
+    /// Operations are placed in the latest column free on all the wires they cross.
+    Alap,
+}
+
+impl FromStr for LayoutMode {
+    type Err = RoqollageError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sequential" => Ok(LayoutMode::Sequential),
+            "asap" => Ok(LayoutMode::Asap),
+            "alap" => Ok(LayoutMode::Alap),
+            _ => Err(RoqollageError::InvalidOption {
+                msg: format!(r#"Invalid layout mode: {s}, use `sequential`, `asap` or `alap`."#),
+            }),
+        }
+    }
+}
+
+/// Wire of the circuit representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Wire {
+    /// Qubit wire.
+    Qubit(usize),
+    /// Bosonic mode wire, displayed below the qubit wires.
+    Boson(usize),
+    /// Classical register wire, displayed below the bosonic mode wires.
+    Classical(usize),
+}
+
+impl Wire {
+    /// Returns the row of the wire in the circuit representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `n_qubits` - The number of qubit wires.
+    /// * `n_bosons` - The number of bosonic mode wires.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The index of the row of the wire.
+    pub fn row(&self, n_qubits: usize, n_bosons: usize) -> usize {
+        match self {
+            Wire::Qubit(qubit) => *qubit,
+            Wire::Boson(mode) => n_qubits + mode,
+            Wire::Classical(index) => n_qubits + n_bosons + index,
+        }
+    }
+}
+
+/// Cell of the circuit representation.
+///
+/// Each operation is lowered into cells placed on the wires it acts on.
+/// The cells are only translated to Typst once the whole circuit has been laid out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    /// Empty column of a wire.
+    Idle,
+    /// Gate acting on a single wire.
+    Gate {
+        /// Math content displayed in the gate.
+        content: String,
+        /// Text displayed above the gate.
+        label: Option<String>,
+        /// Fill color of the gate.
+        fill: Option<String>,
+    },
+    /// Gate spanning several consecutive wires, starting on the wire of the cell.
+    MultiGate {
+        /// Math content displayed in the gate.
+        content: String,
+        /// Number of wires spanned by the gate.
+        n_wires: usize,
+        /// Width of the gate.
+        width: Option<String>,
+        /// Fill color of the gate.
+        fill: Option<String>,
+        /// Offsets of the wires the gate acts on, relative to the first wire of the gate.
+        inputs: Vec<usize>,
+    },
+    /// Gate linked to a gate on another wire.
+    LinkedGate {
+        /// Math content displayed in the gate.
+        content: String,
+        /// Wire of the linked gate.
+        target: Wire,
+        /// Extent of the gate around its content.
+        extent: Option<String>,
+    },
+    /// Control dot, linked to the target wire if any.
+    Control {
+        /// Wire of the controlled gate.
+        target: Option<Wire>,
+        /// Math content displayed below the dot.
+        label: Option<String>,
+    },
+    /// Target of a controlled Pauli X.
+    Target,
+    /// Swap cross, linked to the target wire if any.
+    Swap {
+        /// Wire of the other swap cross.
+        target: Option<Wire>,
+        /// Math content displayed above the swap.
+        label: Option<String>,
+    },
+    /// Measurement meter, linked to the classical wire the result is stored in if any.
+    Meter {
+        /// Wire of the classical register.
+        target: Option<Wire>,
+    },
+    /// Box around the next `length` columns of `n_wires` wires. Doesn't take a column.
+    Group {
+        /// Number of wires surrounded by the box.
+        n_wires: usize,
+        /// Number of columns surrounded by the box.
+        length: usize,
+        /// Text displayed above the box.
+        label: String,
+    },
+    /// Vertical line across the wires. Doesn't take a column.
+    Slice {
+        /// Math content displayed above the line.
+        label: String,
+        /// Dash pattern of the line, the default one if None.
+        dash: Option<String>,
+    },
+    /// Name of a classical register, at the start of its wire. Doesn't take a column.
+    RegisterName(String),
+    /// Number of lines of the wire from this column on. Doesn't take a column.
+    WireLines(usize),
+    /// Cell filled with a highlight color, e.g. to flag an operation a device doesn't support.
+    Highlighted {
+        /// The highlighted cell.
+        cell: Box<Cell>,
+        /// Fill color of the highlight.
+        fill: String,
+    },
+    /// Cell added by an operation, used to locate the errors of the Typst compiler.
+    Traced {
+        /// The cell added by the operation.
+        cell: Box<Cell>,
+        /// Index of the operation in the circuit.
+        index: usize,
+    },
+}
+
+impl Cell {
+    /// Creates a single wire gate.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The math content displayed in the gate.
+    pub fn gate(content: impl Into<String>) -> Self {
+        Cell::Gate {
+            content: content.into(),
+            label: None,
+            fill: None,
+        }
+    }
+
+    /// Creates a single wire gate with a text displayed above it.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The math content displayed in the gate.
+    /// * `label` - The text displayed above the gate.
+    pub fn labelled_gate(content: impl Into<String>, label: impl Into<String>) -> Self {
+        Cell::Gate {
+            content: content.into(),
+            label: Some(label.into()),
+            fill: None,
+        }
+    }
+
+    /// Creates a single wire gate representing a pragma operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The math content displayed in the gate.
+    pub fn pragma_gate(content: impl Into<String>) -> Self {
+        Cell::Gate {
+            content: content.into(),
+            label: None,
+            fill: Some("gray".to_owned()),
+        }
+    }
+
+    /// Creates a control dot linked to the target wire.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The wire of the controlled gate.
+    pub fn control(target: Wire) -> Self {
+        Cell::Control {
+            target: Some(target),
+            label: None,
+        }
+    }
+
+    /// Returns whether the cell takes a column on its wire.
+    ///
+    /// Groups, slices, register names and wire changes are drawn on top of the other cells.
+    pub fn takes_column(&self) -> bool {
+        match self {
+            Cell::Traced { cell, .. } => cell.takes_column(),
+            cell => !matches!(
+                cell,
+                Cell::Group { .. }
+                    | Cell::Slice { .. }
+                    | Cell::RegisterName(_)
+                    | Cell::WireLines(_)
+            ),
+        }
+    }
+
+    /// Returns the cell without its highlight and its operation index.
+    pub(crate) fn unhighlighted(&self) -> &Cell {
+        match self {
+            Cell::Highlighted { cell, .. } | Cell::Traced { cell, .. } => cell.unhighlighted(),
+            cell => cell,
+        }
+    }
+
+    /// Returns the cell without its operation index.
+    pub(crate) fn untraced(self) -> Cell {
+        match self {
+            Cell::Traced { cell, .. } => cell.untraced(),
+            cell => cell,
+        }
+    }
+
+    /// Returns the first and last rows crossed by the cell.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row of the wire the cell is placed on.
+    /// * `n_qubits` - The number of qubit wires.
+    /// * `n_bosons` - The number of bosonic mode wires.
+    ///
+    /// # Returns
+    ///
+    /// * `(usize, usize)` - The first and last rows crossed by the cell.
+    pub(crate) fn row_span(&self, row: usize, n_qubits: usize, n_bosons: usize) -> (usize, usize) {
+        let linked_row = match self {
+            Cell::MultiGate { n_wires, .. } | Cell::Group { n_wires, .. } => {
+                Some(row + (*n_wires).max(1) - 1)
+            }
+            Cell::LinkedGate { target, .. } => Some(target.row(n_qubits, n_bosons)),
+            Cell::Control { target, .. } | Cell::Swap { target, .. } | Cell::Meter { target } => {
+                target.map(|target| target.row(n_qubits, n_bosons))
+            }
+            Cell::Highlighted { cell, .. } | Cell::Traced { cell, .. } => {
+                return cell.row_span(row, n_qubits, n_bosons)
+            }
+            _ => None,
+        }
+        .unwrap_or(row);
+        (row.min(linked_row), row.max(linked_row))
+    }
+
+    /// Translates the cell to its Typst (quill) representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `wire` - The wire the cell is placed on.
+    /// * `n_qubits` - The number of qubit wires.
+    /// * `n_bosons` - The number of bosonic mode wires.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The Typst representation of the cell.
+    pub fn to_typst(&self, wire: Wire, n_qubits: usize, n_bosons: usize) -> String {
+        self.to_filled_typst(wire, n_qubits, n_bosons, None)
+    }
+
+    /// Translates the cell to its Typst (quill) representation, filled with the highlight color
+    /// if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `wire` - The wire the cell is placed on.
+    /// * `n_qubits` - The number of qubit wires.
+    /// * `n_bosons` - The number of bosonic mode wires.
+    /// * `highlight` - The fill color of the highlight, replacing the fill of the cell.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The Typst representation of the cell.
+    fn to_filled_typst(
+        &self,
+        wire: Wire,
+        n_qubits: usize,
+        n_bosons: usize,
+        highlight: Option<&str>,
+    ) -> String {
+        let fill_argument = highlight
+            .map(|fill| format!(", fill: {fill}"))
+            .unwrap_or_default();
+        let offset = |target: &Wire| {
+            target.row(n_qubits, n_bosons) as i64 - wire.row(n_qubits, n_bosons) as i64
+        };
+        match self {
+            Cell::Idle => "1".to_owned(),
+            Cell::Gate {
+                content,
+                label,
+                fill,
+            } => {
+                let fill = highlight.or(fill.as_deref());
+                if label.is_none() && fill.is_none() {
+                    format!("$ {content} $")
+                } else {
+                    format!(
+                        "gate($ {content} ${}{})",
+                        label
+                            .as_ref()
+                            .map(|label| format!(", label: {}", typst_string(label)))
+                            .unwrap_or_default(),
+                        fill.map(|fill| format!(", fill: {fill}"))
+                            .unwrap_or_default(),
+                    )
+                }
+            }
+            Cell::MultiGate {
+                content,
+                n_wires,
+                width,
+                fill,
+                inputs,
+            } => format!(
+                "mqgate($ {content} $, n: {n_wires}{}{}, inputs: ({}))",
+                width
+                    .as_ref()
+                    .map(|width| format!(", width: {width}"))
+                    .unwrap_or_default(),
+                highlight
+                    .or(fill.as_deref())
+                    .map(|fill| format!(", fill: {fill}"))
+                    .unwrap_or_default(),
+                inputs
+                    .iter()
+                    .map(|input| format!(r#"(qubit: {input}, label: "x")"#))
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ),
+            Cell::LinkedGate {
+                content,
+                target,
+                extent,
+            } => format!(
+                "mqgate($ {content} ${}, target: {}{fill_argument})",
+                extent
+                    .as_ref()
+                    .map(|extent| format!(", extent: {extent}"))
+                    .unwrap_or_default(),
+                offset(target),
+            ),
+            Cell::Control { target, label } => format!(
+                "ctrl({}{}{fill_argument})",
+                target.as_ref().map(offset).unwrap_or(0),
+                label
+                    .as_ref()
+                    .map(|label| format!(", label: (content: $ {label} $, pos: bottom)"))
+                    .unwrap_or_default(),
+            ),
+            Cell::Target => match highlight {
+                Some(fill) => format!("targ(fill: {fill})"),
+                None => "targ()".to_owned(),
+            },
+            Cell::Swap { target, label } => format!(
+                "swap({}{})",
+                target.as_ref().map(offset).unwrap_or(0),
+                label
+                    .as_ref()
+                    .map(|label| format!(", label: $ {label} $"))
+                    .unwrap_or_default(),
+            ),
+            Cell::Meter { target } => {
+                let arguments = target
+                    .as_ref()
+                    .map(|target| format!("target: {}", offset(target)))
+                    .into_iter()
+                    .chain(highlight.map(|fill| format!("fill: {fill}")))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!("meter({arguments})")
+            }
+            Cell::Group {
+                n_wires,
+                length,
+                label,
+            } => format!(
+                r#"gategroup({n_wires}, {length}, label: {}, stroke: (dash: "dotted"))"#,
+                typst_string(label),
+            ),
+            Cell::Slice { label, dash } => format!(
+                "slice(label: $ {label} ${})",
+                dash.as_ref()
+                    .map(|dash| format!(
+                        r#", stroke: (paint: black, thickness: 1pt, dash: "{dash}")"#
+                    ))
+                    .unwrap_or_default(),
+            ),
+            Cell::RegisterName(name) => {
+                format!("lstick($ {} $)", typst_string(&format!("{name} : ")))
+            }
+            Cell::WireLines(n_lines) => format!("setwire({n_lines})"),
+            Cell::Highlighted { cell, fill } => {
+                cell.to_filled_typst(wire, n_qubits, n_bosons, Some(fill))
+            }
+            Cell::Traced { cell, .. } => cell.to_filled_typst(wire, n_qubits, n_bosons, highlight),
+        }
+    }
+}
+
+/// Formats a text as a Typst string literal.
+///
+/// # Arguments
+///
+/// * `text` - The text to format.
+///
+/// # Returns
+///
+/// * `String` - The quoted and escaped text.
+pub(crate) fn typst_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Lays out the operations of a circuit in a grid of cells.
+///
+/// The operations are added one after the other with `add_gate`, all the wires are padded to the
+/// same length and the cells are then placed according to the layout mode.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit to lay out.
+/// * `render_pragmas` - Whether to render Pragma Operations or not.
+/// * `number_format` - How the numbers are displayed.
+/// * `layout_mode` - How to place the operations in the columns.
+///
+/// # Returns
+///
+/// * `Ok((Vec<Vec<Cell>>, Vec<Vec<Cell>>, Vec<Vec<Cell>>))` - The cells of the qubit, bosonic mode
+///   and classical register wires.
+/// * `Err(RoqollageError)` - Operation not supported or not valid, with its index in the circuit.
+#[allow(clippy::type_complexity)]
+pub fn circuit_into_cells(
+    circuit: &Circuit,
+    render_pragmas: &RenderPragmas,
+    number_format: NumberFormat,
+    layout_mode: LayoutMode,
+) -> Result<(Vec<Vec<Cell>>, Vec<Vec<Cell>>, Vec<Vec<Cell>>), RoqollageError> {
+    let (circuit_gates, bosonic_gates, classical_gates) = circuit_into_highlighted_cells(
+        circuit,
+        render_pragmas,
+        number_format,
+        layout_mode,
+        &HashMap::new(),
+        None,
+    )?;
+    let untraced = |wires: Vec<Vec<Cell>>| -> Vec<Vec<Cell>> {
+        wires
+            .into_iter()
+            .map(|cells| cells.into_iter().map(Cell::untraced).collect())
+            .collect()
+    };
+    Ok((
+        untraced(circuit_gates),
+        untraced(bosonic_gates),
+        untraced(classical_gates),
+    ))
+}
+
+/// Lays out the operations of a circuit in a grid of cells, highlighting some operations.
+///
+/// The cells added by a highlighted operation on the qubit and bosonic mode wires are wrapped in
+/// `Cell::Highlighted` before the layout mode is applied. The gates and slices added by each
+/// operation are then wrapped in `Cell::Traced` with the index of the operation.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit to lay out.
+/// * `render_pragmas` - Whether to render Pragma Operations or not.
+/// * `number_format` - How the numbers are displayed.
+/// * `layout_mode` - How to place the operations in the columns.
+/// * `highlights` - The fill color of the highlighted operations, by index in the circuit.
+/// * `warnings` - If set, the operations not supported are drawn as placeholders and a warning is
+///   added to this list for each of them instead of returning an error.
+///
+/// # Returns
+///
+/// * `Ok((Vec<Vec<Cell>>, Vec<Vec<Cell>>, Vec<Vec<Cell>>))` - The cells of the qubit, bosonic mode
+///   and classical register wires.
+/// * `Err(RoqollageError)` - Operation not supported or not valid, with its index in the circuit.
+#[allow(clippy::type_complexity)]
+pub(crate) fn circuit_into_highlighted_cells(
+    circuit: &Circuit,
+    render_pragmas: &RenderPragmas,
+    number_format: NumberFormat,
+    layout_mode: LayoutMode,
+    highlights: &HashMap<usize, String>,
+    mut warnings: Option<&mut Vec<String>>,
+) -> Result<(Vec<Vec<Cell>>, Vec<Vec<Cell>>, Vec<Vec<Cell>>), RoqollageError> {
+    let mut circuit_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut bosonic_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut classical_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut circuit_lock: Vec<(usize, usize)> = Vec::new();
+    let mut bosonic_lock: Vec<(usize, usize)> = Vec::new();
+    let mut classical_lock: Vec<(usize, usize)> = Vec::new();
+    for (index, operation) in circuit.iter().enumerate() {
+        let qubit_lengths: Vec<usize> = circuit_gates.iter().map(Vec::len).collect();
+        let bosonic_lengths: Vec<usize> = bosonic_gates.iter().map(Vec::len).collect();
+        let classical_lengths: Vec<usize> = classical_gates.iter().map(Vec::len).collect();
+        add_gate(
+            &mut circuit_gates,
+            &mut bosonic_gates,
+            &mut classical_gates,
+            &mut circuit_lock,
+            &mut bosonic_lock,
+            &mut classical_lock,
+            operation,
+            render_pragmas,
+            number_format,
+            warnings.as_deref_mut(),
+        )
+        .map_err(|err| err.at_operation(index))?;
+        if let Some(fill) = highlights.get(&index) {
+            for (wires, lengths) in [
+                (&mut circuit_gates, &qubit_lengths),
+                (&mut bosonic_gates, &bosonic_lengths),
+            ] {
+                for (wire, cells) in wires.iter_mut().enumerate() {
+                    let start = lengths.get(wire).copied().unwrap_or_default();
+                    for cell in cells.iter_mut().skip(start) {
+                        if cell.takes_column() && *cell != Cell::Idle {
+                            *cell = Cell::Highlighted {
+                                cell: Box::new(std::mem::replace(cell, Cell::Idle)),
+                                fill: fill.clone(),
+                            };
+                        }
+                    }
+                }
+            }
+        }
+        for (wires, lengths) in [
+            (&mut circuit_gates, &qubit_lengths),
+            (&mut bosonic_gates, &bosonic_lengths),
+            (&mut classical_gates, &classical_lengths),
+        ] {
+            for (wire, cells) in wires.iter_mut().enumerate() {
+                let start = lengths.get(wire).copied().unwrap_or_default();
+                for cell in cells.iter_mut().skip(start) {
+                    if (cell.takes_column() && *cell != Cell::Idle)
+                        || matches!(cell, Cell::Slice { .. })
+                    {
+                        *cell = Cell::Traced {
+                            cell: Box::new(std::mem::replace(cell, Cell::Idle)),
+                            index,
+                        };
+                    }
+                }
+            }
+        }
+    }
+    let n_qubits = circuit_gates.len();
+    let n_bosons = bosonic_gates.len();
+    let n_classical = classical_gates.len();
+    flatten_multiple_vec(
+        &mut circuit_gates,
+        &mut bosonic_gates,
+        (0..n_qubits).collect::<Vec<usize>>().as_slice(),
+        (0..n_bosons).collect::<Vec<usize>>().as_slice(),
+    );
+    flatten_multiple_vec(
+        &mut circuit_gates,
+        &mut classical_gates,
+        (0..n_qubits).collect::<Vec<usize>>().as_slice(),
+        (0..n_classical).collect::<Vec<usize>>().as_slice(),
+    );
+    flatten_multiple_vec(
+        &mut bosonic_gates,
+        &mut classical_gates,
+        (0..n_bosons).collect::<Vec<usize>>().as_slice(),
+        (0..n_classical).collect::<Vec<usize>>().as_slice(),
+    );
+    apply_layout_mode(
+        &mut circuit_gates,
+        &mut bosonic_gates,
+        &mut classical_gates,
+        layout_mode,
+    );
+    Ok((circuit_gates, bosonic_gates, classical_gates))
+}
+
+/// Splits the wires of a laid out circuit into columns.
+///
+/// The cells taking a column are placed in the columns, the other ones (groups, slices, register
+/// names and wire changes) are drawn on the boundary preceding the next column of their wire.
+///
+/// # Arguments
+///
+/// * `rows` - The cells of each wire, in the order of the rows.
+///
+/// # Returns
+///
+/// * `Vec<Vec<Cell>>` - The cell of each row in each column.
+/// * `Vec<Vec<(usize, Cell)>>` - The row and the cell drawn on each boundary, the boundary `n` being
+///   the one before the column `n`.
+#[allow(clippy::type_complexity)]
+pub(crate) fn cells_into_columns(
+    rows: Vec<Vec<Cell>>,
+) -> (Vec<Vec<Cell>>, Vec<Vec<(usize, Cell)>>) {
+    let n_rows = rows.len();
+    let n_columns = rows
+        .iter()
+        .map(|cells| cells.iter().filter(|cell| cell.takes_column()).count())
+        .max()
+        .unwrap_or_default();
+    let mut columns: Vec<Vec<Cell>> = vec![vec![Cell::Idle; n_rows]; n_columns];
+    let mut boundaries: Vec<Vec<(usize, Cell)>> = vec![Vec::new(); n_columns + 1];
+    for (row, cells) in rows.into_iter().enumerate() {
+        let mut column = 0;
+        for cell in cells {
+            if cell.takes_column() {
+                columns[column][row] = cell;
+                column += 1;
+            } else {
+                boundaries[column].push((row, cell));
+            }
+        }
+    }
+    (columns, boundaries)
+}
+
+/// Step of the circuit representation to place in the compact layouts.
+#[derive(Debug)]
+enum LayoutEvent {
+    /// Cells of the same column linked together.
+    Block(LayoutBlock),
+    /// Slice drawn across all the wires, with the row it is placed on.
+    Slice(usize, Cell),
+    /// Start of the group with the given index.
+    OpenGroup(usize),
+    /// End of the group with the given index.
+    CloseGroup(usize),
+}
+
+/// Cells of the same column crossing the same wires, moved together in the compact layouts.
+#[derive(Debug)]
+struct LayoutBlock {
+    cells: Vec<(usize, Cell)>,
+    first_row: usize,
+    last_row: usize,
+}
+
+/// Group of the circuit representation to place in the compact layouts.
+#[derive(Debug)]
+struct LayoutGroup {
+    row: usize,
+    cell: Cell,
+    first_row: usize,
+    last_row: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Places the cells of the circuit representation according to the layout mode.
+///
+/// The cells placed in the same column by `add_gate` and crossing the same wires are kept
+/// together, and are moved to the earliest (ASAP) or latest (ALAP) column free on all the wires
+/// they cross. Slices act as barriers across all the wires and groups as barriers across the
+/// wires they surround.
+///
+/// # Arguments
+///
+/// * `circuit_gates` - A vector of all the gates vectors of the circuit.
+/// * `bosonic_gates` - A vector of all the bosonic gates vectors of the circuit.
+/// * `classical_gates` - A vector of all the operations on classical registers of the circuit.
+/// * `layout_mode` - The layout mode to apply.
+pub(crate) fn apply_layout_mode(
+    circuit_gates: &mut Vec<Vec<Cell>>,
+    bosonic_gates: &mut Vec<Vec<Cell>>,
+    classical_gates: &mut Vec<Vec<Cell>>,
+    layout_mode: LayoutMode,
+) {
+    if layout_mode == LayoutMode::Sequential {
+        return;
+    }
+    let n_qubits = circuit_gates.len();
+    let n_bosons = bosonic_gates.len();
+    let rows: Vec<Vec<Cell>> = circuit_gates
+        .drain(..)
+        .chain(bosonic_gates.drain(..))
+        .chain(classical_gates.drain(..))
+        .collect();
+    let n_rows = rows.len();
+    if n_rows == 0 {
+        return;
+    }
+
+    // Reads the columns of the representation.
+    let mut prefixes: Vec<Vec<Cell>> = vec![Vec::new(); n_rows];
+    let mut columns: Vec<Vec<(usize, Cell)>> = Vec::new();
+    let mut boundaries: Vec<Vec<(usize, Cell)>> = vec![Vec::new()];
+    for (row, cells) in rows.into_iter().enumerate() {
+        let mut column = 0;
+        let mut in_prefix = true;
+        for cell in cells {
+            if in_prefix && matches!(cell, Cell::RegisterName(_) | Cell::WireLines(_)) {
+                prefixes[row].push(cell);
+                continue;
+            }
+            in_prefix = false;
+            while columns.len() <= column {
+                columns.push(Vec::new());
+                boundaries.push(Vec::new());
+            }
+            if !cell.takes_column() {
+                boundaries[column].push((row, cell));
+            } else {
+                if cell != Cell::Idle {
+                    columns[column].push((row, cell));
+                }
+                column += 1;
+            }
+        }
+    }
+
+    // Lists the blocks, slices and groups in the order of the representation.
+    let mut events: Vec<LayoutEvent> = Vec::new();
+    let mut groups: Vec<LayoutGroup> = Vec::new();
+    let mut open_groups: Vec<(usize, usize)> = Vec::new();
+    for (index, boundary) in boundaries.into_iter().enumerate() {
+        for position in (0..open_groups.len()).rev() {
+            if open_groups[position].0 <= index {
+                events.push(LayoutEvent::CloseGroup(open_groups.remove(position).1));
+            }
+        }
+        for (row, cell) in boundary {
+            if let Cell::Group { length, .. } = cell {
+                let (first_row, last_row) = cell.row_span(row, n_qubits, n_bosons);
+                events.push(LayoutEvent::OpenGroup(groups.len()));
+                if length == 0 {
+                    events.push(LayoutEvent::CloseGroup(groups.len()));
+                } else {
+                    open_groups.push((index + length, groups.len()));
+                }
+                groups.push(LayoutGroup {
+                    row,
+                    cell,
+                    first_row,
+                    last_row: last_row.min(n_rows - 1),
+                    start: 0,
+                    end: 0,
+                });
+            } else {
+                events.push(LayoutEvent::Slice(row, cell));
+            }
+        }
+        if let Some(column) = columns.get_mut(index) {
+            let mut blocks: Vec<LayoutBlock> = Vec::new();
+            let mut cells = std::mem::take(column)
+                .into_iter()
+                .map(|(row, cell)| {
+                    let (first_row, last_row) = cell.row_span(row, n_qubits, n_bosons);
+                    (row, cell, first_row, last_row.min(n_rows - 1))
+                })
+                .collect::<Vec<_>>();
+            cells.sort_by_key(|(_, _, first_row, _)| *first_row);
+            for (row, cell, first_row, last_row) in cells {
+                match blocks.last_mut() {
+                    Some(block) if first_row <= block.last_row => {
+                        block.cells.push((row, cell));
+                        block.last_row = last_row.max(block.last_row);
+                    }
+                    _ => blocks.push(LayoutBlock {
+                        cells: vec![(row, cell)],
+                        first_row,
+                        last_row,
+                    }),
+                }
+            }
+            events.extend(blocks.into_iter().map(LayoutEvent::Block));
+        }
+    }
+    for (_, group_index) in open_groups.into_iter().rev() {
+        events.push(LayoutEvent::CloseGroup(group_index));
+    }
+    if layout_mode == LayoutMode::Alap {
+        events = events
+            .into_iter()
+            .rev()
+            .map(|event| match event {
+                LayoutEvent::OpenGroup(index) => LayoutEvent::CloseGroup(index),
+                LayoutEvent::CloseGroup(index) => LayoutEvent::OpenGroup(index),
+                event => event,
+            })
+            .collect();
+    }
+
+    // Places each event at the earliest position free on the wires it crosses. The labels are
+    // kept apart only on the wires they share, so that groups on disjoint wires stay side by side.
+    // In ALAP the events are reversed and the label of a group is on its closing side.
+    let is_alap = layout_mode == LayoutMode::Alap;
+    let mut frontier = vec![0_usize; n_rows];
+    let mut label_frontier = vec![1_usize; n_rows];
+    let mut placed_cells: Vec<(usize, usize, Cell)> = Vec::new();
+    let mut placed_slices: Vec<(usize, usize, Cell)> = Vec::new();
+    for event in events {
+        match event {
+            LayoutEvent::Block(block) => {
+                let column = frontier[block.first_row..=block.last_row]
+                    .iter()
+                    .max()
+                    .copied()
+                    .unwrap_or_default();
+                frontier[block.first_row..=block.last_row].fill(column + 1);
+                placed_cells.extend(
+                    block
+                        .cells
+                        .into_iter()
+                        .map(|(row, cell)| (column, row, cell)),
+                );
+            }
+            LayoutEvent::Slice(row, cell) => {
+                let position = frontier
+                    .iter()
+                    .chain(label_frontier.iter())
+                    .max()
+                    .copied()
+                    .unwrap_or_default();
+                frontier.fill(position);
+                label_frontier.fill(position + LABEL_SPACING);
+                placed_slices.push((position, row, cell));
+            }
+            LayoutEvent::OpenGroup(index) => {
+                let group = &mut groups[index];
+                let rows = group.first_row..=group.last_row;
+                let mut position = frontier[rows.clone()]
+                    .iter()
+                    .max()
+                    .copied()
+                    .unwrap_or_default();
+                let label_position = label_frontier[rows.clone()]
+                    .iter()
+                    .max()
+                    .copied()
+                    .unwrap_or_default();
+                if is_alap {
+                    // The label is on the closing side, at least one column after the opening.
+                    position = position.max(label_position - 1);
+                } else {
+                    position = position.max(label_position);
+                    label_frontier[rows.clone()].fill(position + LABEL_SPACING);
+                }
+                frontier[rows].fill(position);
+                group.start = position;
+            }
+            LayoutEvent::CloseGroup(index) => {
+                let group = &mut groups[index];
+                let rows = group.first_row..=group.last_row;
+                let mut position = frontier[rows.clone()]
+                    .iter()
+                    .max()
+                    .copied()
+                    .unwrap_or_default()
+                    .max(group.start);
+                if is_alap {
+                    position = label_frontier[rows.clone()]
+                        .iter()
+                        .max()
+                        .copied()
+                        .unwrap_or_default()
+                        .max(position);
+                    label_frontier[rows.clone()].fill(position + LABEL_SPACING);
+                }
+                frontier[rows].fill(position);
+                group.end = position;
+            }
+        }
+    }
+    let length = frontier
+        .iter()
+        .chain(groups.iter().map(|group| &group.end))
+        .chain(placed_slices.iter().map(|(position, _, _)| position))
+        .max()
+        .copied()
+        .unwrap_or_default();
+    // The cells and slices are mirrored back in ALAP before the groups are added, as the
+    // positions of the groups are mirrored when they are added.
+    if is_alap {
+        for (column, _, _) in placed_cells.iter_mut() {
+            *column = length - 1 - *column;
+        }
+        for (position, _, _) in placed_slices.iter_mut() {
+            *position = length - *position;
+        }
+    }
+    for group in groups {
+        let (start, end) = if is_alap {
+            (length - group.end, length - group.start)
+        } else {
+            (group.start, group.end)
+        };
+        let mut cell = group.cell;
+        if let Cell::Group {
+            length: ref mut group_length,
+            ..
+        } = cell
+        {
+            *group_length = end - start;
+        }
+        placed_slices.push((start, group.row, cell));
+    }
+    placed_slices.sort_by_key(|(position, row, _)| (*position, *row));
+
+    // Writes the cells back in their new columns.
+    let mut grid: Vec<Vec<Cell>> = vec![vec![Cell::Idle; length]; n_rows];
+    for (column, row, cell) in placed_cells {
+        grid[row][column] = cell;
+    }
+    let mut rows = prefixes;
+    for (row, cells) in grid.into_iter().enumerate() {
+        let mut row_slices = placed_slices
+            .iter()
+            .filter(|(_, slice_row, _)| *slice_row == row)
+            .peekable();
+        for (column, cell) in cells.into_iter().enumerate() {
+            while let Some((_, _, slice)) =
+                row_slices.next_if(|(position, _, _)| *position <= column)
+            {
+                rows[row].push(slice.clone());
+            }
+            rows[row].push(cell);
+        }
+        rows[row].extend(row_slices.map(|(_, _, slice)| slice.clone()));
+    }
+    *classical_gates = rows.split_off(n_qubits + n_bosons);
+    *bosonic_gates = rows.split_off(n_qubits);
+    *circuit_gates = rows;
+}
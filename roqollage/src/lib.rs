@@ -25,5 +25,30 @@
 
 mod backend;
 pub use backend::*;
+mod device;
+pub use device::*;
+mod diff;
+pub use diff::*;
+mod error;
+pub use error::*;
+mod expression;
 mod interface;
 pub use interface::*;
+mod labels;
+pub use labels::*;
+mod latex;
+pub use latex::*;
+mod layout;
+pub use layout::*;
+mod number;
+pub use number::*;
+mod program;
+pub use program::*;
+mod resources;
+pub(crate) use resources::*;
+mod statistics;
+pub use statistics::*;
+mod text;
+pub use text::*;
+mod timing;
+pub use timing::*;
@@ -0,0 +1,412 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{f64::consts::PI, str::FromStr};
+
+use crate::{latex_math, latex_math_enabled, typst_math_to_latex, RoqollageError};
+
+/// Tolerance of the recognition of the symbolic values.
+pub(crate) const EPSILON: f64 = 1e-6;
+
+/// Square-free integers whose square roots are recognized.
+const SQUARE_ROOTS: [u64; 6] = [2, 3, 5, 6, 7, 10];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How the floats are written.
+pub enum FloatStyle {
+    /// Multiples of π, fractions and square roots are written symbolically, e.g. 5π/8.
+    #[default]
+    Symbolic,
+    /// The floats are written as decimal numbers.
+    Decimal,
+    /// Multiples of π are written in degrees, e.g. 112.5°, the other floats symbolically.
+    Degrees,
+}
+
+impl FromStr for FloatStyle {
+    type Err = RoqollageError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "symbolic" => Ok(FloatStyle::Symbolic),
+            "decimal" => Ok(FloatStyle::Decimal),
+            "degrees" => Ok(FloatStyle::Degrees),
+            _ => Err(RoqollageError::InvalidOption {
+                msg: format!(
+                    r#"Invalid float style: {s}, use `symbolic`, `decimal` or `degrees`."#
+                ),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The unit of the rotation angles of the operations.
+pub enum AngleUnit {
+    /// The angles are written in radians.
+    #[default]
+    Radians,
+    /// The angles are written in degrees, e.g. 90°.
+    Degrees,
+    /// The angles are written in turns, e.g. 1/4 tr.
+    Turns,
+}
+
+impl FromStr for AngleUnit {
+    type Err = RoqollageError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "radians" => Ok(AngleUnit::Radians),
+            "degrees" => Ok(AngleUnit::Degrees),
+            "turns" => Ok(AngleUnit::Turns),
+            _ => Err(RoqollageError::InvalidOption {
+                msg: format!(r#"Invalid angle unit: {s}, use `radians`, `degrees` or `turns`."#),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How the complex numbers are written.
+pub enum ComplexStyle {
+    /// The complex numbers are written as a real and an imaginary part, e.g. 0.5 - 0.5 i.
+    #[default]
+    Cartesian,
+    /// The complex numbers are written as a modulus and a phase, e.g. 1/sqrt(2)·e^(-i π/4).
+    Polar,
+}
+
+impl FromStr for ComplexStyle {
+    type Err = RoqollageError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cartesian" => Ok(ComplexStyle::Cartesian),
+            "polar" => Ok(ComplexStyle::Polar),
+            _ => Err(RoqollageError::InvalidOption {
+                msg: format!(r#"Invalid complex style: {s}, use `cartesian` or `polar`."#),
+            }),
+        }
+    }
+}
+
+/// How the numbers displayed in the circuits are formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// The number of digits to round to when displaying floats.
+    pub rounding_accuracy: usize,
+    /// How the floats are written.
+    pub float_style: FloatStyle,
+    /// The largest denominator of the fractions recognized in the floats.
+    pub max_denominator: u64,
+    /// The unit of the rotation angles of the operations.
+    pub angle_unit: AngleUnit,
+    /// The largest number of kets of the state vectors, and of rows and columns of the density
+    /// matrices, displayed before an ellipsis.
+    pub max_state_size: usize,
+    /// How the complex numbers are written.
+    pub complex_style: ComplexStyle,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            rounding_accuracy: 3,
+            float_style: FloatStyle::default(),
+            max_denominator: 16,
+            angle_unit: AngleUnit::default(),
+            max_state_size: 8,
+            complex_style: ComplexStyle::default(),
+        }
+    }
+}
+
+fn count_digits(num: f64) -> usize {
+    let num_str: String = num
+        .to_string()
+        .chars()
+        .skip_while(|&c| c == '.' || c == '0' || c == '-')
+        .collect();
+    let count = num_str.chars().filter(|&c| c != '.').count();
+    count
+}
+
+/// Formats a float as a decimal number, in scientific notation if it is very small or large.
+///
+/// # Arguments
+///
+/// * `value` - The float to format.
+/// * `rounding_accuracy` - The number of digits to round to.
+fn format_decimal(value: f64, rounding_accuracy: usize) -> String {
+    if value.abs() <= 0.005 || value.abs() >= 1000. {
+        match count_digits(value) {
+            0 => "0".to_owned(),
+            1 => format!("\"{value:e}\""),
+            2 => format!("\"{value:.1e}\""),
+            _ => format!("\"{value:.2e}\""),
+        }
+    } else {
+        // The zeros between the decimal point and the first significant digit are kept.
+        let leading_zeros = if value.abs() < 1.0 {
+            (-value.abs().log10()).ceil() as usize - 1
+        } else {
+            0
+        };
+        let acc = rounding_accuracy.min(count_digits(value) + leading_zeros);
+        format!("{value:.acc$}")
+    }
+}
+
+/// Formats an angle in degrees, without trailing zeros, e.g. `112.5°`.
+///
+/// # Arguments
+///
+/// * `degrees` - The angle in degrees.
+/// * `rounding_accuracy` - The number of digits to round to.
+fn format_degrees(degrees: f64, rounding_accuracy: usize) -> String {
+    let degrees = format!("{degrees:.rounding_accuracy$}");
+    let degrees = if degrees.contains('.') {
+        degrees.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &degrees
+    };
+    match degrees {
+        "-0" => "0°".to_owned(),
+        degrees => format!("{degrees}°"),
+    }
+}
+
+/// Finds the fraction n/m with the smallest denominator equal to a positive value.
+///
+/// # Arguments
+///
+/// * `value` - The positive value.
+/// * `max_denominator` - The largest denominator tried.
+///
+/// # Returns
+///
+/// * `Option<(u64, u64)>` - The numerator and the denominator, None if no fraction matches.
+fn find_fraction(value: f64, max_denominator: u64) -> Option<(u64, u64)> {
+    (1..=max_denominator).find_map(|denominator| {
+        let numerator = (value * denominator as f64).round();
+        (numerator >= 1.0 && (numerator / denominator as f64 - value).abs() < EPSILON)
+            .then_some((numerator as u64, denominator))
+    })
+}
+
+/// Writes a fraction of two Typst math expressions, e.g. `(3pi)/4`.
+///
+/// The numerator is put in parentheses if it is a product. In the cells converted to LaTeX, the
+/// fraction is written with `\frac`.
+///
+/// # Arguments
+///
+/// * `numerator` - The Typst math of the numerator.
+/// * `denominator` - The Typst math of the denominator.
+fn fraction(numerator: &str, denominator: &str) -> String {
+    if latex_math_enabled() {
+        latex_math(&format!(
+            "\\frac{{{}}}{{{}}}",
+            typst_math_to_latex(numerator),
+            typst_math_to_latex(denominator)
+        ))
+    } else if numerator.contains(' ')
+        || (numerator.starts_with(|character: char| character.is_ascii_digit())
+            && numerator.contains(|character: char| character.is_alphabetic()))
+    {
+        format!("({numerator})/{denominator}")
+    } else {
+        format!("{numerator}/{denominator}")
+    }
+}
+
+/// Writes the product of a fraction and a Typst factor, e.g. `(3pi)/4`.
+///
+/// # Arguments
+///
+/// * `numerator` - The numerator of the fraction.
+/// * `denominator` - The denominator of the fraction.
+/// * `factor` - The Typst math of the factor, empty for a plain fraction.
+fn fraction_with_factor(numerator: u64, denominator: u64, factor: &str) -> String {
+    let product = match numerator {
+        1 if !factor.is_empty() => factor.to_owned(),
+        numerator => format!("{numerator}{factor}"),
+    };
+    if denominator == 1 {
+        product
+    } else {
+        fraction(&product, &denominator.to_string())
+    }
+}
+
+/// Writes a positive float symbolically, if it is a fraction of π, a simple fraction or a
+/// fraction of a square root, possibly multiplied by π.
+///
+/// # Arguments
+///
+/// * `value` - The positive float.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `Option<String>` - The Typst math of the float, None if it isn't recognized.
+fn format_symbolic(value: f64, number_format: NumberFormat) -> Option<String> {
+    if value >= 1000. {
+        return None;
+    }
+    let max_denominator = number_format.max_denominator;
+    if let Some((numerator, denominator)) = find_fraction(value / PI, max_denominator) {
+        return Some(match number_format.float_style {
+            FloatStyle::Degrees => format_degrees(
+                numerator as f64 * 180.0 / denominator as f64,
+                number_format.rounding_accuracy,
+            ),
+            _ => fraction_with_factor(numerator, denominator, "pi"),
+        });
+    }
+    // The fractions written exactly with the rounding accuracy stay decimal numbers.
+    let decimal = format_decimal(value, number_format.rounding_accuracy);
+    let exact = decimal
+        .trim_matches('"')
+        .parse::<f64>()
+        .is_ok_and(|parsed| (parsed - value).abs() < EPSILON);
+    if !exact {
+        if let Some((numerator, denominator)) = find_fraction(value, max_denominator) {
+            return Some(fraction_with_factor(numerator, denominator, ""));
+        }
+    }
+    for square in SQUARE_ROOTS {
+        let root = (square as f64).sqrt();
+        for (factor, typst_factor, inverse) in [
+            (
+                root,
+                format!("sqrt({square})"),
+                fraction("1", &format!("sqrt({square})")),
+            ),
+            (
+                root * PI,
+                format!("sqrt({square}) pi"),
+                fraction("pi", &format!("sqrt({square})")),
+            ),
+        ] {
+            if let Some((numerator, denominator)) = find_fraction(value / factor, max_denominator) {
+                return Some(if numerator == 1 && denominator == square {
+                    inverse
+                } else {
+                    fraction_with_factor(numerator, denominator, &typst_factor)
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Formats a float to be displayed in a Typst math expression.
+///
+/// # Arguments
+///
+/// * `value` - The float to format.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `String` - The Typst math of the float.
+pub(crate) fn format_float(value: f64, number_format: NumberFormat) -> String {
+    if number_format.float_style == FloatStyle::Decimal || value == 0.0 {
+        return format_decimal(value, number_format.rounding_accuracy);
+    }
+    match format_symbolic(value.abs(), number_format) {
+        Some(symbolic) if value < 0.0 => format!("-{symbolic}"),
+        Some(symbolic) => symbolic,
+        None => format_decimal(value, number_format.rounding_accuracy),
+    }
+}
+
+/// Formats a float rotation angle, given in radians, in the angle unit of the number format.
+///
+/// # Arguments
+///
+/// * `value` - The angle in radians.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `String` - The Typst math of the angle.
+pub(crate) fn format_angle_float(value: f64, number_format: NumberFormat) -> String {
+    match number_format.angle_unit {
+        AngleUnit::Radians => format_float(value, number_format),
+        AngleUnit::Degrees => format_degrees(value.to_degrees(), number_format.rounding_accuracy),
+        AngleUnit::Turns => {
+            let turns = value / (2.0 * PI);
+            let fraction = (number_format.float_style != FloatStyle::Decimal)
+                .then(|| find_fraction(turns.abs(), number_format.max_denominator))
+                .flatten();
+            match fraction {
+                Some((numerator, denominator)) => format!(
+                    "{}{} \"tr\"",
+                    if turns < 0.0 { "-" } else { "" },
+                    fraction_with_factor(numerator, denominator, "")
+                ),
+                None => format!(
+                    "{} \"tr\"",
+                    format_decimal(turns, number_format.rounding_accuracy)
+                ),
+            }
+        }
+    }
+}
+
+/// Formats a complex number to be displayed in a Typst math expression.
+///
+/// The zero parts are omitted and the unit factors are not written.
+///
+/// # Arguments
+///
+/// * `re` - The real part of the complex number.
+/// * `im` - The imaginary part of the complex number.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `String` - The Typst math of the complex number.
+pub(crate) fn format_complex(re: f64, im: f64, number_format: NumberFormat) -> String {
+    if re.abs() < EPSILON && im.abs() < EPSILON {
+        return "0".to_owned();
+    }
+    if im.abs() < EPSILON {
+        return format_float(re, number_format);
+    }
+    match number_format.complex_style {
+        ComplexStyle::Cartesian => {
+            let imaginary = if (im.abs() - 1.0).abs() < EPSILON {
+                "i".to_owned()
+            } else {
+                format!("{} i", format_float(im.abs(), number_format))
+            };
+            match (re.abs() < EPSILON, im < 0.0) {
+                (true, false) => imaginary,
+                (true, true) => format!("-{imaginary}"),
+                (false, false) => format!("{} + {imaginary}", format_float(re, number_format)),
+                (false, true) => format!("{} - {imaginary}", format_float(re, number_format)),
+            }
+        }
+        ComplexStyle::Polar => {
+            let modulus = re.hypot(im);
+            let phase = im.atan2(re);
+            let sign = if phase < 0.0 { "-" } else { "" };
+            let exponential = format!("e^({sign}i {})", format_float(phase.abs(), number_format));
+            if (modulus - 1.0).abs() < EPSILON {
+                exponential
+            } else {
+                format!("{} dot {exponential}", format_float(modulus, number_format))
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use image::DynamicImage;
+use qoqo_calculator::CalculatorFloat;
+use roqoqo::{
+    measurements::{
+        CheatedInput, CheatedPauliZProductInput, Measure, PauliProductsToExpVal, PauliZProductInput,
+    },
+    Circuit, QuantumProgram,
+};
+
+use crate::{
+    backend_from_config, circuit_into_quill_code, format_calculator, format_complex_value,
+    typst_string, BackendConfig, NumberFormat, RenderOptions, RoqollageError, QUILL_PACKAGE,
+    TYPST_PREAMBLE,
+};
+
+/// Returns the keys of a map in ascending order.
+fn sorted_keys<K: Ord, V>(map: &HashMap<K, V>) -> Vec<&K> {
+    let mut keys: Vec<&K> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+/// Formats a name of the quantum program (parameter, register or expectation value) as raw text.
+fn typst_name(name: &str) -> String {
+    format!("#raw({})", typst_string(name))
+}
+
+/// Wraps Typst content in a labeled panel.
+///
+/// # Arguments
+///
+/// * `label` - The title of the panel.
+/// * `content` - The Typst markup displayed in the panel.
+fn panel(label: &str, content: &str) -> String {
+    format!(
+        "#block(stroke: 0.5pt + gray, inset: 6pt, radius: 3pt, breakable: false)[\n  *{label}* \\\n{content}\n]\n\n"
+    )
+}
+
+/// Formats the expectation values computed from the measured Pauli products.
+///
+/// # Arguments
+///
+/// * `measured_exp_vals` - The expectation values, by name.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `Vec<String>` - The list items describing each expectation value.
+fn format_exp_vals(
+    measured_exp_vals: &HashMap<String, PauliProductsToExpVal>,
+    number_format: NumberFormat,
+) -> Vec<String> {
+    sorted_keys(measured_exp_vals)
+        .into_iter()
+        .map(|name| {
+            let formula = match &measured_exp_vals[name] {
+                PauliProductsToExpVal::Linear(coefficients) if coefficients.is_empty() => {
+                    "0".to_owned()
+                }
+                PauliProductsToExpVal::Linear(coefficients) => sorted_keys(coefficients)
+                    .into_iter()
+                    .map(|index| {
+                        format!(
+                            "{} p_({index})",
+                            format_calculator(
+                                &CalculatorFloat::Float(coefficients[index]),
+                                number_format
+                            )
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" + ")
+                    .replace("+ -", "- "),
+                PauliProductsToExpVal::Symbolic(expression) => {
+                    format_calculator(expression, number_format)
+                }
+            };
+            format!("  - Expectation value {}: $ {formula} $", typst_name(name))
+        })
+        .collect()
+}
+
+/// Describes the input of a PauliZProduct measurement.
+fn pauli_z_product_input(input: &PauliZProductInput, number_format: NumberFormat) -> String {
+    let mut items = vec![format!(
+        "  Qubits: {}, Pauli products: {}, flipped measurement: {}",
+        input.number_qubits,
+        input.number_pauli_products,
+        if input.use_flipped_measurement {
+            "yes"
+        } else {
+            "no"
+        },
+    )];
+    for readout in sorted_keys(&input.pauli_product_qubit_masks) {
+        let masks = &input.pauli_product_qubit_masks[readout];
+        let products = sorted_keys(masks)
+            .into_iter()
+            .map(|index| {
+                let qubits = &masks[index];
+                format!(
+                    "$ p_({index}) = {} $",
+                    if qubits.is_empty() {
+                        "I".to_owned()
+                    } else {
+                        qubits
+                            .iter()
+                            .map(|qubit| format!("Z_({qubit})"))
+                            .collect::<Vec<String>>()
+                            .join(" ")
+                    }
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        items.push(format!("  - Readout {}: {products}", typst_name(readout)));
+    }
+    items.extend(format_exp_vals(&input.measured_exp_vals, number_format));
+    items.join("\n")
+}
+
+/// Describes the input of a CheatedPauliZProduct measurement.
+fn cheated_pauli_z_product_input(
+    input: &CheatedPauliZProductInput,
+    number_format: NumberFormat,
+) -> String {
+    let mut keys: Vec<(&usize, &String)> = input
+        .pauli_product_keys
+        .iter()
+        .map(|(readout, index)| (index, readout))
+        .collect();
+    keys.sort();
+    let mut items: Vec<String> = keys
+        .into_iter()
+        .map(|(index, readout)| format!("  - $ p_({index}) $ = readout {}", typst_name(readout)))
+        .collect();
+    items.extend(format_exp_vals(&input.measured_exp_vals, number_format));
+    items.join("\n")
+}
+
+/// Describes the input of a Cheated measurement.
+fn cheated_input(input: &CheatedInput, number_format: NumberFormat) -> String {
+    let mut items = vec![format!("  Qubits: {}", input.number_qubits)];
+    for name in sorted_keys(&input.measured_operators) {
+        let (operator, readout) = &input.measured_operators[name];
+        items.push(format!(
+            "  - Operator {} on readout {}: $ {} $",
+            typst_name(name),
+            typst_name(readout),
+            operator
+                .iter()
+                .map(|(row, column, value)| format!(
+                    "O_({row},{column}) = {}",
+                    format_complex_value(*value, number_format)
+                ))
+                .collect::<Vec<String>>()
+                .join(", "),
+        ));
+    }
+    items.join("\n")
+}
+
+/// Converts a qoqo quantum program to a typst string.
+///
+/// The constant circuit and each measurement circuit of the measurement are drawn in their own
+/// labeled panel, along with the input parameters of the program and the input of the
+/// measurement (the measured Pauli products and the formulas of the expectation values).
+///
+///  ## Arguments
+///
+/// * `program` - The quantum program to convert.
+/// * `options` - The options of the representation of the circuits. The page, device and
+///   statistics options are not used.
+///
+/// ## Returns
+///
+/// * `String` - The string representation of the quantum program in Typst.
+pub fn quantum_program_into_typst_str(
+    program: &QuantumProgram,
+    options: &RenderOptions,
+) -> Result<String, RoqollageError> {
+    let input_format = options.number_format;
+    // The chunks of the circuits stay in their panels.
+    let circuit_options = RenderOptions {
+        page_per_chunk: false,
+        ..options.clone()
+    };
+    let (kind, input_parameter_names, constant_circuit, circuits, measurement_input): (
+        &str,
+        &Vec<String>,
+        &Option<Circuit>,
+        Vec<&Circuit>,
+        String,
+    ) = match program {
+        QuantumProgram::PauliZProduct {
+            measurement,
+            input_parameter_names,
+        } => (
+            "PauliZProduct",
+            input_parameter_names,
+            measurement.constant_circuit(),
+            measurement.circuits().collect(),
+            pauli_z_product_input(&measurement.input, input_format),
+        ),
+        QuantumProgram::CheatedPauliZProduct {
+            measurement,
+            input_parameter_names,
+        } => (
+            "CheatedPauliZProduct",
+            input_parameter_names,
+            measurement.constant_circuit(),
+            measurement.circuits().collect(),
+            cheated_pauli_z_product_input(&measurement.input, input_format),
+        ),
+        QuantumProgram::Cheated {
+            measurement,
+            input_parameter_names,
+        } => (
+            "Cheated",
+            input_parameter_names,
+            measurement.constant_circuit(),
+            measurement.circuits().collect(),
+            cheated_input(&measurement.input, input_format),
+        ),
+        QuantumProgram::ClassicalRegister {
+            measurement,
+            input_parameter_names,
+        } => (
+            "ClassicalRegister",
+            input_parameter_names,
+            measurement.constant_circuit(),
+            measurement.circuits().collect(),
+            "  The measured classical registers are returned directly.".to_owned(),
+        ),
+        #[allow(unreachable_patterns)]
+        _ => {
+            return Err(RoqollageError::InvalidOption {
+                msg: "Quantum program measurement not supported by the Typst backend.".to_owned(),
+            })
+        }
+    };
+
+    let circuit_panel = |label: &str, circuit: &Circuit| -> Result<String, RoqollageError> {
+        let content = if circuit.is_empty() {
+            "  _Empty circuit_".to_owned()
+        } else {
+            format!(
+                "  #{{\n{}  }}",
+                circuit_into_quill_code(circuit, &circuit_options, &HashMap::new(), None)?.0
+            )
+        };
+        Ok(panel(label, &content))
+    };
+
+    let mut typst_str = format!("{TYPST_PREAMBLE}#import \"{QUILL_PACKAGE}\": *\n\n");
+    typst_str.push_str(&format!(
+        "*{kind} measurement* \\\nInput parameters: {}\n\n",
+        if input_parameter_names.is_empty() {
+            "none".to_owned()
+        } else {
+            input_parameter_names
+                .iter()
+                .map(|name| typst_name(name))
+                .collect::<Vec<String>>()
+                .join(", ")
+        }
+    ));
+    if let Some(constant_circuit) = constant_circuit {
+        typst_str.push_str(&circuit_panel("Constant circuit", constant_circuit)?);
+    }
+    for (index, circuit) in circuits.into_iter().enumerate() {
+        typst_str.push_str(&circuit_panel(
+            &format!("Measurement circuit {index}"),
+            circuit,
+        )?);
+    }
+    typst_str.push_str(&panel("Measurement input", &measurement_input));
+    Ok(typst_str)
+}
+
+/// Converts a qoqo quantum program to an image.
+///
+///  ## Arguments
+///
+/// * `program` - The quantum program to convert.
+/// * `pixels_per_point` - The pixel per point ratio.
+/// * `options` - The options of the representation of the circuits. The page, device and
+///   statistics options are not used.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * DynamicImage: The image reprensenting the quantum program.
+pub fn quantum_program_to_image(
+    program: &QuantumProgram,
+    pixels_per_point: Option<f32>,
+    options: &RenderOptions,
+    backend_config: Option<&BackendConfig>,
+) -> Result<DynamicImage, RoqollageError> {
+    let typst_str = quantum_program_into_typst_str(program, options)?;
+    backend_from_config(backend_config)?.render_typst_str(typst_str, pixels_per_point)
+}
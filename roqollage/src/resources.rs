@@ -0,0 +1,22 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Constants shared with the build script, which includes this file.
+
+/// Version of the quill package used to draw the circuits.
+pub(crate) const QUILL_PACKAGE: &str = "@preview/quill:0.7.1";
+
+/// Default url of the Typst packages registry.
+pub(crate) const DEFAULT_PACKAGE_URL: &str = "https://packages.typst.org";
+
+/// Url of the FiraMath font used for the math in the circuits.
+pub(crate) const FONT_URL: &str = "https://mirrors.ctan.org/fonts/firamath/FiraMath-Regular.otf";
@@ -0,0 +1,239 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use qoqo_calculator::{Calculator, CalculatorError};
+use roqoqo::{
+    operations::{Operate, Operation, Substitute},
+    Circuit, RoqoqoError,
+};
+
+use crate::{
+    cells_into_columns, circuit_into_highlighted_cells, typst_string, Cell, LayoutMode,
+    NumberFormat, RenderPragmas, RoqollageError,
+};
+
+/// Summary of the content of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CircuitStatistics {
+    /// Number of qubit wires drawn.
+    pub number_qubits: usize,
+    /// Number of bosonic mode wires drawn.
+    pub number_modes: usize,
+    /// Number of classical registers defined by the Definition operations.
+    pub number_classical_registers: usize,
+    /// Number of columns of the drawn circuit in the chosen layout.
+    pub depth: usize,
+    /// Number of operations of each name, pragmas and register definitions excepted.
+    pub gate_counts: BTreeMap<String, usize>,
+    /// Number of two-qubit gates.
+    pub two_qubit_gate_count: usize,
+    /// Number of measurement operations.
+    pub measurement_count: usize,
+    /// Names of the symbolic parameters used by the operations.
+    pub symbolic_parameters: BTreeSet<String>,
+    /// Number of pragmas of each name not drawn because of the pragma rendering option.
+    pub hidden_pragmas: BTreeMap<String, usize>,
+}
+
+/// Returns whether a pragma operation is drawn with the pragma rendering option.
+fn is_rendered(operation: &Operation, render_pragmas: &RenderPragmas) -> bool {
+    match render_pragmas {
+        RenderPragmas::All => true,
+        RenderPragmas::None => false,
+        RenderPragmas::Partial(pragmas) => pragmas.contains(&operation.hqslang().to_owned()),
+    }
+}
+
+/// Collects the names of the symbolic parameters of an operation.
+///
+/// The parameters are substituted with an empty calculator, the name of each missing variable
+/// being recorded and set before trying again.
+///
+/// # Arguments
+///
+/// * `operation` - The operation to inspect.
+/// * `symbols` - The set the names are added to.
+fn collect_symbols(operation: &Operation, symbols: &mut BTreeSet<String>) {
+    if !operation.is_parametrized() {
+        return;
+    }
+    let mut calculator = Calculator::new();
+    while let Err(RoqoqoError::CalculatorError(CalculatorError::VariableNotSet { name })) =
+        operation.substitute_parameters(&calculator)
+    {
+        calculator.set_variable(&name, 1.0);
+        symbols.insert(name);
+    }
+}
+
+/// Counts the operations of a circuit and of the circuits nested in its operations.
+///
+/// The bodies of the PragmaLoop and PragmaConditional operations are counted once, whatever the
+/// number of repetitions.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit to inspect.
+/// * `render_pragmas` - Whether to render Pragma Operations or not.
+/// * `statistics` - The statistics the operations are added to.
+fn count_operations(
+    circuit: &Circuit,
+    render_pragmas: &RenderPragmas,
+    statistics: &mut CircuitStatistics,
+) {
+    for operation in circuit.iter() {
+        let tags = operation.tags();
+        collect_symbols(operation, &mut statistics.symbolic_parameters);
+        if tags.contains(&"Measurement") {
+            statistics.measurement_count += 1;
+        }
+        if operation.hqslang().starts_with("Definition") {
+            statistics.number_classical_registers += 1;
+        } else if tags.contains(&"PragmaOperation") {
+            if !is_rendered(operation, render_pragmas) {
+                *statistics
+                    .hidden_pragmas
+                    .entry(operation.hqslang().to_owned())
+                    .or_default() += 1;
+            }
+        } else {
+            *statistics
+                .gate_counts
+                .entry(operation.hqslang().to_owned())
+                .or_default() += 1;
+            if tags.contains(&"TwoQubitGateOperation") {
+                statistics.two_qubit_gate_count += 1;
+            }
+        }
+        match operation {
+            Operation::PragmaLoop(op) => count_operations(op.circuit(), render_pragmas, statistics),
+            Operation::PragmaConditional(op) => {
+                count_operations(op.circuit(), render_pragmas, statistics)
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Computes the statistics of a qoqo circuit.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to inspect.
+/// * `render_pragmas` - Whether to render Pragma Operations or not.
+/// * `layout_mode` - How to place the operations in the columns, sequentially if None.
+///
+/// ## Returns
+///
+/// * `CircuitStatistics` - The statistics of the circuit.
+pub fn circuit_into_statistics(
+    circuit: &Circuit,
+    render_pragmas: RenderPragmas,
+    layout_mode: Option<LayoutMode>,
+) -> Result<CircuitStatistics, RoqollageError> {
+    // The operations not supported by the backend take a column as placeholders.
+    let (circuit_gates, bosonic_gates, classical_gates) = circuit_into_highlighted_cells(
+        circuit,
+        &render_pragmas,
+        NumberFormat::default(),
+        layout_mode.unwrap_or_default(),
+        &HashMap::new(),
+        Some(&mut Vec::new()),
+    )?;
+    let mut statistics = CircuitStatistics {
+        number_qubits: circuit_gates.len(),
+        number_modes: bosonic_gates.len(),
+        ..Default::default()
+    };
+    let (columns, _) = cells_into_columns(
+        circuit_gates
+            .into_iter()
+            .chain(bosonic_gates)
+            .chain(classical_gates)
+            .collect(),
+    );
+    statistics.depth = columns
+        .iter()
+        .filter(|column| column.iter().any(|cell| *cell != Cell::Idle))
+        .count();
+
+    count_operations(circuit, &render_pragmas, &mut statistics);
+    Ok(statistics)
+}
+
+/// Joins the formatted items of a table cell, or returns `none` if there is none.
+fn join_or_none(names: impl Iterator<Item = String>) -> String {
+    let names: Vec<String> = names.collect();
+    if names.is_empty() {
+        "none".to_owned()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Converts the statistics of a circuit to a Typst table.
+///
+/// # Arguments
+///
+/// * `statistics` - The statistics of the circuit.
+///
+/// # Returns
+///
+/// * `String` - The Typst markup of the table.
+pub(crate) fn statistics_table(statistics: &CircuitStatistics) -> String {
+    let raw = |name: &str| format!("#raw({})", typst_string(name));
+    let rows = [
+        ("Qubits", statistics.number_qubits.to_string()),
+        ("Bosonic modes", statistics.number_modes.to_string()),
+        (
+            "Classical registers",
+            statistics.number_classical_registers.to_string(),
+        ),
+        ("Depth", statistics.depth.to_string()),
+        (
+            "Gates",
+            join_or_none(
+                statistics
+                    .gate_counts
+                    .iter()
+                    .map(|(name, count)| format!("{}: {count}", raw(name))),
+            ),
+        ),
+        (
+            "Two-qubit gates",
+            statistics.two_qubit_gate_count.to_string(),
+        ),
+        ("Measurements", statistics.measurement_count.to_string()),
+        (
+            "Symbolic parameters",
+            join_or_none(statistics.symbolic_parameters.iter().map(|name| raw(name))),
+        ),
+        (
+            "Hidden pragmas",
+            join_or_none(
+                statistics
+                    .hidden_pragmas
+                    .iter()
+                    .map(|(name, count)| format!("{}: {count}", raw(name))),
+            ),
+        ),
+    ];
+    format!(
+        "#table(\n  columns: 2,\n  align: left,\n  stroke: 0.5pt + gray,\n  table.cell(colspan: 2)[*Circuit statistics*],\n{}\n)\n",
+        rows.iter()
+            .map(|(label, value)| format!("  [{label}], [{value}],"))
+            .collect::<Vec<String>>()
+            .join("\n")
+    )
+}
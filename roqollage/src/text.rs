@@ -0,0 +1,556 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use roqoqo::Circuit;
+
+use crate::{cells_into_columns, circuit_into_cells, Cell, RenderOptions, RoqollageError, Wire};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Characters used to draw the text representation of a circuit.
+pub enum TextCharset {
+    /// Unicode box-drawing characters.
+    #[default]
+    Unicode,
+    /// Plain ASCII characters.
+    Ascii,
+}
+
+impl FromStr for TextCharset {
+    type Err = RoqollageError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unicode" => Ok(TextCharset::Unicode),
+            "ascii" => Ok(TextCharset::Ascii),
+            _ => Err(RoqollageError::InvalidOption {
+                msg: format!(r#"Invalid charset: {s}, use `unicode` or `ascii`."#),
+            }),
+        }
+    }
+}
+
+impl TextCharset {
+    /// Returns the character of a wire with the given number of lines.
+    fn wire(&self, n_lines: usize) -> char {
+        match (self, n_lines > 1) {
+            (TextCharset::Unicode, false) => '─',
+            (TextCharset::Unicode, true) => '═',
+            (TextCharset::Ascii, false) => '-',
+            (TextCharset::Ascii, true) => '=',
+        }
+    }
+
+    /// Returns the character of a vertical link, doubled for the links to classical registers.
+    fn link(&self, classical: bool) -> char {
+        match (self, classical) {
+            (TextCharset::Unicode, false) => '│',
+            (TextCharset::Unicode, true) => '║',
+            (TextCharset::Ascii, _) => '|',
+        }
+    }
+
+    /// Returns the character of a vertical link crossing a wire with the given number of lines.
+    fn crossing(&self, classical: bool, n_lines: usize) -> char {
+        match (self, classical, n_lines > 1) {
+            (TextCharset::Unicode, false, false) => '┼',
+            (TextCharset::Unicode, false, true) => '╪',
+            (TextCharset::Unicode, true, false) => '╫',
+            (TextCharset::Unicode, true, true) => '╬',
+            (TextCharset::Ascii, _, _) => '+',
+        }
+    }
+
+    /// Returns the left and right borders of a box on a wire with the given number of lines.
+    fn box_borders(&self, n_lines: usize) -> (char, char) {
+        match (self, n_lines > 1) {
+            (TextCharset::Unicode, false) => ('┤', '├'),
+            (TextCharset::Unicode, true) => ('╡', '╞'),
+            (TextCharset::Ascii, _) => ('|', '|'),
+        }
+    }
+
+    /// Returns the character of the vertical borders of the gates spanning several wires.
+    fn box_side(&self) -> char {
+        match self {
+            TextCharset::Unicode => '│',
+            TextCharset::Ascii => '|',
+        }
+    }
+
+    /// Returns the character of the vertical lines of slices and groups.
+    fn separator(&self, is_group: bool) -> char {
+        match (self, is_group) {
+            (TextCharset::Unicode, false) => '┆',
+            (TextCharset::Unicode, true) => '┊',
+            (TextCharset::Ascii, _) => ':',
+        }
+    }
+
+    /// Returns the symbol of a control dot.
+    fn control(&self) -> &'static str {
+        match self {
+            TextCharset::Unicode => "●",
+            TextCharset::Ascii => "*",
+        }
+    }
+
+    /// Returns the symbol of the target of a controlled Pauli X.
+    fn target(&self) -> &'static str {
+        match self {
+            TextCharset::Unicode => "⊕",
+            TextCharset::Ascii => "(+)",
+        }
+    }
+
+    /// Returns the symbol of a swap cross.
+    fn swap(&self) -> &'static str {
+        match self {
+            TextCharset::Unicode => "╳",
+            TextCharset::Ascii => "x",
+        }
+    }
+}
+
+/// Returns the Unicode character of the Typst symbols displayed in the gates.
+///
+/// # Arguments
+///
+/// * `name` - The name of the Typst symbol, without its variant.
+fn unicode_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "omicron" => "ο",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "dagger" => "†",
+        "sqrt" => "√",
+        "dot" => "·",
+        "dots" => "…",
+        _ => return None,
+    })
+}
+
+/// Converts the Typst math content of a cell to plain text.
+///
+/// The quotes of the Typst strings and the escape characters are removed and, with the Unicode
+/// charset, the Typst symbols are replaced by their Unicode characters.
+///
+/// # Arguments
+///
+/// * `content` - The Typst math content.
+/// * `charset` - The characters used to draw the circuit.
+///
+/// # Returns
+///
+/// * `String` - The plain text representation of the content.
+pub(crate) fn typst_math_to_text(content: &str, charset: TextCharset) -> String {
+    let mut text = String::new();
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' => match chars.next() {
+                Some('n') if in_string => text.push(' '),
+                Some(escaped) => text.push(escaped),
+                None => (),
+            },
+            '"' => in_string = !in_string,
+            '\n' => text.push(' '),
+            '°' if charset == TextCharset::Ascii => text.push_str("deg"),
+            character if !in_string && character.is_ascii_alphabetic() => {
+                let mut word = character.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_ascii_alphanumeric() || next == '_' || next == '.' {
+                        word.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let main_variant = word.split('.').next().unwrap_or_default();
+                match (charset, unicode_symbol(main_variant)) {
+                    (TextCharset::Unicode, Some(symbol)) => text.push_str(symbol),
+                    (TextCharset::Ascii, _) if main_variant == "dagger" => text.push_str("dg"),
+                    (TextCharset::Ascii, _) if main_variant == "dot" => text.push('*'),
+                    (TextCharset::Ascii, _) if main_variant == "dots" => text.push_str("..."),
+                    _ if main_variant == "mat" && chars.peek() == Some(&'(') => (),
+                    _ => text.push_str(&word),
+                }
+            }
+            character => text.push(character),
+        }
+    }
+    match charset {
+        TextCharset::Unicode => text
+            .replace("^(†)", "†")
+            .replace("=>", "⇒")
+            .replace("->", "→"),
+        TextCharset::Ascii => text.replace("^(dg)", "^dg"),
+    }
+}
+
+/// Index of the line of a wire in the text representation, the first line holds the labels.
+#[inline]
+fn wire_line(row: usize) -> usize {
+    1 + 2 * row
+}
+
+/// Index of the line below a wire in the text representation.
+#[inline]
+fn gap_line(row: usize) -> usize {
+    2 + 2 * row
+}
+
+/// Creates an empty column of the text representation.
+///
+/// # Arguments
+///
+/// * `width` - The width of the column.
+/// * `wire_lines` - The number of lines of each wire.
+/// * `charset` - The characters used to draw the circuit.
+fn blank_column(width: usize, wire_lines: &[usize], charset: TextCharset) -> Vec<Vec<char>> {
+    let mut lines = vec![vec![' '; width]; 1 + 2 * wire_lines.len()];
+    for (row, &n_lines) in wire_lines.iter().enumerate() {
+        lines[wire_line(row)] = vec![charset.wire(n_lines); width];
+    }
+    lines
+}
+
+/// Writes a text in the middle of a line.
+///
+/// # Returns
+///
+/// * `usize` - The index of the first character of the text in the line.
+fn write_centered(line: &mut [char], text: &[char]) -> usize {
+    let start = line.len().saturating_sub(text.len()) / 2;
+    for (index, &character) in text.iter().enumerate() {
+        if let Some(slot) = line.get_mut(start + index) {
+            *slot = character;
+        }
+    }
+    start
+}
+
+/// Renders a column of cells taking a column of the circuit.
+///
+/// # Arguments
+///
+/// * `cells` - The cell of each wire in the column.
+/// * `wire_lines` - The number of lines of each wire.
+/// * `n_qubits` - The number of qubit wires.
+/// * `n_bosons` - The number of bosonic mode wires.
+/// * `charset` - The characters used to draw the circuit.
+fn render_column(
+    cells: &[Cell],
+    wire_lines: &[usize],
+    n_qubits: usize,
+    n_bosons: usize,
+    charset: TextCharset,
+) -> Vec<Vec<char>> {
+    let n_rows = cells.len();
+    let cores: Vec<Option<(Vec<char>, bool)>> = cells
+        .iter()
+        .map(|cell| match cell.unhighlighted() {
+            Cell::Gate { content, .. }
+            | Cell::MultiGate { content, .. }
+            | Cell::LinkedGate { content, .. } => {
+                Some((typst_math_to_text(content, charset).chars().collect(), true))
+            }
+            Cell::Control { .. } => Some((charset.control().chars().collect(), false)),
+            Cell::Target => Some((charset.target().chars().collect(), false)),
+            Cell::Swap { .. } => Some((charset.swap().chars().collect(), false)),
+            Cell::Meter { .. } => Some((vec!['M'], true)),
+            _ => None,
+        })
+        .collect();
+    let labels: Vec<Option<Vec<char>>> = cells
+        .iter()
+        .map(|cell| match cell.unhighlighted() {
+            Cell::Control {
+                label: Some(label), ..
+            } => Some(typst_math_to_text(label, charset).chars().collect()),
+            _ => None,
+        })
+        .collect();
+    let width = cores
+        .iter()
+        .flatten()
+        .map(|(core, boxed)| core.len() + if *boxed { 2 } else { 0 })
+        .chain(labels.iter().flatten().map(Vec::len))
+        .max()
+        .unwrap_or(1)
+        + 2;
+    let middle = (width - 1) / 2;
+    let mut lines = blank_column(width, wire_lines, charset);
+
+    // Draws the content of the cells.
+    let mut covered = vec![false; n_rows];
+    for (row, core) in cores.iter().enumerate() {
+        let Some((core, boxed)) = core else {
+            continue;
+        };
+        covered[row] = true;
+        let text: Vec<char> = if *boxed {
+            let (left, right) = charset.box_borders(wire_lines[row]);
+            std::iter::once(left)
+                .chain(core.iter().copied())
+                .chain(std::iter::once(right))
+                .collect()
+        } else {
+            core.clone()
+        };
+        let start = write_centered(&mut lines[wire_line(row)], &text);
+        if let Cell::MultiGate { n_wires, .. } = *cells[row].unhighlighted() {
+            let end = start + text.len() - 1;
+            let last_row = (row + n_wires.max(1) - 1).min(n_rows - 1);
+            for spanned_row in row..last_row {
+                let gap = &mut lines[gap_line(spanned_row)];
+                gap[start] = charset.box_side();
+                gap[end] = charset.box_side();
+                let (left, right) = charset.box_borders(wire_lines[spanned_row + 1]);
+                let wire = &mut lines[wire_line(spanned_row + 1)];
+                wire[start] = left;
+                wire[start + 1..end].fill(' ');
+                wire[end] = right;
+                covered[spanned_row + 1] = true;
+            }
+        }
+        if let Some(label) = &labels[row] {
+            write_centered(&mut lines[gap_line(row)], label);
+        }
+    }
+
+    // Draws the vertical links between the cells.
+    for (row, cell) in cells.iter().enumerate() {
+        let (target, classical) = match cell.unhighlighted() {
+            Cell::LinkedGate { target, .. } => (Some(*target), false),
+            Cell::Control { target, .. } | Cell::Swap { target, .. } => (*target, false),
+            Cell::Meter { target } => (*target, true),
+            _ => (None, false),
+        };
+        let Some(target) = target else {
+            continue;
+        };
+        let target_row = target.row(n_qubits, n_bosons).min(n_rows - 1);
+        let (first_row, last_row) = (row.min(target_row), row.max(target_row));
+        for crossed_row in first_row..last_row {
+            lines[gap_line(crossed_row)][middle] = charset.link(classical);
+            if crossed_row > first_row && !covered[crossed_row] {
+                lines[wire_line(crossed_row)][middle] =
+                    charset.crossing(classical, wire_lines[crossed_row]);
+            }
+        }
+    }
+    lines
+}
+
+/// Renders a slice or a group, drawn between two columns of the circuit.
+///
+/// # Arguments
+///
+/// * `label` - The text displayed above the line.
+/// * `rows` - The first and last rows crossed by the line.
+/// * `is_group` - Whether the line is the border of a group.
+/// * `wire_lines` - The number of lines of each wire.
+/// * `charset` - The characters used to draw the circuit.
+fn render_separator(
+    label: &str,
+    rows: (usize, usize),
+    is_group: bool,
+    wire_lines: &[usize],
+    charset: TextCharset,
+) -> Vec<Vec<char>> {
+    let label: Vec<char> = label.chars().collect();
+    let width = label.len().max(1) + usize::from(!label.is_empty());
+    let mut lines = blank_column(width, wire_lines, charset);
+    lines[0][..label.len()].copy_from_slice(&label);
+    let (first_row, last_row) = rows;
+    for row in first_row..=last_row {
+        lines[wire_line(row)][0] = charset.separator(is_group);
+        if row < last_row {
+            lines[gap_line(row)][0] = charset.separator(is_group);
+        }
+    }
+    lines
+}
+
+/// Converts a qoqo circuit to a text representation.
+///
+/// The circuit is laid out in the same way as its Typst representation and drawn with
+/// box-drawing characters, or plain ASCII characters, so that it can be displayed in a terminal
+/// without the Typst compiler, the fonts or a network access.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `charset` - The characters used to draw the circuit.
+/// * `options` - The options of the circuit representation. The maximum length, page, device and
+///   statistics options only apply to Typst documents and are not used.
+///
+/// ## Returns
+///
+/// * `String` - The text representation of the circuit.
+pub fn circuit_into_text_str(
+    circuit: &Circuit,
+    charset: TextCharset,
+    options: &RenderOptions,
+) -> Result<String, RoqollageError> {
+    let (circuit_gates, bosonic_gates, mut classical_gates) = circuit_into_cells(
+        circuit,
+        &options.render_pragmas,
+        options.number_format,
+        options.layout_mode,
+    )?;
+    let wire_labels = &options.wire_labels;
+    wire_labels.relabel_registers(&mut classical_gates);
+    let n_qubits = circuit_gates.len();
+    let n_bosons = bosonic_gates.len();
+    let wires: Vec<Wire> = (0..n_qubits)
+        .map(Wire::Qubit)
+        .chain((0..n_bosons).map(Wire::Boson))
+        .chain((0..classical_gates.len()).map(Wire::Classical))
+        .collect();
+    let rows: Vec<Vec<Cell>> = circuit_gates
+        .into_iter()
+        .chain(bosonic_gates)
+        .chain(classical_gates)
+        .collect();
+    let n_rows = rows.len();
+    if n_rows == 0 {
+        return Ok(String::new());
+    }
+
+    // Reads the columns of the representation and the cells drawn between them.
+    let (columns, boundaries) = cells_into_columns(rows);
+
+    let mut labels: Vec<String> = wires
+        .iter()
+        .map(|wire| match wire {
+            Wire::Qubit(_) | Wire::Boson(_) => typst_math_to_text(
+                &wire_labels.wire_label(*wire, options.initialization_mode),
+                charset,
+            ),
+            Wire::Classical(index) => format!("c[{index}]"),
+        })
+        .collect();
+    let mut wire_lines = vec![1_usize; n_rows];
+    let mut group_ends: Vec<(usize, usize, usize)> = Vec::new();
+    let mut segments: Vec<Vec<Vec<char>>> = Vec::new();
+    for (index, boundary) in boundaries.into_iter().enumerate() {
+        for (_, first_row, last_row) in group_ends.iter().filter(|(end, _, _)| *end == index) {
+            segments.push(render_separator(
+                "",
+                (*first_row, *last_row),
+                true,
+                &wire_lines,
+                charset,
+            ));
+        }
+        for (row, cell) in boundary {
+            match cell {
+                Cell::RegisterName(name) => labels[row] = name,
+                Cell::WireLines(n_lines) => wire_lines[row] = n_lines,
+                Cell::Slice { label, .. } => segments.push(render_separator(
+                    &typst_math_to_text(&label, charset),
+                    (0, n_rows - 1),
+                    false,
+                    &wire_lines,
+                    charset,
+                )),
+                Cell::Group {
+                    n_wires,
+                    length,
+                    label,
+                } => {
+                    let last_row = (row + n_wires.max(1) - 1).min(n_rows - 1);
+                    group_ends.push((index + length, row, last_row));
+                    segments.push(render_separator(
+                        &label,
+                        (row, last_row),
+                        true,
+                        &wire_lines,
+                        charset,
+                    ));
+                }
+                _ => (),
+            }
+        }
+        if let Some(cells) = columns.get(index) {
+            segments.push(render_column(
+                cells,
+                &wire_lines,
+                n_qubits,
+                n_bosons,
+                charset,
+            ));
+        }
+    }
+
+    let label_width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or_default();
+    let mut lines: Vec<String> = (0..1 + 2 * n_rows)
+        .map(|line| {
+            if line % 2 == 1 {
+                format!("{:<label_width$} ", labels[line / 2])
+            } else {
+                " ".repeat(label_width + 1)
+            }
+        })
+        .collect();
+    for segment in segments {
+        for (line, characters) in lines.iter_mut().zip(segment) {
+            line.extend(characters);
+        }
+    }
+    Ok(lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim_end()))
+        .filter(|(index, line)| index % 2 == 1 || !line.is_empty())
+        .map(|(_, line)| line)
+        .collect::<Vec<&str>>()
+        .join("\n"))
+}
@@ -0,0 +1,372 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use image::DynamicImage;
+use qoqo_calculator::CalculatorFloat;
+use roqoqo::{
+    devices::Device,
+    operations::{InvolveQubits, InvolvedQubits, Operate, Operation},
+    Circuit,
+};
+
+use crate::{
+    backend_from_config, circuit_into_cells, format_calculator, typst_string, BackendConfig, Cell,
+    LayoutMode, NumberFormat, RenderPragmas, RoqollageError, TYPST_PREAMBLE,
+};
+
+/// Horizontal position of the start of the time axis, leaving room for the wire names, in points.
+const TIME_ORIGIN: f64 = 40.0;
+/// Vertical distance between two qubit wires, in points.
+const WIRE_SPACING: f64 = 30.0;
+/// Height of the boxes drawn for the operations, in points.
+const BOX_HEIGHT: f64 = 18.0;
+/// Width of the shortest operation, if the total width allows it, in points.
+const MIN_BOX_WIDTH: f64 = 24.0;
+/// Minimal and maximal width of the time axis, in points.
+const AXIS_WIDTH: (f64, f64) = (400.0, 2000.0);
+
+/// Operation placed on the time axis.
+#[derive(Debug, Clone, PartialEq)]
+struct TimedOperation {
+    /// Typst markup displayed in the boxes of the operation.
+    label: String,
+    /// Qubits the operation acts on, in ascending order.
+    qubits: Vec<usize>,
+    /// Start time of the operation.
+    start: f64,
+    /// Duration of the operation.
+    duration: f64,
+    /// Whether the operation is a PragmaSleep.
+    is_sleep: bool,
+}
+
+/// Returns the duration of an operation from the duration map, the device or the default duration.
+///
+/// # Arguments
+///
+/// * `operation` - The operation.
+/// * `qubits` - The qubits the operation acts on, in ascending order.
+/// * `device` - The device providing the gate times, if any.
+/// * `durations` - The durations of the operations by name, overriding the gate times of the device.
+///
+/// # Returns
+///
+/// * `Ok(Option<f64>)` - The duration of the operation, None if it is not known.
+/// * `Err(RoqollageError)` - The sleep time of a PragmaSleep is symbolic.
+fn operation_duration(
+    operation: &Operation,
+    qubits: &[usize],
+    device: Option<&dyn Device>,
+    durations: &HashMap<String, f64>,
+) -> Result<Option<f64>, RoqollageError> {
+    if let Operation::PragmaSleep(op) = operation {
+        return match op.sleep_time() {
+            CalculatorFloat::Float(time) => Ok(Some(*time)),
+            CalculatorFloat::Str(time) => Err(RoqollageError::InvalidOperation {
+                index: None,
+                msg: format!("Symbolic sleep time {time} can't be placed on the time axis."),
+            }),
+        };
+    }
+    if let Some(duration) = durations.get(operation.hqslang()) {
+        return Ok(Some(*duration));
+    }
+    let Some(device) = device else {
+        return Ok(None);
+    };
+    let hqslang = operation.hqslang();
+    Ok(match qubits {
+        [qubit] => device.single_qubit_gate_time(hqslang, qubit),
+        [first, second] => device
+            .two_qubit_gate_time(hqslang, first, second)
+            .or_else(|| device.two_qubit_gate_time(hqslang, second, first)),
+        _ => device.multi_qubit_gate_time(hqslang, qubits),
+    })
+}
+
+/// Returns the Typst markup naming an operation in its boxes.
+///
+/// The math content of the gate drawn by the circuit representation is used when there is one,
+/// the name of the operation otherwise.
+fn operation_label(operation: &Operation, number_format: NumberFormat) -> String {
+    if let Operation::PragmaSleep(_) = operation {
+        return "Sleep".to_owned();
+    }
+    let mut circuit = Circuit::new();
+    circuit.add_operation(operation.clone());
+    let content = circuit_into_cells(
+        &circuit,
+        &RenderPragmas::All,
+        number_format,
+        LayoutMode::Sequential,
+    )
+    .ok()
+    .and_then(|(qubit_cells, _, _)| {
+        qubit_cells
+            .into_iter()
+            .flatten()
+            .find_map(|cell| match cell {
+                Cell::Gate { content, .. }
+                | Cell::MultiGate { content, .. }
+                | Cell::LinkedGate { content, .. } => Some(content),
+                Cell::Meter { .. } => Some("\"M\"".to_owned()),
+                _ => None,
+            })
+    });
+    match content {
+        Some(content) => format!("$ {content} $"),
+        None => format!("#{}", typst_string(operation.hqslang())),
+    }
+}
+
+/// Places the operations of a circuit on the time axis, each one as early as its qubits allow.
+///
+/// # Arguments
+///
+/// * `circuit` - The circuit to schedule.
+/// * `device` - The device providing the gate times, if any.
+/// * `durations` - The durations of the operations by name, overriding the gate times of the device.
+/// * `default_duration` - The duration of the operations without known duration.
+/// * `number_format` - How the numbers are displayed.
+///
+/// # Returns
+///
+/// * `Ok((Vec<TimedOperation>, usize))` - The scheduled operations and the number of qubits.
+/// * `Err(RoqollageError)` - The duration of an operation is not known.
+fn schedule_operations(
+    circuit: &Circuit,
+    device: Option<&dyn Device>,
+    durations: &HashMap<String, f64>,
+    default_duration: Option<f64>,
+    number_format: NumberFormat,
+) -> Result<(Vec<TimedOperation>, usize), RoqollageError> {
+    let number_qubits = circuit
+        .iter()
+        .filter_map(|operation| match operation.involved_qubits() {
+            InvolvedQubits::Set(qubits) => qubits.into_iter().max().map(|qubit| qubit + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or_default();
+    let mut free_times = vec![0.0_f64; number_qubits];
+    let mut timed_operations = Vec::new();
+    for (index, operation) in circuit.iter().enumerate() {
+        let mut qubits: Vec<usize> = match operation.involved_qubits() {
+            InvolvedQubits::Set(qubits) => qubits.into_iter().collect(),
+            InvolvedQubits::All => (0..number_qubits).collect(),
+            InvolvedQubits::None => continue,
+        };
+        if qubits.is_empty() {
+            continue;
+        }
+        qubits.sort();
+        let is_sleep = matches!(operation, Operation::PragmaSleep(_));
+        let is_pragma = operation.hqslang().starts_with("Pragma");
+        if is_pragma && !is_sleep && !durations.contains_key(operation.hqslang()) {
+            continue;
+        }
+        let duration = match operation_duration(operation, &qubits, device, durations)
+            .map_err(|err| err.at_operation(index))?
+        {
+            Some(duration) => duration,
+            None => default_duration.ok_or_else(|| RoqollageError::InvalidOperation {
+                index: Some(index),
+                msg: format!(
+                    "No duration for {}, add it to the durations or set a default duration.",
+                    operation.hqslang()
+                ),
+            })?,
+        };
+        let start = qubits
+            .iter()
+            .map(|qubit| free_times[*qubit])
+            .fold(0.0, f64::max);
+        for qubit in qubits.iter() {
+            free_times[*qubit] = start + duration;
+        }
+        timed_operations.push(TimedOperation {
+            label: operation_label(operation, number_format),
+            qubits,
+            start,
+            duration,
+            is_sleep,
+        });
+    }
+    Ok((timed_operations, number_qubits))
+}
+
+/// Returns the step between the ticks of a time axis: 1, 2 or 5 times a power of ten.
+fn tick_step(total_time: f64) -> f64 {
+    let raw_step = total_time / 8.0;
+    let magnitude = 10_f64.powf(raw_step.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= raw_step)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Converts a qoqo circuit to a typst string drawing it on a time axis.
+///
+/// Each operation is drawn as a box whose horizontal position and width are proportional to its
+/// start time and duration, and is placed as early as its qubits allow. The durations are taken
+/// from the duration map, then from the gate times of the device, then from the default duration.
+/// PragmaSleep operations are drawn with their sleep time, the other pragmas only if they appear
+/// in the duration map. A time axis runs along the bottom of the drawing.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `device` - The device providing the gate times, if any.
+/// * `durations` - The durations of the operations by name, overriding the gate times of the device.
+/// * `default_duration` - The duration of the operations without known duration. An error is
+///   returned for these operations if None.
+/// * `number_format` - How the numbers are displayed, the default format if None.
+///
+/// ## Returns
+///
+/// * `Ok(String)` - The string representation of the circuit in Typst.
+/// * `Err(RoqollageError)` - The duration of an operation is not known or is symbolic.
+pub fn circuit_into_timing_typst_str(
+    circuit: &Circuit,
+    device: Option<&dyn Device>,
+    durations: &HashMap<String, f64>,
+    default_duration: Option<f64>,
+    number_format: Option<NumberFormat>,
+) -> Result<String, RoqollageError> {
+    let number_format = number_format.unwrap_or_default();
+    let (timed_operations, number_qubits) =
+        schedule_operations(circuit, device, durations, default_duration, number_format)?;
+    let total_time = timed_operations
+        .iter()
+        .map(|operation| operation.start + operation.duration)
+        .fold(0.0, f64::max);
+    let min_duration = timed_operations
+        .iter()
+        .map(|operation| operation.duration)
+        .filter(|duration| *duration > 0.0)
+        .fold(f64::INFINITY, f64::min);
+    let scale = if total_time > 0.0 {
+        (MIN_BOX_WIDTH / min_duration)
+            .max(AXIS_WIDTH.0 / total_time)
+            .min(AXIS_WIDTH.1 / total_time)
+    } else {
+        0.0
+    };
+    let axis_width = total_time * scale;
+    let wire_y = |qubit: usize| WIRE_SPACING * (qubit as f64 + 0.5);
+    let axis_y = WIRE_SPACING * number_qubits as f64 + 5.0;
+
+    let mut typst_str = format!(
+        "{TYPST_PREAMBLE}#set text(size: 8pt)\n#box(width: {:.1}pt, height: {:.1}pt)[\n",
+        TIME_ORIGIN + axis_width + 30.0,
+        axis_y + 30.0,
+    );
+    for qubit in 0..number_qubits {
+        typst_str.push_str(&format!(
+            "  #place(top + left, dx: 0pt, dy: {:.1}pt, $q[{qubit}]$)\n  #place(top + left, line(start: ({TIME_ORIGIN:.1}pt, {:.1}pt), end: ({:.1}pt, {:.1}pt), stroke: 0.8pt))\n",
+            wire_y(qubit) - 5.0,
+            wire_y(qubit),
+            TIME_ORIGIN + axis_width,
+            wire_y(qubit),
+        ));
+    }
+    for operation in timed_operations.iter() {
+        let x = TIME_ORIGIN + operation.start * scale;
+        let width = operation.duration * scale;
+        let (first, last) = (
+            operation.qubits[0],
+            operation.qubits[operation.qubits.len() - 1],
+        );
+        if first != last {
+            typst_str.push_str(&format!(
+                "  #place(top + left, line(start: ({:.1}pt, {:.1}pt), end: ({:.1}pt, {:.1}pt), stroke: 0.8pt))\n",
+                x + width / 2.0,
+                wire_y(first),
+                x + width / 2.0,
+                wire_y(last),
+            ));
+        }
+        let fill = if operation.is_sleep {
+            "gray.lighten(60%)"
+        } else {
+            "white"
+        };
+        for qubit in operation.qubits.iter() {
+            typst_str.push_str(&format!(
+                "  #place(top + left, dx: {x:.1}pt, dy: {:.1}pt, rect(width: {width:.1}pt, height: {BOX_HEIGHT:.1}pt, fill: {fill}, stroke: 0.8pt, inset: 0pt, align(center + horizon, {})))\n",
+                wire_y(*qubit) - BOX_HEIGHT / 2.0,
+                if operation.label.starts_with('$') {
+                    format!("[{}]", operation.label)
+                } else {
+                    format!("text(size: 6pt)[{}]", operation.label)
+                },
+            ));
+        }
+    }
+
+    // Draws the time axis.
+    typst_str.push_str(&format!(
+        "  #place(top + left, line(start: ({TIME_ORIGIN:.1}pt, {axis_y:.1}pt), end: ({:.1}pt, {axis_y:.1}pt), stroke: 0.8pt))\n  #place(top + left, dx: 0pt, dy: {:.1}pt, [Time])\n",
+        TIME_ORIGIN + axis_width,
+        axis_y - 4.0,
+    ));
+    if total_time > 0.0 {
+        let step = tick_step(total_time);
+        let number_ticks = (total_time / step + 1e-9).floor() as usize;
+        for tick in 0..=number_ticks {
+            let time = tick as f64 * step;
+            let x = TIME_ORIGIN + time * scale;
+            typst_str.push_str(&format!(
+                "  #place(top + left, line(start: ({x:.1}pt, {axis_y:.1}pt), end: ({x:.1}pt, {:.1}pt), stroke: 0.8pt))\n  #place(top + left, dx: {:.1}pt, dy: {:.1}pt, box(width: 40pt, align(center, text(size: 6pt)[${}$])))\n",
+                axis_y + 3.0,
+                x - 20.0,
+                axis_y + 5.0,
+                format_calculator(&CalculatorFloat::Float(time), number_format),
+            ));
+        }
+    }
+    typst_str.push_str("]\n");
+    Ok(typst_str)
+}
+
+/// Converts a qoqo circuit to an image drawing it on a time axis.
+///
+///  ## Arguments
+///
+/// * `circuit` - The circuit to convert.
+/// * `pixels_per_point` - The pixel per point ratio.
+/// * `device` - The device providing the gate times, if any.
+/// * `durations` - The durations of the operations by name, overriding the gate times of the device.
+/// * `default_duration` - The duration of the operations without known duration. An error is
+///   returned for these operations if None.
+/// * `number_format` - How the numbers are displayed, the default format if None.
+/// * `backend_config` - The configuration of the Typst backend, the default one if None.
+///
+/// ## Returns
+///
+/// * DynamicImage: The image reprensenting the circuit.
+pub fn circuit_timing_to_image(
+    circuit: &Circuit,
+    pixels_per_point: Option<f32>,
+    device: Option<&dyn Device>,
+    durations: &HashMap<String, f64>,
+    default_duration: Option<f64>,
+    number_format: Option<NumberFormat>,
+    backend_config: Option<&BackendConfig>,
+) -> Result<DynamicImage, RoqollageError> {
+    let typst_str =
+        circuit_into_timing_typst_str(circuit, device, durations, default_duration, number_format)?;
+    backend_from_config(backend_config)?.render_typst_str(typst_str, pixels_per_point)
+}
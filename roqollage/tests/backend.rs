@@ -12,14 +12,21 @@
 //
 //! Testing the roqollage backend
 
-use std::f32::consts::PI;
+use std::{f32::consts::PI, str::FromStr};
 
 use qoqo_calculator::CalculatorFloat;
 use roqollage::{
-    circuit_into_typst_str, circuit_to_image, InitializationMode, RenderPragmas, TypstBackend,
+    circuit_into_lenient_typst_str, circuit_into_typst_str, circuit_to_image,
+    circuit_to_lenient_image, circuit_to_pdf, circuit_to_svg, circuits_to_images,
+    circuits_to_lenient_images, BackendConfig, InitializationMode, LayoutMode, NumberFormat,
+    PackageSource, RenderOptions, RenderPragmas, TypstBackend,
 };
 use roqoqo::{operations::*, Circuit};
 use serial_test::serial;
+use struqture::{
+    prelude::*,
+    spins::{PauliHamiltonian, PauliProduct},
+};
 use typst::layout::PagedDocument;
 
 #[test]
@@ -47,13 +54,21 @@ fn test_str() {
     circuit.add_operation(SWAP::new(2, 1));
     circuit.add_operation(Toffoli::new(0, 1, 4));
 
-    assert!(circuit_into_typst_str(&circuit, RenderPragmas::None, None, None, None).is_ok());
     assert!(circuit_into_typst_str(
         &circuit,
-        RenderPragmas::None,
-        Some(InitializationMode::Qubit),
-        None,
-        None
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            ..RenderOptions::default()
+        }
+    )
+    .is_ok());
+    assert!(circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            initialization_mode: Some(InitializationMode::Qubit),
+            ..RenderOptions::default()
+        }
     )
     .is_ok());
 }
@@ -77,13 +92,22 @@ fn test_max_len() {
     circuit.add_operation(SWAP::new(2, 1));
     circuit.add_operation(Toffoli::new(0, 1, 4));
 
-    assert!(circuit_into_typst_str(&circuit, RenderPragmas::None, None, None, None).is_ok());
     assert!(circuit_into_typst_str(
         &circuit,
-        RenderPragmas::None,
-        Some(InitializationMode::Qubit),
-        Some(20),
-        None
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            ..RenderOptions::default()
+        }
+    )
+    .is_ok());
+    assert!(circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            initialization_mode: Some(InitializationMode::Qubit),
+            max_length: Some(20),
+            ..RenderOptions::default()
+        }
     )
     .is_ok());
 }
@@ -136,23 +160,79 @@ fn test_image() {
     assert!(circuit_to_image(
         &circuit,
         None,
-        RenderPragmas::None,
-        Some(InitializationMode::State),
-        None,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            initialization_mode: Some(InitializationMode::State),
+            ..RenderOptions::default()
+        },
         None
     )
     .is_ok());
     assert!(circuit_to_image(
         &circuit,
         None,
-        RenderPragmas::Partial(vec!["PragmaLoop".to_owned()]),
-        Some(InitializationMode::Qubit),
-        None,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::Partial(vec!["PragmaLoop".to_owned()]),
+            initialization_mode: Some(InitializationMode::Qubit),
+            ..RenderOptions::default()
+        },
         None
     )
     .is_ok());
 }
 
+#[test]
+#[serial]
+fn test_svg() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(RotateX::new(0, -CalculatorFloat::from("theta")));
+    circuit.add_operation(MeasureQubit::new(0, "ro".to_owned(), 0));
+    circuit.add_operation(MeasureQubit::new(1, "ro".to_owned(), 1));
+
+    let svg = circuit_to_svg(
+        &circuit,
+        &RenderOptions {
+            initialization_mode: Some(InitializationMode::Qubit),
+            ..RenderOptions::default()
+        },
+        None,
+    )
+    .unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+#[serial]
+fn test_pdf() {
+    let mut circuit = Circuit::new();
+    for _ in 0..30 {
+        circuit.add_operation(Hadamard::new(0));
+        circuit.add_operation(CNOT::new(0, 1));
+    }
+
+    let single_page = circuit_to_pdf(&circuit, &RenderOptions::default(), None);
+    assert!(single_page.is_ok());
+    assert!(single_page.unwrap().starts_with(b"%PDF"));
+    let options = RenderOptions {
+        max_length: Some(10),
+        page_per_chunk: true,
+        ..RenderOptions::default()
+    };
+    let multi_page = circuit_to_pdf(&circuit, &options, None);
+    assert!(multi_page.is_ok());
+    assert!(multi_page.unwrap().starts_with(b"%PDF"));
+
+    let typst_str = circuit_into_typst_str(&circuit, &options).unwrap();
+    let n_chunks = typst_str.matches("quantum-circuit(").count();
+    let document = TypstBackend::new(typst_str).unwrap().compile().unwrap();
+    assert!(document.pages.len() > 1);
+    assert_eq!(document.pages.len(), n_chunks);
+}
+
 #[test]
 #[serial]
 fn test_flatten() {
@@ -170,9 +250,11 @@ fn test_flatten() {
     assert!(circuit_to_image(
         &circuit,
         None,
-        RenderPragmas::None,
-        Some(InitializationMode::State),
-        None,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            initialization_mode: Some(InitializationMode::State),
+            ..RenderOptions::default()
+        },
         None
     )
     .is_ok());
@@ -206,9 +288,11 @@ fn test_flatten_boson() {
     assert!(circuit_to_image(
         &circuit,
         None,
-        RenderPragmas::None,
-        Some(InitializationMode::State),
-        None,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            initialization_mode: Some(InitializationMode::State),
+            ..RenderOptions::default()
+        },
         None
     )
     .is_ok());
@@ -232,9 +316,11 @@ fn test_resonator() {
     assert!(circuit_to_image(
         &circuit,
         None,
-        RenderPragmas::None,
-        Some(InitializationMode::State),
-        None,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            initialization_mode: Some(InitializationMode::State),
+            ..RenderOptions::default()
+        },
         None
     )
     .is_ok());
@@ -271,7 +357,14 @@ fn test_values() {
         CalculatorFloat::Float((-3.0 * PI / 4.0).into()),
     ));
 
-    assert!(circuit_into_typst_str(&circuit, RenderPragmas::None, None, None, None).is_ok());
+    assert!(circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            ..RenderOptions::default()
+        }
+    )
+    .is_ok());
 }
 
 #[test]
@@ -282,17 +375,123 @@ fn test_rounding() {
     circuit.add_operation(RotateY::new(1, -CalculatorFloat::from(0.65468211)));
     circuit.add_operation(RotateX::new(0, -CalculatorFloat::FRAC_PI_2));
 
-    assert!(circuit_into_typst_str(&circuit, RenderPragmas::None, None, None, Some(6)).is_ok());
     assert!(circuit_into_typst_str(
         &circuit,
-        RenderPragmas::None,
-        Some(InitializationMode::Qubit),
-        None,
-        Some(10)
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            number_format: NumberFormat {
+                rounding_accuracy: 6,
+                ..NumberFormat::default()
+            },
+            ..RenderOptions::default()
+        }
+    )
+    .is_ok());
+    assert!(circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            initialization_mode: Some(InitializationMode::Qubit),
+            number_format: NumberFormat {
+                rounding_accuracy: 10,
+                ..NumberFormat::default()
+            },
+            ..RenderOptions::default()
+        }
     )
     .is_ok());
 }
 
+#[test]
+#[serial]
+fn test_layout_mode() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(Hadamard::new(1));
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 2));
+
+    let asap = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            layout_mode: LayoutMode::Asap,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(asap.contains("lstick($|0>$), $ H $, 1, 1,  1, [\\ ],"));
+    let alap = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            layout_mode: LayoutMode::Alap,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(alap.contains("lstick($|0>$), 1, $ H $, 1,  1, [\\ ],"));
+    let sequential = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            layout_mode: LayoutMode::Sequential,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(sequential.contains("lstick($|0>$), $ H $, 1, 1,  1, [\\ ],"));
+    assert_eq!(
+        sequential,
+        circuit_into_typst_str(&circuit, &RenderOptions::default()).unwrap()
+    );
+    let render = |circuit: &Circuit, layout_mode: LayoutMode| {
+        circuit_into_typst_str(
+            circuit,
+            &RenderOptions {
+                layout_mode,
+                ..RenderOptions::default()
+            },
+        )
+        .unwrap()
+    };
+
+    let mut body = Circuit::new();
+    body.add_operation(Hadamard::new(0));
+    let mut circuit = Circuit::new();
+    circuit.add_operation(PragmaLoop::new(2.into(), body));
+    for _ in 0..5 {
+        circuit.add_operation(PauliX::new(0));
+    }
+    let group = "gategroup(1, 1, label: \"Loop: 2 times\", stroke: (dash: \"dotted\"))";
+    assert!(render(&circuit, LayoutMode::Asap).contains(&format!(
+        "1, {group}, $ H $, $ X $, $ X $, $ X $, $ X $, $ X $,  1, )"
+    )));
+    assert!(render(&circuit, LayoutMode::Alap).contains(&format!(
+        "lstick($|0>$, label: \"Qubits\"), {group}, $ H $, $ X $, $ X $, $ X $, $ X $, $ X $,  1, )"
+    )));
+
+    let mut first_body = Circuit::new();
+    first_body.add_operation(Hadamard::new(0));
+    let mut second_body = Circuit::new();
+    second_body.add_operation(Hadamard::new(1));
+    let mut circuit = Circuit::new();
+    circuit.add_operation(PragmaLoop::new(2.into(), first_body));
+    circuit.add_operation(PragmaLoop::new(3.into(), second_body));
+    let sequential = render(&circuit, LayoutMode::Sequential);
+    assert!(sequential.contains(&format!("1, {group}, $ H $, 1, 1,  1, [\\ ],")));
+    for layout_mode in [LayoutMode::Asap, LayoutMode::Alap] {
+        let compact = render(&circuit, layout_mode);
+        assert!(compact.contains(&format!("{group}, $ H $,  1, [\\ ],")));
+        assert!(compact.len() <= sequential.len());
+    }
+
+    assert_eq!(LayoutMode::from_str("ASAP").unwrap(), LayoutMode::Asap);
+    assert_eq!(LayoutMode::from_str("alap").unwrap(), LayoutMode::Alap);
+    assert_eq!(
+        LayoutMode::from_str("sequential").unwrap(),
+        LayoutMode::Sequential
+    );
+    assert!(LayoutMode::from_str("compact").is_err());
+}
+
 #[test]
 #[serial]
 fn test_backend_today() {
@@ -300,3 +499,176 @@ fn test_backend_today() {
     let res: Result<PagedDocument, _> = typst::compile(&backend).output;
     assert!(res.is_ok());
 }
+
+#[test]
+#[serial]
+fn test_backend_config() {
+    let cache_dir = std::env::temp_dir().join("roqollage_test_backend_config");
+    let config = BackendConfig {
+        cache_dir: cache_dir.clone(),
+        ..Default::default()
+    };
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+    assert!(circuit_to_svg(&circuit, &RenderOptions::default(), Some(&config)).is_ok());
+    #[cfg(not(feature = "embedded_resources"))]
+    assert!(cache_dir.join("fonts").join("FiraMath.otf").exists());
+
+    let font_config = BackendConfig {
+        cache_dir: cache_dir.clone(),
+        font_paths: vec![cache_dir.join("missing_font.otf")],
+        ..Default::default()
+    };
+    assert!(TypstBackend::new_with_config("".to_owned(), &font_config).is_err());
+
+    let empty_packages = cache_dir.join("empty_packages");
+    std::fs::create_dir_all(&empty_packages).unwrap();
+    let local_config = BackendConfig {
+        cache_dir: cache_dir.clone(),
+        font_paths: vec![],
+        package_source: PackageSource::from_str(empty_packages.to_str().unwrap()).unwrap(),
+    };
+    assert_eq!(
+        local_config.package_source,
+        PackageSource::Directory(empty_packages)
+    );
+    let backend = TypstBackend::new_with_config(
+        r#"#import "@preview/nonexistent:0.1.0": *"#.to_owned(),
+        &local_config,
+    )
+    .unwrap();
+    let res: Result<PagedDocument, _> = typst::compile(&backend).output;
+    assert!(res.is_err());
+
+    assert_eq!(
+        PackageSource::from_str("https://example.org/packages/").unwrap(),
+        PackageSource::Url("https://example.org/packages".to_owned())
+    );
+    assert!(PackageSource::from_str("not/an/existing/directory").is_err());
+
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
+
+#[test]
+#[serial]
+fn test_backend_reuse() {
+    let mut backend = TypstBackend::new(String::new()).unwrap();
+    for angle in 0..5 {
+        let mut circuit = Circuit::new();
+        circuit.add_operation(Hadamard::new(0));
+        circuit.add_operation(RotateX::new(1, CalculatorFloat::from(angle)));
+        circuit.add_operation(CNOT::new(0, 1));
+        assert!(backend
+            .circuit_to_image(&circuit, Some(1.0), &RenderOptions::default())
+            .is_ok());
+        assert!(backend
+            .circuit_to_svg(&circuit, &RenderOptions::default())
+            .unwrap()
+            .starts_with("<svg"));
+        assert!(backend
+            .circuit_to_pdf(&circuit, &RenderOptions::default())
+            .unwrap()
+            .starts_with(b"%PDF"));
+    }
+    assert!(backend
+        .render_typst_str("#unknown-function()".to_owned(), None)
+        .is_err());
+    assert!(backend
+        .render_typst_str("#datetime.today().display()".to_owned(), None)
+        .is_ok());
+}
+
+#[test]
+#[serial]
+fn test_circuits_to_images() {
+    let mut circuits = vec![];
+    for angle in 0..8 {
+        let mut circuit = Circuit::new();
+        circuit.add_operation(Hadamard::new(0));
+        circuit.add_operation(RotateX::new(1, CalculatorFloat::from(angle)));
+        circuit.add_operation(CNOT::new(0, 1));
+        circuits.push(circuit);
+    }
+    let mut unsupported_circuit = Circuit::new();
+    unsupported_circuit.add_operation(PragmaStopParallelBlock::new(vec![], CalculatorFloat::ONE));
+    circuits.insert(3, unsupported_circuit);
+
+    let images = circuits_to_images(&circuits, Some(1.0), &RenderOptions::default(), None).unwrap();
+    assert_eq!(images.len(), circuits.len());
+    for (index, image) in images.iter().enumerate() {
+        assert_eq!(image.is_err(), index == 3);
+    }
+}
+
+/// Returns an operation the backend doesn't draw.
+fn constant_hamiltonian() -> ApplyConstantPauliHamiltonian {
+    let mut hamiltonian = PauliHamiltonian::new();
+    hamiltonian
+        .add_operator_product(PauliProduct::new().z(0).x(1), 1.0.into())
+        .unwrap();
+    ApplyConstantPauliHamiltonian::new(hamiltonian, 1.0.into())
+}
+
+#[test]
+#[serial]
+fn test_lenient() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(constant_hamiltonian());
+    circuit.add_operation(CNOT::new(0, 1));
+
+    assert!(circuit_into_typst_str(&circuit, &RenderOptions::default()).is_err());
+    let (typst_str, warnings) =
+        circuit_into_lenient_typst_str(&circuit, &RenderOptions::default()).unwrap();
+    assert!(typst_str.contains("\"ApplyConstantPauliHamiltonian\""));
+    assert_eq!(
+        warnings,
+        vec![
+            "Operation ApplyConstantPauliHamiltonian not supported by the Typst backend, drawn as a placeholder."
+                .to_owned()
+        ]
+    );
+
+    let mut empty_circuit = Circuit::new();
+    empty_circuit.add_operation(Hadamard::new(0));
+    empty_circuit.add_operation(ApplyConstantPauliHamiltonian::new(
+        PauliHamiltonian::new(),
+        1.0.into(),
+    ));
+    let (typst_str, warnings) =
+        circuit_into_lenient_typst_str(&empty_circuit, &RenderOptions::default()).unwrap();
+    assert!(typst_str.contains("slice(label: $ \"ApplyConstantPauliHamiltonian\" $"));
+    assert_eq!(warnings.len(), 1);
+
+    let (_, warnings) =
+        circuit_to_lenient_image(&circuit, Some(1.0), &RenderOptions::default(), None).unwrap();
+    assert_eq!(warnings.len(), 1);
+
+    let images = circuits_to_lenient_images(
+        &[circuit, empty_circuit, Circuit::new()],
+        Some(1.0),
+        &RenderOptions::default(),
+        None,
+    )
+    .unwrap();
+    let warning_counts: Vec<usize> = images
+        .into_iter()
+        .map(|image| image.unwrap().1.len())
+        .collect();
+    assert_eq!(warning_counts, vec![1, 1, 0]);
+}
+
+#[cfg(feature = "embedded_resources")]
+#[test]
+#[serial]
+fn test_backend_embedded_resources() {
+    let backend = TypstBackend::new(
+        r#"#import "@preview/quill:0.7.1": *
+#quantum-circuit(1, gate($H$), 1)"#
+            .to_owned(),
+    )
+    .unwrap();
+    let res: Result<PagedDocument, _> = typst::compile(&backend).output;
+    assert!(res.is_ok());
+}
@@ -0,0 +1,151 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage device representation
+
+use ndarray::array;
+use roqollage::{
+    circuit_into_typst_str, device_into_typst_str, device_to_image, device_violations,
+    DeviceViolation, DeviceViolationKind, NumberFormat, RenderOptions, RenderPragmas,
+};
+use roqoqo::{
+    devices::{AllToAllDevice, GenericDevice, SquareLatticeDevice},
+    operations::*,
+    Circuit,
+};
+
+#[test]
+fn test_device_square_lattice() {
+    let device = SquareLatticeDevice::new(2, 3, &["RotateZ".to_owned()], &["CNOT".to_owned()], 1.0);
+    let typst_str = device_into_typst_str(&device, None);
+    assert!(typst_str.contains("Qubits: 6, two-qubit edges: 7"));
+    assert!(typst_str.contains("Every qubit: $\"RotateZ\": 1.0$"));
+    assert!(typst_str.contains("Every edge: $\"CNOT\": 1.0$"));
+    // The qubits are placed on a 2x3 lattice.
+    assert!(typst_str.contains("dx: 49.0pt, dy: 49.0pt, circle"));
+    assert!(typst_str.contains("dx: 269.0pt, dy: 159.0pt, circle"));
+    assert_eq!(typst_str.matches("line(start").count(), 7);
+    assert_eq!(typst_str.matches("circle(").count(), 6);
+
+    let image = device_to_image(&device, Some(1.0), None, None);
+    assert!(image.is_ok());
+}
+
+#[test]
+fn test_device_all_to_all() {
+    let device = AllToAllDevice::new(
+        4,
+        &["RotateX".to_owned(), "RotateZ".to_owned()],
+        &["CZ".to_owned()],
+        0.5,
+    );
+    let typst_str = device_into_typst_str(&device, None);
+    assert!(typst_str.contains("Qubits: 4, two-qubit edges: 6"));
+    assert!(typst_str.contains("Every qubit: $\"RotateX\": 0.5$, $\"RotateZ\": 0.5$"));
+    assert_eq!(typst_str.matches("line(start").count(), 6);
+    assert_eq!(typst_str.matches("circle(").count(), 4);
+}
+
+#[test]
+fn test_device_generic() {
+    let mut device = GenericDevice::new(3);
+    device
+        .set_single_qubit_gate_time("RotateZ", 0, 0.1)
+        .unwrap();
+    device
+        .set_single_qubit_gate_time("RotateZ", 1, 0.2)
+        .unwrap();
+    device.set_two_qubit_gate_time("CNOT", 0, 1, 0.5).unwrap();
+    device.set_two_qubit_gate_time("CNOT", 1, 2, 0.7).unwrap();
+    device
+        .set_qubit_decoherence_rates(
+            1,
+            array![[0.0, 0.0, 0.0], [0.0, 0.01, 0.0], [0.0, 0.0, 0.02]],
+        )
+        .unwrap();
+
+    let typst_str = device_into_typst_str(
+        &device,
+        Some(NumberFormat {
+            rounding_accuracy: 2,
+            ..NumberFormat::default()
+        }),
+    );
+    assert!(typst_str.contains("Qubits: 3, two-qubit edges: 2"));
+    assert!(!typst_str.contains("Every qubit"));
+    assert!(!typst_str.contains("Every edge"));
+    assert!(typst_str.contains("[$\"RotateZ\": 0.1$]"));
+    assert!(typst_str.contains("[$\"RotateZ\": 0.2$ \\ $Gamma_(+,-,z): (0, 0.01, 0.02)$]"));
+    assert!(typst_str.contains("[$\"CNOT\": 0.5$]"));
+    assert!(typst_str.contains("[$\"CNOT\": 0.7$]"));
+}
+
+#[test]
+fn test_device_violations() {
+    let device = SquareLatticeDevice::new(2, 2, &["RotateZ".to_owned()], &["CNOT".to_owned()], 1.0);
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateZ::new(0, 0.5.into()));
+    circuit.add_operation(RotateX::new(1, 0.5.into()));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(CNOT::new(0, 3));
+    circuit.add_operation(PragmaSetNumberOfMeasurements::new(10, "ro".to_owned()));
+
+    assert_eq!(
+        device_violations(&circuit, &device),
+        vec![
+            DeviceViolation {
+                index: 1,
+                hqslang: "RotateX".to_owned(),
+                qubits: vec![1],
+                kind: DeviceViolationKind::UnsupportedGate,
+            },
+            DeviceViolation {
+                index: 3,
+                hqslang: "CNOT".to_owned(),
+                qubits: vec![0, 3],
+                kind: DeviceViolationKind::NonAdjacentQubits,
+            },
+        ]
+    );
+
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            device: Some(&device),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains("$ \"Rz\"(0.5) $"));
+    assert!(typst_str.contains("gate($ \"Rx\"(0.5) $, fill: orange.lighten(40%))"));
+    assert!(typst_str.contains("ctrl(1)"));
+    assert!(typst_str.contains("ctrl(3, fill: red.lighten(40%))"));
+    assert!(typst_str.contains("targ(fill: red.lighten(40%))"));
+    assert!(typst_str.contains("*Device violations: 2*"));
+    assert!(
+        typst_str.contains("operation \\#1 (RotateX on qubit 1): gate not supported by the device")
+    );
+    assert!(typst_str
+        .contains("operation \\#3 (CNOT on qubits 0, 3): qubits not connected in the device"));
+
+    let unchecked = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(!unchecked.contains("fill: red"));
+    assert!(!unchecked.contains("Device violations"));
+}
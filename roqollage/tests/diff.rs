@@ -0,0 +1,81 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage circuit diff representation
+
+use roqollage::{
+    circuits_diff_into_typst_str, circuits_diff_to_image, diff_circuits, OperationDiff,
+    RenderOptions,
+};
+use roqoqo::{operations::*, Circuit};
+
+#[test]
+fn test_diff_circuits() {
+    let mut before = Circuit::new();
+    before.add_operation(Hadamard::new(0));
+    before.add_operation(RotateX::new(1, 0.3.into()));
+    before.add_operation(PauliX::new(2));
+    before.add_operation(CNOT::new(0, 1));
+    let mut after = Circuit::new();
+    after.add_operation(Hadamard::new(0));
+    after.add_operation(RotateX::new(1, 0.5.into()));
+    after.add_operation(CNOT::new(0, 1));
+    after.add_operation(PauliZ::new(2));
+
+    assert_eq!(
+        diff_circuits(&before, &after),
+        vec![
+            OperationDiff::Unchanged(Hadamard::new(0).into()),
+            OperationDiff::Removed(PauliX::new(2).into()),
+            OperationDiff::Changed {
+                before: RotateX::new(1, 0.3.into()).into(),
+                after: RotateX::new(1, 0.5.into()).into(),
+            },
+            OperationDiff::Unchanged(CNOT::new(0, 1).into()),
+            OperationDiff::Added(PauliZ::new(2).into()),
+        ]
+    );
+    assert_eq!(
+        diff_circuits(&before, &before),
+        before
+            .iter()
+            .map(|operation| OperationDiff::Unchanged(operation.clone()))
+            .collect::<Vec<OperationDiff>>()
+    );
+}
+
+#[test]
+fn test_circuits_diff_typst() {
+    let mut before = Circuit::new();
+    before.add_operation(Hadamard::new(0));
+    before.add_operation(RotateX::new(1, 0.3.into()));
+    before.add_operation(PauliX::new(2));
+    let mut after = Circuit::new();
+    after.add_operation(DefinitionBit::new("ro".to_owned(), 1, true));
+    after.add_operation(Hadamard::new(0));
+    after.add_operation(RotateX::new(1, 0.5.into()));
+    after.add_operation(CNOT::new(0, 1));
+
+    let typst_str =
+        circuits_diff_into_typst_str(&before, &after, &RenderOptions::default()).unwrap();
+    assert!(typst_str.contains("fill: green.lighten(40%)"));
+    assert!(typst_str.contains("fill: red.lighten(40%)"));
+    assert!(typst_str.contains("fill: yellow.lighten(20%)"));
+    assert!(typst_str.contains("\"Rx\"(0.5)"));
+    assert!(!typst_str.contains("\"Rx\"(0.3)"));
+    assert!(typst_str.contains("added: 2"));
+    assert!(typst_str.contains("removed: 1"));
+    assert!(typst_str.contains("changed: 1"));
+
+    let image = circuits_diff_to_image(&before, &after, Some(1.0), &RenderOptions::default(), None);
+    assert!(image.is_ok());
+}
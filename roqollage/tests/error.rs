@@ -0,0 +1,141 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage errors
+
+use std::str::FromStr;
+
+use qoqo_calculator::CalculatorFloat;
+use roqollage::{
+    circuit_into_typst_str, circuit_to_image, render_typst_str, BackendConfig, InitializationMode,
+    LayoutMode, PackageSource, RenderOptions, RoqollageError,
+};
+use roqoqo::{operations::*, Circuit, RoqoqoBackendError};
+use serial_test::serial;
+use struqture::spins::PauliHamiltonian;
+
+#[test]
+fn test_unsupported_operation_index() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(ApplyConstantPauliHamiltonian::new(
+        PauliHamiltonian::new(),
+        1.0.into(),
+    ));
+    circuit.add_operation(CNOT::new(0, 1));
+
+    let err = circuit_into_typst_str(&circuit, &RenderOptions::default()).unwrap_err();
+    assert_eq!(
+        err,
+        RoqollageError::UnsupportedOperation {
+            index: Some(1),
+            hqslang: "ApplyConstantPauliHamiltonian",
+        }
+    );
+    assert_eq!(err.index(), Some(1));
+    assert_eq!(
+        err.to_string(),
+        "Operation #1 (ApplyConstantPauliHamiltonian) not supported by the Typst backend."
+    );
+    assert_eq!(
+        RoqoqoBackendError::from(err),
+        RoqoqoBackendError::OperationNotInBackend {
+            backend: "TypstBackend",
+            hqslang: "ApplyConstantPauliHamiltonian",
+        }
+    );
+}
+
+#[test]
+fn test_invalid_operation_index() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(MultiQubitZZ::new(vec![], 0.0.into()));
+
+    let err = circuit_into_typst_str(&circuit, &RenderOptions::default()).unwrap_err();
+    assert!(matches!(
+        err,
+        RoqollageError::InvalidOperation { index: Some(2), .. }
+    ));
+}
+
+#[test]
+fn test_invalid_option() {
+    assert!(matches!(
+        LayoutMode::from_str("diagonal"),
+        Err(RoqollageError::InvalidOption { .. })
+    ));
+    assert!(matches!(
+        InitializationMode::from_str("ket"),
+        Err(RoqollageError::InvalidOption { .. })
+    ));
+}
+
+#[test]
+#[serial]
+fn test_typst_compile_error() {
+    let err = render_typst_str("#unknown-function()".to_owned(), None, None).unwrap_err();
+    match err {
+        RoqollageError::TypstCompileError { diagnostics } => {
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].range, Some(1..17));
+            assert_eq!(diagnostics[0].source.as_deref(), Some("unknown-function"));
+        }
+        _ => panic!("Expected a Typst compilation error, got {err:?}"),
+    }
+}
+
+#[test]
+#[serial]
+fn test_typst_compile_error_operation() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(RotateX::new(3, CalculatorFloat::from("x#y")));
+
+    let err = circuit_to_image(&circuit, None, &RenderOptions::default(), None).unwrap_err();
+    assert_eq!(err.index(), Some(1));
+    match &err {
+        RoqollageError::TypstCompileError { diagnostics } => {
+            assert_eq!(diagnostics[0].index, Some(1));
+            assert_eq!(
+                diagnostics[0].operation.as_deref(),
+                Some("RotateX on qubit 3")
+            );
+        }
+        _ => panic!("Expected a Typst compilation error, got {err:?}"),
+    }
+    assert!(err
+        .to_string()
+        .contains("\noperation #1 (RotateX on qubit 3): "));
+}
+
+#[test]
+#[serial]
+fn test_network_error() {
+    let config = BackendConfig {
+        package_source: PackageSource::from_str("http://127.0.0.1:9").unwrap(),
+        ..Default::default()
+    };
+    let err = render_typst_str(
+        "#import \"@preview/missing-package:0.0.1\": *".to_owned(),
+        None,
+        Some(&config),
+    )
+    .unwrap_err();
+    match err {
+        RoqollageError::NetworkError { msg } => {
+            assert!(msg.contains("http://127.0.0.1:9/preview/missing-package-0.0.1.tar.gz"));
+        }
+        _ => panic!("Expected a network error, got {err:?}"),
+    }
+}
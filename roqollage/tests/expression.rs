@@ -0,0 +1,46 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the Typst math of the symbolic parameters
+
+use qoqo_calculator::CalculatorFloat;
+use roqollage::{circuit_into_typst_str, RenderOptions};
+use roqoqo::{operations::*, Circuit};
+use test_case::test_case;
+
+#[test_case("theta", "theta"; "variable")]
+#[test_case("phi_1", "phi_1"; "subscript")]
+#[test_case("theta1", "theta_1"; "trailing digits")]
+#[test_case("drive_ab", "\"drive\"_\"ab\""; "unknown names")]
+#[test_case("sqrt(theta)/2", "sqrt(theta)/2"; "fraction")]
+#[test_case("2*pi*t", "2 pi t"; "implicit multiplication")]
+#[test_case("theta * 2e0", "2 theta"; "coefficient")]
+#[test_case("x * 2 * 3", "2 x dot 3"; "numbers product")]
+#[test_case("sin(phi)^2", "sin(phi)^2"; "power")]
+#[test_case("exp(-gamma*t)", "e^(-gamma t)"; "exponential")]
+#[test_case("(a + b)^(c - d)", "(a + b)^(c - d)"; "grouped power")]
+#[test_case("a + -b", "a - b"; "negated term")]
+#[test_case("-(a + b) * c", "-(a + b) c"; "negated sum")]
+#[test_case("a / (b * c)", "a/(b c)"; "product denominator")]
+#[test_case("asin(x) + atan2(y, x)", "arcsin(x) + \"atan2\"(y, x)"; "functions")]
+#[test_case("hypot(a, b)", "sqrt(a^2 + b^2)"; "hypot")]
+#[test_case("(gamma))", "(gamma))"; "unbalanced")]
+fn test_symbolic_parameter(expression: &str, typst_math: &str) {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::from(expression)));
+
+    let typst_str = circuit_into_typst_str(&circuit, &RenderOptions::default()).unwrap();
+    assert!(
+        typst_str.contains(&format!("\"Rx\"({typst_math})")),
+        "{typst_str}"
+    );
+}
@@ -15,7 +15,10 @@
 use ndarray::array;
 use num_complex::Complex64;
 use qoqo_calculator::CalculatorFloat;
-use roqollage::{add_gate, circuit_to_image};
+use roqollage::{
+    add_gate, circuit_into_quantikz_str, circuit_into_typst_str, circuit_to_image, Cell,
+    NumberFormat, RenderOptions, Wire,
+};
 use roqoqo::{operations::*, Circuit};
 use std::collections::HashMap;
 use std::f64::consts::PI;
@@ -132,9 +135,9 @@ use test_case::test_case;
 #[test_case(Operation::from(MultiQubitCNOT::new([0, 1, 2, 3].to_vec())); "MultiQubitCNOT")]
 #[test_case(Operation::from(QFT::new([0, 1, 2, 3].to_vec(), false, false)); "QFT")]
 fn test_add_gate(operation: Operation) {
-    let mut circuit_gates: Vec<Vec<String>> = Vec::new();
-    let mut bosonic_gates: Vec<Vec<String>> = Vec::new();
-    let mut classical_gates: Vec<Vec<String>> = Vec::new();
+    let mut circuit_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut bosonic_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut classical_gates: Vec<Vec<Cell>> = Vec::new();
     let mut circuit_lock: Vec<(usize, usize)> = Vec::new();
     let mut bosonic_lock: Vec<(usize, usize)> = Vec::new();
     let mut classical_lock: Vec<(usize, usize)> = Vec::new();
@@ -147,20 +150,13 @@ fn test_add_gate(operation: Operation) {
         &mut classical_lock,
         &operation,
         &roqollage::RenderPragmas::All,
-        3
+        NumberFormat::default(),
+        None
     )
     .is_ok());
     let mut circuit = roqoqo::Circuit::new();
     circuit += operation;
-    assert!(circuit_to_image(
-        &circuit,
-        None,
-        roqollage::RenderPragmas::All,
-        None,
-        None,
-        None
-    )
-    .is_ok())
+    assert!(circuit_to_image(&circuit, None, &RenderOptions::default(), None).is_ok())
 }
 
 #[test_case(Operation::from(PragmaStartDecompositionBlock::new(vec![], HashMap::new())); "PragmaStartDecompositionBlock")]
@@ -181,9 +177,9 @@ fn test_add_gate(operation: Operation) {
 #[test_case(Operation::from(MultiQubitCNOT::new([].to_vec())); "MultiQubitCNOT")]
 #[test_case(Operation::from(QFT::new([].to_vec(), false, false)); "QFT")]
 fn test_add_gate_errors(operation: Operation) {
-    let mut circuit_gates: Vec<Vec<String>> = Vec::new();
-    let mut bosonic_gates: Vec<Vec<String>> = Vec::new();
-    let mut classical_gates: Vec<Vec<String>> = Vec::new();
+    let mut circuit_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut bosonic_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut classical_gates: Vec<Vec<Cell>> = Vec::new();
     let mut circuit_lock: Vec<(usize, usize)> = Vec::new();
     let mut bosonic_lock: Vec<(usize, usize)> = Vec::new();
     let mut classical_lock: Vec<(usize, usize)> = Vec::new();
@@ -196,7 +192,8 @@ fn test_add_gate_errors(operation: Operation) {
         &mut classical_lock,
         &operation,
         &roqollage::RenderPragmas::All,
-        3
+        NumberFormat::default(),
+        None
     )
     .is_err(),);
 }
@@ -205,9 +202,9 @@ fn test_add_gate_errors(operation: Operation) {
 #[test_case(Operation::from(GateDefinition::new(vec![Operation::from(RotateX::new(0, CalculatorFloat::from("theta"))), Operation::from(RotateX::new(1, CalculatorFloat::PI))].into_iter().collect(), "test_gate".to_owned(), vec![0, 1], vec!["theta".to_owned()])); "GateDefinition")]
 #[test_case(Operation::from(CallDefinedGate::new("test".to_owned(), vec![0, 1], vec![CalculatorFloat::from("3.14")])); "CallDefinedGate")]
 fn test_add_gate_unstable(operation: Operation) {
-    let mut circuit_gates: Vec<Vec<String>> = Vec::new();
-    let mut bosonic_gates: Vec<Vec<String>> = Vec::new();
-    let mut classical_gates: Vec<Vec<String>> = Vec::new();
+    let mut circuit_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut bosonic_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut classical_gates: Vec<Vec<Cell>> = Vec::new();
     let mut circuit_lock: Vec<(usize, usize)> = Vec::new();
     let mut bosonic_lock: Vec<(usize, usize)> = Vec::new();
     let mut classical_lock: Vec<(usize, usize)> = Vec::new();
@@ -220,29 +217,22 @@ fn test_add_gate_unstable(operation: Operation) {
         &mut classical_lock,
         &operation,
         &roqollage::RenderPragmas::All,
-        3
+        NumberFormat::default(),
+        None
     )
     .is_ok());
     let mut circuit = roqoqo::Circuit::new();
     circuit += operation;
-    assert!(circuit_to_image(
-        &circuit,
-        None,
-        roqollage::RenderPragmas::All,
-        None,
-        None,
-        None
-    )
-    .is_ok());
+    assert!(circuit_to_image(&circuit, None, &RenderOptions::default(), None).is_ok());
 }
 
 #[cfg(feature = "unstable_operation_definition")]
 #[test_case(Operation::from(GateDefinition::new(vec![Operation::from(InputBit::new("ro".to_owned(), 0, true))].into_iter().collect(), "test_gate".to_owned(), vec![0, 1], vec!["theta".to_owned()])); "GateDefinition")]
 #[test_case(Operation::from(CallDefinedGate::new("test".to_owned(), vec![], vec![CalculatorFloat::from("3.14")])); "CallDefinedGate")]
 fn test_add_gate_unstable_errors(operation: Operation) {
-    let mut circuit_gates: Vec<Vec<String>> = Vec::new();
-    let mut bosonic_gates: Vec<Vec<String>> = Vec::new();
-    let mut classical_gates: Vec<Vec<String>> = Vec::new();
+    let mut circuit_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut bosonic_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut classical_gates: Vec<Vec<Cell>> = Vec::new();
     let mut circuit_lock: Vec<(usize, usize)> = Vec::new();
     let mut bosonic_lock: Vec<(usize, usize)> = Vec::new();
     let mut classical_lock: Vec<(usize, usize)> = Vec::new();
@@ -255,7 +245,138 @@ fn test_add_gate_unstable_errors(operation: Operation) {
         &mut classical_lock,
         &operation,
         &roqollage::RenderPragmas::All,
-        3
+        NumberFormat::default(),
+        None
     )
     .is_err(),);
 }
+
+#[test]
+fn test_add_gate_layout() {
+    let mut circuit_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut bosonic_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut classical_gates: Vec<Vec<Cell>> = Vec::new();
+    let mut circuit_lock: Vec<(usize, usize)> = Vec::new();
+    let mut bosonic_lock: Vec<(usize, usize)> = Vec::new();
+    let mut classical_lock: Vec<(usize, usize)> = Vec::new();
+    let operations = [
+        Operation::from(DefinitionBit::new("ro".to_owned(), 1, true)),
+        Operation::from(PragmaAnnotatedOp::new(
+            Hadamard::new(0).into(),
+            "slice \"gategroup\"".to_owned(),
+        )),
+        Operation::from(CNOT::new(0, 1)),
+        Operation::from(MeasureQubit::new(1, "ro".to_owned(), 0)),
+    ];
+    for operation in operations.iter() {
+        add_gate(
+            &mut circuit_gates,
+            &mut bosonic_gates,
+            &mut classical_gates,
+            &mut circuit_lock,
+            &mut bosonic_lock,
+            &mut classical_lock,
+            operation,
+            &roqollage::RenderPragmas::All,
+            NumberFormat::default(),
+            None,
+        )
+        .unwrap();
+    }
+    let group = Cell::Group {
+        n_wires: 1,
+        length: 1,
+        label: "slice \"gategroup\"".to_owned(),
+    };
+    assert!(circuit_gates[0].contains(&group));
+    assert!(circuit_gates[0].contains(&Cell::gate("H")));
+    assert!(circuit_gates[0].contains(&Cell::control(Wire::Qubit(1))));
+    assert!(circuit_gates[1].contains(&Cell::Meter {
+        target: Some(Wire::Classical(0))
+    }));
+    assert_eq!(
+        group.to_typst(Wire::Qubit(0), 2, 0),
+        r#"gategroup(1, 1, label: "slice \"gategroup\"", stroke: (dash: "dotted"))"#
+    );
+    assert_eq!(
+        Cell::Meter {
+            target: Some(Wire::Classical(0))
+        }
+        .to_typst(Wire::Qubit(1), 2, 1),
+        "meter(target: 2)"
+    );
+    assert_eq!(
+        Cell::control(Wire::Qubit(0)).to_typst(Wire::Qubit(1), 2, 0),
+        "ctrl(-1)"
+    );
+
+    let mut circuit = Circuit::new();
+    for operation in operations {
+        circuit += operation;
+    }
+    assert!(circuit_to_image(&circuit, None, &RenderOptions::default(), None).is_ok());
+}
+
+#[test_case(array![Complex64::new(0.0, 0.0), Complex64::new(1.0 / 2.0_f64.sqrt(), 0.0), Complex64::new(-1.0 / 2.0_f64.sqrt(), 0.0), Complex64::new(0.0, 0.0)], 8, r#""SetStatevector"\ 1/sqrt(2) |01> - 1/sqrt(2) |10>"#; "ket sum")]
+#[test_case(array![Complex64::new(1.0, 0.0), Complex64::new(0.0, 0.0)], 8, r#""SetStatevector"\ |0>"#; "basis state")]
+#[test_case(array![Complex64::new(0.5, 0.0), Complex64::new(0.5, 0.0), Complex64::new(0.5, 0.0), Complex64::new(-0.5, 0.0)], 2, r#""SetStatevector"\ 0.5 |00> + 0.5 |01> + dots"#; "truncated")]
+fn test_set_statevector(
+    statevector: ndarray::Array1<Complex64>,
+    max_state_size: usize,
+    label: &str,
+) {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(1));
+    circuit.add_operation(PragmaSetStateVector::new(statevector));
+    let number_format = NumberFormat {
+        max_state_size,
+        ..NumberFormat::default()
+    };
+
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            number_format,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains(label), "{typst_str}");
+}
+
+#[test_case(2, "mat(0.5, 0; 0, 0.5)"; "full")]
+#[test_case(1, "mat(0.5, dots.h; dots.v, dots.down)"; "truncated")]
+fn test_set_density_matrix(max_state_size: usize, matrix: &str) {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(PragmaSetDensityMatrix::new(array![
+        [Complex64::new(0.5, 0.0), Complex64::new(0.0, 0.0)],
+        [Complex64::new(0.0, 0.0), Complex64::new(0.5, 0.0)],
+    ]));
+    let number_format = NumberFormat {
+        max_state_size,
+        ..NumberFormat::default()
+    };
+
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            number_format,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(
+        typst_str.contains(&format!(r#""SetDensityMatrix"\ {matrix}"#)),
+        "{typst_str}"
+    );
+    let latex = circuit_into_quantikz_str(
+        &circuit,
+        &RenderOptions {
+            number_format,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(latex.contains("\\begin{pmatrix}"), "{latex}");
+}
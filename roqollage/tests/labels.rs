@@ -0,0 +1,138 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage custom wire labels
+
+use std::collections::HashMap;
+
+use qoqo_calculator::CalculatorFloat;
+use roqollage::{
+    circuit_into_quantikz_str, circuit_into_text_str, circuit_into_typst_str, InitializationMode,
+    RenderOptions, TextCharset, WireLabels,
+};
+use roqoqo::{operations::*, Circuit};
+
+fn labelled_circuit() -> (Circuit, WireLabels) {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(Squeezing::new(
+        0,
+        CalculatorFloat::ZERO,
+        CalculatorFloat::ZERO,
+    ));
+    circuit.add_operation(MeasureQubit::new(1, "ro".to_owned(), 0));
+    let wire_labels = WireLabels {
+        qubits: HashMap::from([(0, "anc".to_owned()), (1, "data_0".to_owned())]),
+        registers: HashMap::from([("ro".to_owned(), "readout".to_owned())]),
+        initial_states: HashMap::from([(0, "|+>".to_owned())]),
+        ..WireLabels::default()
+    };
+    (circuit, wire_labels)
+}
+
+#[test]
+fn test_default_labels() {
+    let (circuit, _) = labelled_circuit();
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            initialization_mode: Some(InitializationMode::Qubit),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains("lstick($q[0]$, label: \"Qubits\")"));
+    assert!(typst_str.contains("lstick($b[0]$, label: \"Bosons\")"));
+    assert!(typst_str.contains("lstick($ \"ro : \" $)"));
+
+    let typst_str = circuit_into_typst_str(&circuit, &RenderOptions::default()).unwrap();
+    assert!(typst_str.contains("lstick($|0>$, label: \"Qubits\")"));
+    assert!(typst_str.contains("lstick($|0>$, label: \"Bosons\")"));
+}
+
+#[test]
+fn test_typst_labels() {
+    let (circuit, wire_labels) = labelled_circuit();
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            initialization_mode: Some(InitializationMode::State),
+            wire_labels: wire_labels.clone(),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains("lstick($\"anc\": |+>$, label: \"Qubits\")"));
+    assert!(typst_str.contains("lstick($\"data\"_0: |0>$)"));
+    assert!(typst_str.contains("lstick($|0>$, label: \"Bosons\")"));
+    assert!(typst_str.contains("lstick($ \"readout : \" $)"));
+
+    let wire_labels = WireLabels {
+        modes: HashMap::from([(0, "a_1".to_owned())]),
+        ..wire_labels
+    };
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            initialization_mode: Some(InitializationMode::Qubit),
+            max_length: Some(2),
+            wire_labels: wire_labels.clone(),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains("lstick($\"anc\": |+>$, label: \"Qubits\")"));
+    assert!(typst_str.contains("lstick($\"data\"_0$)"));
+    assert!(typst_str.contains("lstick($a_1$, label: \"Bosons\")"));
+    assert!(typst_str.contains("lstick($···\"anc\"$)"));
+}
+
+#[test]
+fn test_text_labels() {
+    let (circuit, wire_labels) = labelled_circuit();
+    let text = circuit_into_text_str(
+        &circuit,
+        TextCharset::Ascii,
+        &RenderOptions {
+            initialization_mode: Some(InitializationMode::Qubit),
+            wire_labels: wire_labels.clone(),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    for label in ["anc: |+> ", "data_0 ", "b[0] ", "readout "] {
+        assert!(
+            lines.iter().any(|line| line.starts_with(label)),
+            "{label} missing in\n{text}"
+        );
+    }
+}
+
+#[test]
+fn test_quantikz_labels() {
+    let (circuit, wire_labels) = labelled_circuit();
+    let latex = circuit_into_quantikz_str(
+        &circuit,
+        &RenderOptions {
+            initialization_mode: Some(InitializationMode::Qubit),
+            wire_labels: wire_labels.clone(),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(latex.contains("\\lstick{$\\text{anc}: \\ket{+}$}"));
+    assert!(latex.contains("\\lstick{$b[0]$}"));
+    assert!(latex.contains("\\lstick{readout : }"));
+}
@@ -0,0 +1,140 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage quantikz representation
+
+use qoqo_calculator::CalculatorFloat;
+use roqollage::{
+    circuit_into_quantikz_str, InitializationMode, LayoutMode, NumberFormat, RenderOptions,
+    RenderPragmas,
+};
+use roqoqo::{operations::*, Circuit};
+
+#[test]
+fn test_quantikz_simple() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+
+    let latex = circuit_into_quantikz_str(&circuit, &RenderOptions::default()).unwrap();
+    assert_eq!(
+        latex,
+        "\\begin{quantikz}\n\
+         \\lstick{$\\ket{0}$} & \\gate{H} & \\ctrl{1} & \\\\\n\
+         \\lstick{$\\ket{0}$} & & \\targ{} &\n\
+         \\end{quantikz}\n"
+    );
+}
+
+#[test]
+fn test_quantikz_operations() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::from("theta")));
+    circuit.add_operation(InvSqrtPauliX::new(1));
+    circuit.add_operation(SWAP::new(2, 0));
+    circuit.add_operation(MultiQubitMS::new(vec![0, 1, 2], CalculatorFloat::PI));
+    circuit.add_operation(PragmaLoop::new(
+        CalculatorFloat::from(2.0),
+        [Operation::from(PauliX::new(0))].into_iter().collect(),
+    ));
+    circuit.add_operation(PragmaSetNumberOfMeasurements::new(10, "ro".to_owned()));
+    circuit.add_operation(MeasureQubit::new(0, "ro".to_owned(), 0));
+
+    let latex = circuit_into_quantikz_str(
+        &circuit,
+        &RenderOptions {
+            initialization_mode: Some(InitializationMode::Qubit),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    for command in [
+        "\\lstick{$q[0]$}",
+        "\\lstick{ro : }",
+        "\\setwiretype{c}",
+        "\\gate{\\text{Rx}(\\theta)}",
+        "\\gate{\\sqrt{X}^{\\dagger}}",
+        "\\swap{2}",
+        "\\targX{}",
+        "\\gate[3]{\\text{MultiQubitMS}(\\pi)}",
+        "\\gategroup[1,steps=1,style={dotted,rounded corners,inner xsep=2pt}]{Loop: 2 times}",
+        "\\slice",
+        "\\meter{} \\wire[d][3]{c}",
+        "\\control{}",
+    ] {
+        assert!(latex.contains(command), "{command} missing in\n{latex}");
+    }
+    assert_eq!(latex.matches("\\begin{quantikz}").count(), 1);
+
+    let latex = circuit_into_quantikz_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            max_length: Some(2),
+            number_format: NumberFormat {
+                rounding_accuracy: 3,
+                ..NumberFormat::default()
+            },
+            layout_mode: LayoutMode::Asap,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(latex.matches("\\begin{quantikz}").count() > 1);
+    assert!(latex.contains("\\rstick{$\\cdots$}"));
+    assert!(latex.contains("\\lstick{$\\cdots q[0]$}"));
+    assert!(!latex.contains("\\slice"));
+}
+
+#[test]
+fn test_quantikz_empty() {
+    assert_eq!(
+        circuit_into_quantikz_str(&Circuit::new(), &RenderOptions::default()).unwrap(),
+        "\\begin{quantikz}\n\\end{quantikz}\n"
+    );
+}
+
+#[test]
+fn test_quantikz_fractions() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::from("theta/2 + 1/(3*x)")));
+    circuit.add_operation(RotateZ::new(0, CalculatorFloat::from("(a+b)/c^2")));
+    circuit.add_operation(RotateY::new(0, CalculatorFloat::FRAC_PI_4 * 3.0));
+    circuit.add_operation(RotateX::new(0, CalculatorFloat::FRAC_1_SQRT_2));
+
+    let latex = circuit_into_quantikz_str(&circuit, &RenderOptions::default()).unwrap();
+    for command in [
+        "\\gate{\\text{Rx}(\\frac{\\theta}{2} + \\frac{1}{3 x})}",
+        "\\gate{\\text{Rz}(\\frac{a + b}{c^{2}})}",
+        "\\gate{\\text{Ry}(\\frac{3\\pi}{4})}",
+        "\\gate{\\text{Rx}(\\frac{1}{\\sqrt{2}})}",
+    ] {
+        assert!(latex.contains(command), "{command} missing in\n{latex}");
+    }
+    assert!(!latex.contains('/'), "{latex}");
+}
+
+#[test]
+fn test_quantikz_readout_slice() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(PragmaGetStateVector::new("ro".to_owned(), None));
+
+    let latex = circuit_into_quantikz_str(&circuit, &RenderOptions::default()).unwrap();
+    assert!(
+        latex.contains("\\ctrl{1} \\slice{$\\text{GetStateVector: ro}$}"),
+        "{latex}"
+    );
+    assert!(!latex.contains("\\gategroup"), "{latex}");
+    assert!(!latex.contains("\\gate{I}"), "{latex}");
+}
@@ -15,3 +15,36 @@ mod interface;
 
 #[cfg(test)]
 mod backend;
+
+#[cfg(test)]
+mod text;
+
+#[cfg(test)]
+mod latex;
+
+#[cfg(test)]
+mod program;
+
+#[cfg(test)]
+mod device;
+
+#[cfg(test)]
+mod timing;
+
+#[cfg(test)]
+mod diff;
+
+#[cfg(test)]
+mod statistics;
+
+#[cfg(test)]
+mod error;
+
+#[cfg(test)]
+mod expression;
+
+#[cfg(test)]
+mod number;
+
+#[cfg(test)]
+mod labels;
@@ -0,0 +1,205 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the format of the float parameters
+
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    str::FromStr,
+};
+
+use roqollage::{
+    circuit_into_quantikz_str, circuit_into_text_str, circuit_into_typst_str, AngleUnit,
+    ComplexStyle, FloatStyle, NumberFormat, RenderOptions, RoqollageError, TextCharset,
+};
+use roqoqo::{operations::*, Circuit};
+use test_case::test_case;
+
+/// Returns the Typst representation of a RotateX gate of the angle with the number format.
+fn rotate_x_typst(angle: f64, number_format: NumberFormat) -> String {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateX::new(0, angle.into()));
+    circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            number_format,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test_case(5.0 * PI / 8.0, "(5pi)/8"; "fraction of pi")]
+#[test_case(PI / 6.0, "pi/6"; "unit fraction of pi")]
+#[test_case(-7.0 * PI / 4.0, "-(7pi)/4"; "negative fraction of pi")]
+#[test_case(2.0 * PI, "2pi"; "multiple of pi")]
+#[test_case(1.0 / 3.0, "1/3"; "fraction")]
+#[test_case(0.5, "0.5"; "exact decimal")]
+#[test_case(3.0_f64.sqrt() / 2.0, "sqrt(3)/2"; "square root")]
+#[test_case(1.0 / 2.0_f64.sqrt(), "1/sqrt(2)"; "inverse square root")]
+#[test_case(1.963, "1.963"; "decimal")]
+fn test_symbolic_float(angle: f64, typst_math: &str) {
+    let typst_str = rotate_x_typst(angle, NumberFormat::default());
+    assert!(
+        typst_str.contains(&format!("\"Rx\"({typst_math})")),
+        "{typst_str}"
+    );
+}
+
+#[test_case(FloatStyle::Decimal, 3, 16, "1.963"; "decimal")]
+#[test_case(FloatStyle::Degrees, 3, 16, "112.5°"; "degrees")]
+#[test_case(FloatStyle::Symbolic, 3, 4, "1.963"; "max denominator")]
+#[test_case(FloatStyle::Decimal, 1, 16, "2.0"; "rounding accuracy")]
+fn test_number_format(
+    float_style: FloatStyle,
+    rounding_accuracy: usize,
+    max_denominator: u64,
+    typst_math: &str,
+) {
+    let number_format = NumberFormat {
+        rounding_accuracy,
+        float_style,
+        max_denominator,
+        ..NumberFormat::default()
+    };
+    let typst_str = rotate_x_typst(5.0 * PI / 8.0, number_format);
+    assert!(
+        typst_str.contains(&format!("\"Rx\"({typst_math})")),
+        "{typst_str}"
+    );
+}
+
+#[test_case(0.05, "0.05"; "hundredths")]
+#[test_case(0.006, "0.006"; "thousandths")]
+#[test_case(0.005, "\"5e-3\""; "scientific threshold")]
+#[test_case(-0.05, "-0.05"; "negative hundredths")]
+fn test_small_decimal(angle: f64, typst_math: &str) {
+    let number_format = NumberFormat {
+        float_style: FloatStyle::Decimal,
+        ..NumberFormat::default()
+    };
+    let typst_str = rotate_x_typst(angle, number_format);
+    assert!(
+        typst_str.contains(&format!("\"Rx\"({typst_math})")),
+        "{typst_str}"
+    );
+}
+
+#[test]
+fn test_number_format_text() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateX::new(0, (PI / 2.0).into()));
+    let number_format = NumberFormat {
+        float_style: FloatStyle::Degrees,
+        ..NumberFormat::default()
+    };
+
+    let text = circuit_into_text_str(
+        &circuit,
+        TextCharset::Ascii,
+        &RenderOptions {
+            number_format,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(text.contains("Rx(90deg)"), "{text}");
+    let latex = circuit_into_quantikz_str(
+        &circuit,
+        &RenderOptions {
+            number_format,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(latex.contains("90^{\\circ}"), "{latex}");
+}
+
+#[test_case(Operation::from(RotateX::new(0, FRAC_PI_2.into())), AngleUnit::Radians, "\"Rx\"(pi/2)"; "radians")]
+#[test_case(Operation::from(RotateX::new(0, FRAC_PI_2.into())), AngleUnit::Degrees, "\"Rx\"(90°)"; "degrees")]
+#[test_case(Operation::from(RotateZ::new(0, (-PI / 4.0).into())), AngleUnit::Turns, "\"Rz\"(-1/8 \"tr\")"; "turns")]
+#[test_case(Operation::from(RotateX::new(0, 0.3.into())), AngleUnit::Degrees, "\"Rx\"(17.189°)"; "decimal degrees")]
+#[test_case(Operation::from(RotateX::new(0, "theta".into())), AngleUnit::Degrees, "\"Rx\"(theta)"; "symbolic angle")]
+#[test_case(Operation::from(PhaseShiftState1::new(0, (PI / 3.0).into())), AngleUnit::Degrees, "\"p1\"(60°)"; "PhaseShiftState1")]
+#[test_case(Operation::from(ControlledPhaseShift::new(0, 1, PI.into())), AngleUnit::Turns, "\"PhaseShift\"(1/2 \"tr\")"; "ControlledPhaseShift")]
+#[test_case(Operation::from(GPi::new(0, FRAC_PI_2.into())), AngleUnit::Degrees, "\"GPi\"(90°)"; "GPi")]
+#[test_case(Operation::from(RotateXY::new(0, PI.into(), FRAC_PI_2.into())), AngleUnit::Degrees, "\"Rxy\"(180°,90°)"; "RotateXY")]
+#[test_case(Operation::from(PMInteraction::new(0, 1, FRAC_PI_2.into())), AngleUnit::Degrees, "\"PMInteraction\"(pi/2)"; "not an angle")]
+fn test_angle_unit(operation: Operation, angle_unit: AngleUnit, typst_math: &str) {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(operation);
+    let number_format = NumberFormat {
+        angle_unit,
+        ..NumberFormat::default()
+    };
+
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            number_format,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains(typst_math), "{typst_str}");
+}
+
+#[test_case(Operation::from(Bogoliubov::new(0, 1, 0.5.into(), (-0.5).into())), ComplexStyle::Cartesian, "\"Bogoliubov\"(0.5 - 0.5 i)"; "cartesian")]
+#[test_case(Operation::from(ComplexPMInteraction::new(0, 1, 0.0.into(), 1.0.into())), ComplexStyle::Cartesian, "\"ComplexPMInteraction\"(i)"; "imaginary")]
+#[test_case(Operation::from(Bogoliubov::new(0, 1, "theta".into(), 0.0.into())), ComplexStyle::Cartesian, "\"Bogoliubov\"(theta)"; "symbolic real")]
+#[test_case(Operation::from(Bogoliubov::new(0, 1, 0.5.into(), "theta".into())), ComplexStyle::Polar, "\"Bogoliubov\"(0.5 + (theta) i)"; "symbolic imaginary")]
+#[test_case(Operation::from(SingleQubitGate::new(0, 1.0.into(), 0.0.into(), 0.0.into(), 0.0.into(), 0.0.into())), ComplexStyle::Cartesian, "U(1.0,0,0)"; "SingleQubitGate")]
+#[test_case(Operation::from(Bogoliubov::new(0, 1, 0.5.into(), 0.5.into())), ComplexStyle::Polar, "\"Bogoliubov\"(1/sqrt(2) dot e^(i pi/4))"; "polar")]
+#[test_case(Operation::from(ComplexPMInteraction::new(0, 1, 0.0.into(), (-2.0).into())), ComplexStyle::Polar, "\"ComplexPMInteraction\"(2.0 dot e^(-i pi/2))"; "polar negative phase")]
+fn test_complex_style(operation: Operation, complex_style: ComplexStyle, typst_math: &str) {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(operation);
+    let number_format = NumberFormat {
+        complex_style,
+        ..NumberFormat::default()
+    };
+
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            number_format,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains(typst_math), "{typst_str}");
+}
+
+#[test]
+fn test_float_style_from_str() {
+    assert_eq!(
+        FloatStyle::from_str("Degrees").unwrap(),
+        FloatStyle::Degrees
+    );
+    assert!(matches!(
+        FloatStyle::from_str("roman"),
+        Err(RoqollageError::InvalidOption { .. })
+    ));
+    assert_eq!(AngleUnit::from_str("turns").unwrap(), AngleUnit::Turns);
+    assert!(matches!(
+        AngleUnit::from_str("gradians"),
+        Err(RoqollageError::InvalidOption { .. })
+    ));
+    assert_eq!(
+        ComplexStyle::from_str("Polar").unwrap(),
+        ComplexStyle::Polar
+    );
+    assert!(matches!(
+        ComplexStyle::from_str("exponential"),
+        Err(RoqollageError::InvalidOption { .. })
+    ));
+}
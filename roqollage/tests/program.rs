@@ -0,0 +1,145 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage quantum program representation
+
+use std::collections::HashMap;
+
+use qoqo_calculator::CalculatorFloat;
+use roqollage::{
+    quantum_program_into_typst_str, quantum_program_to_image, InitializationMode, RenderOptions,
+    RenderPragmas,
+};
+use roqoqo::{
+    measurements::{
+        CheatedPauliZProduct, CheatedPauliZProductInput, ClassicalRegister, PauliZProduct,
+        PauliZProductInput,
+    },
+    operations::*,
+    Circuit, QuantumProgram,
+};
+use serial_test::serial;
+
+fn create_pauli_z_product_program() -> QuantumProgram {
+    let mut constant_circuit = Circuit::new();
+    constant_circuit.add_operation(Hadamard::new(0));
+    constant_circuit.add_operation(RotateX::new(1, CalculatorFloat::from("theta")));
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(MeasureQubit::new(0, "ro".to_owned(), 0));
+    circuit.add_operation(MeasureQubit::new(1, "ro".to_owned(), 1));
+
+    let mut input = PauliZProductInput::new(2, false);
+    let first = input.add_pauliz_product("ro".to_owned(), vec![0]).unwrap();
+    let second = input
+        .add_pauliz_product("ro".to_owned(), vec![0, 1])
+        .unwrap();
+    input
+        .add_linear_exp_val(
+            "energy".to_owned(),
+            HashMap::from([(first, 0.5), (second, -2.0)]),
+        )
+        .unwrap();
+    input
+        .add_symbolic_exp_val("ratio".to_owned(), CalculatorFloat::from("theta / 2"))
+        .unwrap();
+    QuantumProgram::PauliZProduct {
+        measurement: PauliZProduct {
+            constant_circuit: Some(constant_circuit),
+            circuits: vec![circuit],
+            input,
+        },
+        input_parameter_names: vec!["theta".to_owned()],
+    }
+}
+
+#[test]
+fn test_pauli_z_product_str() {
+    let typst_str = quantum_program_into_typst_str(
+        &create_pauli_z_product_program(),
+        &RenderOptions::default(),
+    )
+    .unwrap();
+    assert!(typst_str.starts_with("#set page"));
+    assert!(typst_str.contains("*PauliZProduct measurement*"));
+    assert!(typst_str.contains(r#"Input parameters: #raw("theta")"#));
+    assert!(typst_str.contains("*Constant circuit*"));
+    assert!(typst_str.contains("*Measurement circuit 0*"));
+    assert!(!typst_str.contains("*Measurement circuit 1*"));
+    assert!(
+        typst_str.contains(r#"- Readout #raw("ro"): $ p_(0) = Z_(0) $, $ p_(1) = Z_(0) Z_(1) $"#)
+    );
+    assert!(typst_str.contains(r#"- Expectation value #raw("energy"): $ 0.5 p_(0) - 2.0 p_(1) $"#));
+    assert!(typst_str.contains(r#"- Expectation value #raw("ratio"): $ theta/2 $"#));
+    assert_eq!(typst_str.matches("quantum-circuit(").count(), 2);
+}
+
+#[test]
+fn test_other_measurements_str() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 1, true));
+    circuit.add_operation(MeasureQubit::new(0, "ro".to_owned(), 0));
+
+    let mut input = CheatedPauliZProductInput::new();
+    let index = input.add_pauliz_product("ro".to_owned());
+    input
+        .add_linear_exp_val("z".to_owned(), HashMap::from([(index, 1.0)]))
+        .unwrap();
+    let program = QuantumProgram::CheatedPauliZProduct {
+        measurement: CheatedPauliZProduct {
+            constant_circuit: None,
+            circuits: vec![circuit.clone(), Circuit::new()],
+            input,
+        },
+        input_parameter_names: vec![],
+    };
+    let typst_str = quantum_program_into_typst_str(
+        &program,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            initialization_mode: Some(InitializationMode::Qubit),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains("*CheatedPauliZProduct measurement*"));
+    assert!(typst_str.contains("Input parameters: none"));
+    assert!(!typst_str.contains("*Constant circuit*"));
+    assert!(typst_str.contains("*Measurement circuit 1*"));
+    assert!(typst_str.contains("_Empty circuit_"));
+    assert!(typst_str.contains(r#"- $ p_(0) $ = readout #raw("ro")"#));
+
+    let program = QuantumProgram::ClassicalRegister {
+        measurement: ClassicalRegister {
+            constant_circuit: None,
+            circuits: vec![circuit],
+        },
+        input_parameter_names: vec!["theta".to_owned(), "phi".to_owned()],
+    };
+    let typst_str = quantum_program_into_typst_str(&program, &RenderOptions::default()).unwrap();
+    assert!(typst_str.contains("*ClassicalRegister measurement*"));
+    assert!(typst_str.contains(r#"Input parameters: #raw("theta"), #raw("phi")"#));
+    assert!(typst_str.contains("*Measurement input*"));
+}
+
+#[test]
+#[serial]
+fn test_quantum_program_image() {
+    assert!(quantum_program_to_image(
+        &create_pauli_z_product_program(),
+        None,
+        &RenderOptions::default(),
+        None
+    )
+    .is_ok());
+}
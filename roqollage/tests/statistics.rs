@@ -0,0 +1,150 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage circuit statistics
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use qoqo_calculator::CalculatorFloat;
+use roqollage::{
+    circuit_into_statistics, circuit_into_typst_str, CircuitStatistics, LayoutMode, RenderOptions,
+    RenderPragmas,
+};
+use roqoqo::{operations::*, Circuit};
+
+fn statistics_circuit() -> Circuit {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(Hadamard::new(1));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(RotateX::new(1, CalculatorFloat::from("theta + 2 * phi")));
+    circuit.add_operation(PragmaSleep::new(vec![0], CalculatorFloat::from(1.0)));
+    circuit.add_operation(MeasureQubit::new(0, "ro".to_owned(), 0));
+    circuit.add_operation(MeasureQubit::new(1, "ro".to_owned(), 1));
+    circuit
+}
+
+#[test]
+fn test_statistics() {
+    let circuit = statistics_circuit();
+    let statistics = circuit_into_statistics(&circuit, RenderPragmas::None, None).unwrap();
+    // The Hadamard, CNOT, RotateX and MeasureQubit operations on qubit 1 are in distinct columns.
+    assert!(statistics.depth >= 4);
+    assert_eq!(
+        CircuitStatistics {
+            depth: 0,
+            ..statistics.clone()
+        },
+        CircuitStatistics {
+            number_qubits: 2,
+            number_modes: 0,
+            number_classical_registers: 1,
+            depth: 0,
+            gate_counts: BTreeMap::from([
+                ("CNOT".to_owned(), 1),
+                ("Hadamard".to_owned(), 2),
+                ("MeasureQubit".to_owned(), 2),
+                ("RotateX".to_owned(), 1),
+            ]),
+            two_qubit_gate_count: 1,
+            measurement_count: 2,
+            symbolic_parameters: BTreeSet::from(["phi".to_owned(), "theta".to_owned()]),
+            hidden_pragmas: BTreeMap::from([("PragmaSleep".to_owned(), 1)]),
+        }
+    );
+
+    let compact =
+        circuit_into_statistics(&circuit, RenderPragmas::None, Some(LayoutMode::Asap)).unwrap();
+    assert!(compact.depth <= statistics.depth);
+    assert_eq!(compact.gate_counts, statistics.gate_counts);
+
+    let all_pragmas = circuit_into_statistics(&circuit, RenderPragmas::All, None).unwrap();
+    assert!(all_pragmas.hidden_pragmas.is_empty());
+    let partial = circuit_into_statistics(
+        &circuit,
+        RenderPragmas::Partial(vec!["PragmaSleep".to_owned()]),
+        None,
+    )
+    .unwrap();
+    assert!(partial.hidden_pragmas.is_empty());
+}
+
+#[test]
+fn test_statistics_nested_circuits() {
+    let mut body = Circuit::new();
+    body.add_operation(CNOT::new(0, 1));
+    body.add_operation(RotateZ::new(1, CalculatorFloat::from("gamma")));
+    let mut conditional_body = Circuit::new();
+    conditional_body.add_operation(PauliX::new(0));
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 1, true));
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(PragmaLoop::new(CalculatorFloat::from(3), body));
+    circuit.add_operation(MeasureQubit::new(0, "ro".to_owned(), 0));
+    circuit.add_operation(PragmaConditional::new("ro".to_owned(), 0, conditional_body));
+
+    let statistics = circuit_into_statistics(&circuit, RenderPragmas::All, None).unwrap();
+    assert_eq!(
+        statistics.gate_counts,
+        BTreeMap::from([
+            ("CNOT".to_owned(), 1),
+            ("Hadamard".to_owned(), 1),
+            ("MeasureQubit".to_owned(), 1),
+            ("PauliX".to_owned(), 1),
+            ("RotateZ".to_owned(), 1),
+        ])
+    );
+    assert_eq!(statistics.two_qubit_gate_count, 1);
+    assert_eq!(
+        statistics.symbolic_parameters,
+        BTreeSet::from(["gamma".to_owned()])
+    );
+    let hidden = circuit_into_statistics(&circuit, RenderPragmas::None, None).unwrap();
+    assert_eq!(hidden.gate_counts, statistics.gate_counts);
+    assert_eq!(
+        hidden.hidden_pragmas,
+        BTreeMap::from([
+            ("PragmaConditional".to_owned(), 1),
+            ("PragmaLoop".to_owned(), 1),
+        ])
+    );
+}
+
+#[test]
+fn test_statistics_table() {
+    let circuit = statistics_circuit();
+    let typst_str = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            statistics: true,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(typst_str.contains("[*Circuit statistics*]"));
+    assert!(typst_str.contains("[Qubits], [2],"));
+    assert!(typst_str.contains("[Gates], [#raw(\"CNOT\"): 1, #raw(\"Hadamard\"): 2,"));
+    assert!(typst_str.contains("[Symbolic parameters], [#raw(\"phi\"), #raw(\"theta\")],"));
+    assert!(typst_str.contains("[Hidden pragmas], [#raw(\"PragmaSleep\"): 1],"));
+
+    let without_table = circuit_into_typst_str(
+        &circuit,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(!without_table.contains("Circuit statistics"));
+}
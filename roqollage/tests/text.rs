@@ -0,0 +1,118 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage text representation
+
+use std::str::FromStr;
+
+use qoqo_calculator::CalculatorFloat;
+use roqollage::{
+    circuit_into_text_str, InitializationMode, LayoutMode, NumberFormat, RenderOptions,
+    RenderPragmas, TextCharset,
+};
+use roqoqo::{operations::*, Circuit};
+
+#[test]
+fn test_text_ascii() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+
+    let text =
+        circuit_into_text_str(&circuit, TextCharset::Ascii, &RenderOptions::default()).unwrap();
+    assert_eq!(text, "|0> -|H|---*--\n           |\n|0> ------(+)-");
+}
+
+#[test]
+fn test_text_unicode() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(RotateX::new(1, CalculatorFloat::from("theta")));
+    circuit.add_operation(InvSqrtPauliX::new(2));
+    circuit.add_operation(Toffoli::new(0, 2, 1));
+    circuit.add_operation(SWAP::new(0, 2));
+    circuit.add_operation(PragmaLoop::new(
+        CalculatorFloat::from(2.0),
+        [Operation::from(PauliX::new(0))].into_iter().collect(),
+    ));
+    circuit.add_operation(PragmaSetNumberOfMeasurements::new(10, "ro".to_owned()));
+    circuit.add_operation(MeasureQubit::new(0, "ro".to_owned(), 0));
+    circuit.add_operation(MeasureQubit::new(1, "ro".to_owned(), 1));
+
+    let text = circuit_into_text_str(
+        &circuit,
+        TextCharset::Unicode,
+        &RenderOptions {
+            initialization_mode: Some(InitializationMode::Qubit),
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines.iter().any(|line| line.starts_with("q[0] ")));
+    assert!(lines.iter().any(|line| line.starts_with("ro   ")));
+    for symbol in [
+        "┤H├",
+        "θ",
+        "√(X)†",
+        "●",
+        "⊕",
+        "╳",
+        "┤M├",
+        "║",
+        "═",
+        "┊",
+        "┆",
+    ] {
+        assert!(text.contains(symbol), "{symbol} missing in\n{text}");
+    }
+    assert!(text.contains("Loop: 2 times"));
+    assert!(text.contains("Measurements n=10"));
+
+    let ascii = circuit_into_text_str(
+        &circuit,
+        TextCharset::Ascii,
+        &RenderOptions {
+            render_pragmas: RenderPragmas::None,
+            number_format: NumberFormat {
+                rounding_accuracy: 2,
+                ..NumberFormat::default()
+            },
+            layout_mode: LayoutMode::Asap,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(ascii.is_ascii());
+    assert!(!ascii.contains("Measurements"));
+    assert!(ascii.contains("theta"));
+}
+
+#[test]
+fn test_text_empty() {
+    assert_eq!(
+        circuit_into_text_str(
+            &Circuit::new(),
+            TextCharset::Unicode,
+            &RenderOptions::default()
+        )
+        .unwrap(),
+        ""
+    );
+    assert_eq!(TextCharset::from_str("ASCII").unwrap(), TextCharset::Ascii);
+    assert_eq!(
+        TextCharset::from_str("unicode").unwrap(),
+        TextCharset::Unicode
+    );
+    assert!(TextCharset::from_str("latin1").is_err());
+}
@@ -0,0 +1,85 @@
+// Copyright © 2021-2024 HQS Quantum Simulations GmbH. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the
+// License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either
+// express or implied. See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Testing the roqollage timing representation
+
+use std::collections::HashMap;
+
+use qoqo_calculator::CalculatorFloat;
+use roqollage::{circuit_into_timing_typst_str, circuit_timing_to_image};
+use roqoqo::{devices::AllToAllDevice, operations::*, Circuit};
+
+#[test]
+fn test_timing_durations() {
+    let mut circuit = Circuit::new();
+    circuit.add_operation(DefinitionBit::new("ro".to_owned(), 2, true));
+    circuit.add_operation(Hadamard::new(0));
+    circuit.add_operation(CNOT::new(0, 1));
+    circuit.add_operation(PragmaSleep::new(vec![1], 3.0.into()));
+    circuit.add_operation(RotateX::new(1, 0.5.into()));
+    circuit.add_operation(PragmaSetNumberOfMeasurements::new(10, "ro".to_owned()));
+    let durations: HashMap<String, f64> = [("Hadamard".to_owned(), 1.0), ("CNOT".to_owned(), 2.0)]
+        .into_iter()
+        .collect();
+
+    let typst_str =
+        circuit_into_timing_typst_str(&circuit, None, &durations, Some(0.5), None).unwrap();
+    // The Hadamard gate, the two boxes of the CNOT, the sleep and the rotation.
+    assert_eq!(typst_str.matches("rect(").count(), 5);
+    assert!(typst_str.contains("[$ H $]"));
+    assert!(typst_str.contains("text(size: 6pt)[#\"CNOT\"]"));
+    assert!(typst_str.contains("fill: gray.lighten(60%)"));
+    assert!(typst_str.contains("text(size: 6pt)[Sleep]"));
+    assert!(typst_str.contains("$q[1]$"));
+    assert!(typst_str.contains("[Time]"));
+    assert!(typst_str.contains("text(size: 6pt)[$0$]"));
+    // The Hadamard gate starts at the origin and the CNOT after one time unit.
+    assert!(typst_str.contains("dx: 40.0pt, dy: 6.0pt, rect(width: 61.5pt"));
+    assert!(typst_str.contains("dx: 101.5pt, dy: 6.0pt, rect(width: 123.1pt"));
+
+    let image =
+        circuit_timing_to_image(&circuit, Some(1.0), None, &durations, Some(0.5), None, None);
+    assert!(image.is_ok());
+}
+
+#[test]
+fn test_timing_device() {
+    let device = AllToAllDevice::new(2, &["RotateZ".to_owned()], &["CNOT".to_owned()], 0.5);
+    let mut circuit = Circuit::new();
+    circuit.add_operation(RotateZ::new(0, 0.5.into()));
+    circuit.add_operation(CNOT::new(1, 0));
+
+    assert!(
+        circuit_into_timing_typst_str(&circuit, Some(&device), &HashMap::new(), None, None).is_ok()
+    );
+
+    circuit.add_operation(RotateX::new(0, 0.5.into()));
+    assert!(
+        circuit_into_timing_typst_str(&circuit, Some(&device), &HashMap::new(), None, None)
+            .is_err()
+    );
+    assert!(circuit_into_timing_typst_str(
+        &circuit,
+        Some(&device),
+        &HashMap::new(),
+        Some(1.0),
+        None
+    )
+    .is_ok());
+
+    let mut symbolic_sleep = Circuit::new();
+    symbolic_sleep.add_operation(PragmaSleep::new(vec![0], CalculatorFloat::from("t")));
+    assert!(
+        circuit_into_timing_typst_str(&symbolic_sleep, None, &HashMap::new(), Some(1.0), None)
+            .is_err()
+    );
+}